@@ -0,0 +1,95 @@
+//! A minimal MCP server over stdio for exercising `ai-dlc-cli mcp probe`.
+//!
+//! It answers `initialize` and lists two tools, a prompt and a resource. One
+//! flag makes it misbehave the way broken servers do:
+//!
+//! - `--hang`: never answer, to trigger the probe's timeout;
+//! - `--garbage`: write something other than JSON-RPC to stdout;
+//! - `--error`: refuse `initialize` with a JSON-RPC error;
+//! - `--exit`: exit at once with a message on stderr;
+//! - `--noisy`: log a megabyte to stderr before answering, more than a pipe
+//!   holds, then behave.
+//!
+//! ```bash
+//! cargo build --examples
+//! ai-dlc-cli mcp add fake -p claude -- target/debug/examples/fake_mcp_server
+//! ai-dlc-cli mcp probe fake
+//! ```
+
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+
+fn main() -> anyhow::Result<()> {
+    let mode = std::env::args().nth(1).unwrap_or_default();
+    if mode == "--exit" {
+        eprintln!("fake_mcp_server: configuration file not found");
+        std::process::exit(1);
+    }
+    if mode == "--noisy" {
+        let mut stderr = std::io::stderr().lock();
+        for line in 0..16_384 {
+            writeln!(
+                stderr,
+                "fake_mcp_server: debug line {line:>5} {}",
+                "-".repeat(48)
+            )?;
+        }
+    }
+
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let request: Value = serde_json::from_str(&line?)?;
+        // Notifications carry no id and get no answer.
+        let Some(id) = request.get("id").cloned() else {
+            continue;
+        };
+        let method = request["method"].as_str().unwrap_or_default();
+        let response = match (mode.as_str(), method) {
+            ("--hang", _) => continue,
+            ("--garbage", _) => {
+                writeln!(stdout, "Starting server on port 8080...")?;
+                stdout.flush()?;
+                continue;
+            }
+            ("--error", "initialize") => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32603, "message": "missing API key" },
+            }),
+            _ => match result(method) {
+                Some(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                None => json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("unknown method {method}") },
+                }),
+            },
+        };
+        writeln!(stdout, "{response}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn result(method: &str) -> Option<Value> {
+    Some(match method {
+        "initialize" => json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": { "tools": {}, "prompts": {}, "resources": {} },
+            "serverInfo": { "name": "fake-mcp-server", "version": "0.1.0" },
+        }),
+        "tools/list" => json!({
+            "tools": [
+                { "name": "echo", "inputSchema": { "type": "object" } },
+                { "name": "add", "inputSchema": { "type": "object" } },
+            ],
+        }),
+        "prompts/list" => json!({ "prompts": [{ "name": "greet" }] }),
+        "resources/list" => json!({
+            "resources": [{ "uri": "fake://readme", "name": "readme" }],
+        }),
+        "ping" => json!({}),
+        _ => return None,
+    })
+}
@@ -0,0 +1,510 @@
+use anyhow::Context;
+use clap::Parser;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::frontmatter;
+use crate::fsutil;
+use crate::manifest::{AGENTS_GROUP, COMMANDS_GROUP};
+use crate::plan::{OutputFormat, slash_path};
+use crate::render::TEMPLATE_SUFFIX;
+use crate::templates::{TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+/// Where playbooks are looked for when none are given: the repository's own
+/// workflows and those scaffolded for Claude.
+const PLAYBOOK_DIRS: &[&str] = &["templates/workflows", ".claude/workflows"];
+
+/// The template tree as laid out in a checkout of this repository, which
+/// playbooks may point into.
+const TEMPLATES_PREFIX: &str = "templates/";
+
+/// Slash commands built into the assistants rather than defined by files.
+const BUILTIN_COMMANDS: &[&str] = &[
+    "add-dir",
+    "agents",
+    "bug",
+    "clear",
+    "compact",
+    "config",
+    "context",
+    "cost",
+    "doctor",
+    "export",
+    "help",
+    "hooks",
+    "ide",
+    "init",
+    "login",
+    "logout",
+    "mcp",
+    "memory",
+    "model",
+    "permissions",
+    "resume",
+    "review",
+    "rewind",
+    "status",
+    "statusline",
+    "todos",
+    "upgrade",
+    "usage",
+    "vim",
+];
+
+#[derive(Parser, Debug)]
+pub struct CheckRefsArgs {
+    /// Playbook files or directories of them. Defaults to templates/workflows
+    /// and .claude/workflows.
+    paths: Vec<PathBuf>,
+    /// Output format for the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum RefKind {
+    Command,
+    Agent,
+    Path,
+}
+
+impl RefKind {
+    fn label(self) -> &'static str {
+        match self {
+            RefKind::Command => "slash command",
+            RefKind::Agent => "agent",
+            RefKind::Path => "path",
+        }
+    }
+}
+
+/// A reference in a playbook that nothing resolves.
+#[derive(Serialize, Debug)]
+struct Unresolved {
+    path: String,
+    line: usize,
+    kind: RefKind,
+    reference: String,
+}
+
+#[derive(Serialize, Debug)]
+struct RefsReport {
+    playbooks: usize,
+    references: usize,
+    unresolved: Vec<Unresolved>,
+}
+
+/// Every command, agent and asset path a playbook may refer to, from the
+/// files installed in the repository and the templates.
+#[derive(Default)]
+struct Assets {
+    /// Commands as invoked, without the leading `/`: `folder:command`.
+    commands: BTreeSet<String>,
+    agents: BTreeSet<String>,
+    /// Template paths as `<provider>/<source>`, and where they install.
+    paths: BTreeSet<String>,
+    /// First components of every install destination, such as `.claude`;
+    /// paths under them are asset references.
+    roots: BTreeSet<String>,
+}
+
+pub fn handle_check_refs(args: CheckRefsArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+    let assets = Assets::collect(root, &templates)?;
+
+    let playbooks = playbooks(root, &args.paths)?;
+    if playbooks.is_empty() {
+        anyhow::bail!(
+            "No playbooks found; pass playbook files or directories, or add them under {}.",
+            PLAYBOOK_DIRS.join(" or ")
+        );
+    }
+
+    let mut report = RefsReport {
+        playbooks: playbooks.len(),
+        references: 0,
+        unresolved: Vec::new(),
+    };
+    for playbook in &playbooks {
+        let text = std::fs::read_to_string(playbook)
+            .with_context(|| format!("Failed to read playbook: {:?}", playbook))?;
+        let shown = playbook
+            .strip_prefix(root)
+            .map(slash_path)
+            .unwrap_or_else(|_| playbook.display().to_string());
+        for (index, line) in text.lines().enumerate() {
+            for (kind, reference) in references(line) {
+                if kind == RefKind::Path && !assets.is_asset_path(reference) {
+                    continue;
+                }
+                report.references += 1;
+                if !assets.resolves(root, kind, reference) {
+                    report.unresolved.push(Unresolved {
+                        path: shown.clone(),
+                        line: index + 1,
+                        kind,
+                        reference: reference.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            for entry in &report.unresolved {
+                println!(
+                    "{}:{}: unresolved {} {}",
+                    entry.path,
+                    entry.line,
+                    entry.kind.label(),
+                    entry.reference
+                );
+            }
+            if !report.unresolved.is_empty() {
+                println!();
+            }
+            println!(
+                "{} playbook(s), {} reference(s): {} unresolved",
+                report.playbooks,
+                report.references,
+                report.unresolved.len()
+            );
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if !report.unresolved.is_empty() {
+        anyhow::bail!(
+            "{} playbook reference(s) do not resolve",
+            report.unresolved.len()
+        );
+    }
+    Ok(())
+}
+
+/// The Markdown files named by `paths`, or found in the default playbook
+/// directories.
+fn playbooks(root: &Path, paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    let defaults: Vec<PathBuf>;
+    let paths = if paths.is_empty() {
+        defaults = PLAYBOOK_DIRS.iter().map(|dir| root.join(dir)).collect();
+        &defaults
+    } else {
+        for path in paths {
+            if !path.exists() {
+                anyhow::bail!("Playbook {:?} does not exist.", path);
+            }
+        }
+        paths
+    };
+
+    let mut playbooks = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            for file in fsutil::walk_files(path, "")? {
+                if file.ends_with(".md") {
+                    playbooks.push(path.join(file));
+                }
+            }
+        } else if path.is_file() {
+            playbooks.push(path.clone());
+        }
+    }
+    Ok(playbooks)
+}
+
+impl Assets {
+    fn collect(root: &Path, templates: &Templates) -> anyhow::Result<Self> {
+        let mut assets = Assets::default();
+        for info in templates.available_providers()? {
+            let provider = info.name;
+            let Some(manifest) = templates.manifest(&provider)? else {
+                continue;
+            };
+            for mapping in &manifest.mappings {
+                if let Some(first) = mapping.dest.split('/').next() {
+                    assets.roots.insert(first.to_string());
+                }
+                // The folders assets install into count even while empty.
+                assets
+                    .paths
+                    .insert(mapping.dest.trim_end_matches('/').to_string());
+            }
+            let agents_dir = manifest.group_dir(AGENTS_GROUP);
+            let commands_dir = manifest.group_dir(COMMANDS_GROUP);
+
+            for file in templates.provider_templates(&provider)?.unwrap_or_default() {
+                let path = file
+                    .path
+                    .strip_suffix(TEMPLATE_SUFFIX)
+                    .unwrap_or(&file.path)
+                    .to_string();
+                match file.group.as_str() {
+                    AGENTS_GROUP => {
+                        assets
+                            .agents
+                            .insert(frontmatter::agent_name(&path, &file.contents));
+                    }
+                    COMMANDS_GROUP => {
+                        if let Some(command) = commands_dir.and_then(|dir| command_name(dir, &path))
+                        {
+                            assets.commands.insert(command);
+                        }
+                    }
+                    _ => {}
+                }
+                assets.paths.insert(format!("{provider}/{}", file.source));
+                assets.paths.insert(path);
+            }
+
+            if let Some(dir) = agents_dir {
+                for path in fsutil::walk_files(root, dir)? {
+                    let contents = std::fs::read(root.join(&path))
+                        .with_context(|| format!("Failed to read file: {:?}", root.join(&path)))?;
+                    assets
+                        .agents
+                        .insert(frontmatter::agent_name(&path, &contents));
+                }
+            }
+            if let Some(dir) = commands_dir {
+                for path in fsutil::walk_files(root, dir)? {
+                    if let Some(command) = command_name(dir, &path) {
+                        assets.commands.insert(command);
+                    }
+                }
+            }
+        }
+        Ok(assets)
+    }
+
+    fn resolves(&self, root: &Path, kind: RefKind, reference: &str) -> bool {
+        match kind {
+            RefKind::Command => {
+                let name = &reference[1..];
+                BUILTIN_COMMANDS.contains(&name)
+                    || self.commands.contains(name)
+                    // A command may be invoked by its last segment alone.
+                    || (!name.contains(':')
+                        && self
+                            .commands
+                            .iter()
+                            .any(|command| command.rsplit(':').next() == Some(name)))
+            }
+            RefKind::Agent => self.agents.contains(&reference[1..]),
+            RefKind::Path => {
+                let path = reference.trim_end_matches('/');
+                if root.join(path).exists() {
+                    return true;
+                }
+                let path = path.strip_prefix(TEMPLATES_PREFIX).unwrap_or(path);
+                self.paths.iter().any(|asset| {
+                    asset == path
+                        || asset
+                            .strip_prefix(path)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+            }
+        }
+    }
+
+    /// Whether a path in a playbook points at an asset rather than a file
+    /// the workflow produces, such as a design doc.
+    fn is_asset_path(&self, path: &str) -> bool {
+        path.starts_with(TEMPLATES_PREFIX)
+            || path
+                .split('/')
+                .next()
+                .is_some_and(|first| self.roots.contains(first))
+    }
+}
+
+/// How the command file at `path` below `dir` is invoked: its path without
+/// the extension, with `:` between folders.
+fn command_name(dir: &str, path: &str) -> Option<String> {
+    let relative = path.strip_prefix(dir)?.strip_suffix(".md")?;
+    if relative.rsplit('/').next()?.eq_ignore_ascii_case("README") {
+        return None;
+    }
+    Some(relative.replace('/', ":"))
+}
+
+/// The slash commands, `@agent` mentions and asset-looking paths on a line.
+/// Paths are only taken from inline code, where playbooks quote them.
+fn references(line: &str) -> Vec<(RefKind, &str)> {
+    let mut found = Vec::new();
+    let bytes = line.as_bytes();
+    let boundary = |index: usize| {
+        index == 0
+            || matches!(
+                bytes[index - 1],
+                b' ' | b'\t' | b'`' | b'(' | b'"' | b'\'' | b'|'
+            )
+    };
+    let name_char = |c: u8| c.is_ascii_alphanumeric() || matches!(c, b'-' | b'_' | b':');
+
+    let mut index = 0;
+    while index < bytes.len() {
+        let sigil = bytes[index];
+        if (sigil == b'/' || sigil == b'@') && boundary(index) {
+            let start = index;
+            let mut end = index + 1;
+            while end < bytes.len() && name_char(bytes[end]) {
+                end += 1;
+            }
+            let name = line[start + 1..end].trim_end_matches(':');
+            let followed_by_path = end < bytes.len() && matches!(bytes[end], b'/' | b'.' | b'@');
+            let starts_well = name.as_bytes().first().is_some_and(u8::is_ascii_lowercase);
+            if starts_well && !followed_by_path {
+                let kind = if sigil == b'/' {
+                    RefKind::Command
+                } else {
+                    RefKind::Agent
+                };
+                found.push((kind, &line[start..start + 1 + name.len()]));
+            }
+            index = end;
+        } else {
+            index += 1;
+        }
+    }
+
+    for (position, span) in line.split('`').enumerate() {
+        // Odd pieces sit between backticks.
+        if position % 2 == 1
+            && span.contains('/')
+            && !span.starts_with('/')
+            && !span.contains(char::is_whitespace)
+            && !span.contains("://")
+        {
+            found.push((RefKind::Path, span));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embedded_assets(root: &Path) -> Assets {
+        let config = Config::default();
+        let templates = Templates::load(root, &config, &TemplateArgs::default(), None).unwrap();
+        Assets::collect(root, &templates).unwrap()
+    }
+
+    #[test]
+    fn references_finds_commands_agents_and_quoted_paths() {
+        let line = "Run /review:pr, then ask @code-reviewer (or `@tester`) to check \
+                    `.claude/agents/x.md` and `docs/design.md`.";
+        assert_eq!(
+            references(line),
+            [
+                (RefKind::Command, "/review:pr"),
+                (RefKind::Agent, "@code-reviewer"),
+                (RefKind::Agent, "@tester"),
+                (RefKind::Path, ".claude/agents/x.md"),
+                (RefKind::Path, "docs/design.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn references_skips_what_only_looks_like_one() {
+        for line in [
+            "Files live in /usr/local/bin and mail goes to ops@example.com.",
+            "See https://example.com/docs and `https://example.com/x`.",
+            "Use and/or, a /Capitalised word, and `a path with spaces/x`.",
+            "A trailing colon is punctuation: /deploy:",
+        ] {
+            let found = references(line);
+            assert!(
+                found
+                    .iter()
+                    .all(|(kind, reference)| *kind == RefKind::Command && *reference == "/deploy"),
+                "{line}: {found:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn command_names_join_folders_with_colons() {
+        let dir = ".claude/commands/";
+        assert_eq!(
+            command_name(dir, ".claude/commands/review/pr.md").as_deref(),
+            Some("review:pr")
+        );
+        assert_eq!(
+            command_name(dir, ".claude/commands/plan.md").as_deref(),
+            Some("plan")
+        );
+        assert_eq!(command_name(dir, ".claude/commands/review/README.md"), None);
+        assert_eq!(command_name(dir, ".claude/commands/notes.txt"), None);
+        assert_eq!(command_name(dir, ".cursor/commands/plan.md"), None);
+    }
+
+    #[test]
+    fn resolves_against_templates_and_installed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let commands = root.join(".claude/commands/review");
+        std::fs::create_dir_all(&commands).unwrap();
+        std::fs::write(commands.join("pr.md"), "Review the pull request.\n").unwrap();
+        std::fs::write(root.join(".claude/settings.json"), "{}\n").unwrap();
+        let agents = root.join(".claude/agents");
+        std::fs::create_dir_all(&agents).unwrap();
+        std::fs::write(
+            agents.join("tester.md"),
+            "---\nname: tester\ndescription: Tests.\n---\n",
+        )
+        .unwrap();
+        let assets = embedded_assets(root);
+
+        for (kind, reference) in [
+            // Installed files.
+            (RefKind::Command, "/review:pr"),
+            (RefKind::Command, "/pr"),
+            (RefKind::Agent, "@tester"),
+            (RefKind::Path, ".claude/settings.json"),
+            // Templates and where they install.
+            (RefKind::Command, "/initial:implementation_plan"),
+            (RefKind::Agent, "@example"),
+            (RefKind::Path, "templates/claude/agents/example.md"),
+            (RefKind::Path, ".claude/agents/example.md"),
+            (RefKind::Path, ".cursor/commands/"),
+            // Built into the assistants.
+            (RefKind::Command, "/compact"),
+        ] {
+            assert!(
+                assets.resolves(root, kind, reference),
+                "{reference} should resolve"
+            );
+        }
+        for (kind, reference) in [
+            (RefKind::Command, "/review:issue"),
+            (RefKind::Command, "/issue"),
+            (RefKind::Agent, "@reviewer"),
+            (RefKind::Path, ".claude/agents/reviewer.md"),
+            (RefKind::Path, "templates/claude/commands/missing.md"),
+        ] {
+            assert!(
+                !assets.resolves(root, kind, reference),
+                "{reference} should not resolve"
+            );
+        }
+
+        assert!(assets.is_asset_path(".claude/agents/x.md"));
+        assert!(assets.is_asset_path("templates/roo/x.md"));
+        assert!(!assets.is_asset_path("docs/design.md"));
+    }
+}
@@ -0,0 +1,96 @@
+use clap::Parser;
+
+use crate::fsutil;
+use crate::lock::{self, LOCK_PATH, LockedFile, Lockfile};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct CleanArgs {
+    /// Providers whose scaffolded files should be removed.
+    #[arg(long, short, required_unless_present = "all")]
+    provider: Vec<String>,
+    /// Remove the files of every provider in the lockfile.
+    #[arg(long, conflicts_with = "provider")]
+    all: bool,
+    /// Also remove files that were edited after they were scaffolded.
+    #[arg(long)]
+    force: bool,
+    /// Report what would be removed without touching disk.
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_clean(args: CleanArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let mut lockfile = Lockfile::load(root)?;
+
+    let providers: Vec<String> = if args.all {
+        lockfile.providers.keys().cloned().collect()
+    } else {
+        args.provider
+    };
+
+    let (mut removed, mut kept, mut unmanaged) = (0, 0, 0);
+    for provider in providers {
+        let Some(entry) = lockfile.providers.get_mut(&provider) else {
+            tracing::warn!("Provider '{}' is not recorded in {}.", provider, LOCK_PATH);
+            continue;
+        };
+
+        let files: Vec<(String, LockedFile)> = entry
+            .files
+            .iter()
+            .map(|(path, locked)| (path.clone(), locked.clone()))
+            .collect();
+        for (path, LockedFile { hash, adopted }) in files {
+            // The file was there before ai-dlc, so it is not ours to delete.
+            if adopted {
+                println!("{:<10} {}", "unmanage", path);
+                unmanaged += 1;
+                if !args.dry_run {
+                    entry.unlock_file(root, &path)?;
+                }
+                continue;
+            }
+            let modified = match fsutil::read_optional(&root.join(&path))? {
+                Some(local) => lock::hash_bytes(&local) != hash,
+                None => false,
+            };
+            if modified && !args.force {
+                println!("{:<10} {}", "keep", path);
+                kept += 1;
+                continue;
+            }
+            println!("{:<10} {}", "remove", path);
+            removed += 1;
+            if !args.dry_run {
+                fsutil::remove_file_and_prune(root, &path)?;
+                entry.unlock_file(root, &path)?;
+            }
+        }
+
+        if entry.files.is_empty() {
+            lockfile.providers.remove(&provider);
+        }
+    }
+
+    let verb = if args.dry_run { "to remove" } else { "removed" };
+    print!("\n{removed} files {verb}, {kept} locally modified files kept");
+    if unmanaged > 0 {
+        print!(", {unmanaged} pre-existing files left in place");
+    }
+    println!();
+    if kept > 0 {
+        tracing::warn!(
+            "Kept {} locally modified file(s); rerun with --force to remove them.",
+            kept
+        );
+    }
+
+    if !args.dry_run {
+        lockfile.save(root)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::mcp::McpServer;
+use crate::workspace::CONFIG_FILE;
+
+/// Project configuration read from `ai-dlc.toml` at the repository root.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// Values for template placeholders such as `project_name`.
+    pub variables: BTreeMap<String, String>,
+    /// Local template directory layered over the embedded templates,
+    /// relative to the repository root.
+    pub templates_dir: Option<PathBuf>,
+    /// Whether the embedded templates sit underneath `templates_dir`.
+    pub embedded_templates: bool,
+    /// Base64 ed25519 public keys whose signatures on template packs are
+    /// accepted. Once set, unsigned packs are refused.
+    pub trusted_keys: Vec<String>,
+    /// MCP servers that `mcp sync` writes to each provider's config.
+    pub mcp: McpConfig,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct McpConfig {
+    /// Server definitions by name.
+    pub servers: BTreeMap<String, McpServer>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            variables: BTreeMap::new(),
+            templates_dir: None,
+            embedded_templates: true,
+            trusted_keys: Vec::new(),
+            mcp: McpConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `ai-dlc.toml` from `root`; a missing file yields the defaults.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = root.join(CONFIG_FILE);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read config: {:?}", path));
+            }
+        };
+        toml::from_str(&contents).with_context(|| format!("Failed to parse config: {:?}", path))
+    }
+}
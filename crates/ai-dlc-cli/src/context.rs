@@ -0,0 +1,149 @@
+use clap::Parser;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::inspect;
+use crate::lock::Lockfile;
+use crate::plan::{ConflictPolicy, OutputFormat, ScaffoldPlan};
+use crate::render::{Renderer, VarArgs};
+use crate::scaffold;
+use crate::templates::{CONTEXT_DIR, TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+/// Markers around the generated part of a briefing. When an existing file
+/// has them, only the part between them is regenerated.
+const BEGIN_MARKER: &str = "<!-- ai-dlc:context:begin -->";
+const END_MARKER: &str = "<!-- ai-dlc:context:end -->";
+
+#[derive(Parser, Debug)]
+pub struct InitContextArgs {
+    /// Providers to write a briefing for. Defaults to the providers recorded
+    /// in the lockfile.
+    #[arg(long, short)]
+    provider: Vec<String>,
+    /// Write a briefing for every provider that has one.
+    #[arg(long, conflicts_with = "provider")]
+    all: bool,
+    /// Print what was detected about the repository as JSON and exit.
+    #[arg(long)]
+    facts: bool,
+    /// Report the files that would be written without touching disk.
+    #[arg(long)]
+    dry_run: bool,
+    /// Output format used for the dry-run plan.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, requires = "dry_run")]
+    plan_format: OutputFormat,
+    /// What to do with existing briefings that have no generated section.
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Skip)]
+    on_conflict: ConflictPolicy,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_init_context(args: InitContextArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let facts = inspect::inspect(root)?;
+    if args.facts {
+        println!("{}", serde_json::to_string_pretty(&facts)?);
+        return Ok(());
+    }
+    tracing::info!(
+        languages = facts.languages.len(),
+        build_files = facts.build_files.len(),
+        commands = facts.commands.len(),
+        ci = facts.ci.len(),
+        "Inspected the repository."
+    );
+
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+    let names: Vec<String> = if args.all {
+        templates
+            .available_providers()?
+            .into_iter()
+            .map(|provider| provider.name)
+            .collect()
+    } else if !args.provider.is_empty() {
+        args.provider.clone()
+    } else {
+        Lockfile::load(root)?.providers.into_keys().collect()
+    };
+    if names.is_empty() {
+        anyhow::bail!("No provider has been scaffolded here; pass --provider or --all.");
+    }
+
+    // Providers that read the same file, such as AGENTS.md, share it.
+    let mut briefings: BTreeMap<String, String> = BTreeMap::new();
+    for name in &names {
+        let Some(manifest) = templates.manifest(name)? else {
+            anyhow::bail!("Provider '{}' not found in the templates.", name);
+        };
+        match manifest.context {
+            Some(file) => {
+                briefings.entry(file).or_insert_with(|| name.clone());
+            }
+            None if args.all || args.provider.is_empty() => {
+                tracing::info!("Provider '{}' has no project briefing; skipping.", name);
+            }
+            None => anyhow::bail!("Provider '{}' has no project briefing file.", name),
+        }
+    }
+
+    let mut renderer = Renderer::new(root, &config, &BTreeMap::new(), &args.vars);
+    renderer.bind("repo", &facts);
+    let mut plan = ScaffoldPlan::new(args.on_conflict);
+    for (file, provider) in briefings {
+        let Some(template) = templates.context_template(&file) else {
+            anyhow::bail!(
+                "The templates have no {}/{}.jinja for provider '{}'.",
+                CONTEXT_DIR,
+                file,
+                provider
+            );
+        };
+        renderer.require(std::slice::from_ref(&template))?;
+        let contents = renderer.render(template)?.contents;
+        match fsutil::read_optional(&root.join(&file))? {
+            Some(existing) => match splice(&existing, &contents) {
+                Some(updated) => plan.add_update(&provider, file, &existing, updated),
+                None => plan.add(&provider, root, file, contents)?,
+            },
+            None => plan.add(&provider, root, file, contents)?,
+        }
+    }
+
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+    scaffold::extract_plan(&mut plan, root)?;
+    scaffold::report(&plan);
+    Ok(())
+}
+
+/// `existing` with its generated section replaced by the one in `rendered`,
+/// or `None` when either lacks the markers.
+fn splice(existing: &[u8], rendered: &[u8]) -> Option<Vec<u8>> {
+    fn section(text: &str) -> Option<(usize, usize)> {
+        let begin = text.find(BEGIN_MARKER)?;
+        let end = begin + text[begin..].find(END_MARKER)? + END_MARKER.len();
+        Some((begin, end))
+    }
+
+    let existing = std::str::from_utf8(existing).ok()?;
+    let rendered = std::str::from_utf8(rendered).ok()?;
+    let (begin, end) = section(existing)?;
+    let (new_begin, new_end) = section(rendered)?;
+    let spliced = [
+        &existing[..begin],
+        &rendered[new_begin..new_end],
+        &existing[end..],
+    ]
+    .concat();
+    Some(spliced.into_bytes())
+}
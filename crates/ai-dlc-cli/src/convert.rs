@@ -0,0 +1,756 @@
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::frontmatter::{self, Frontmatter};
+use crate::lint;
+
+/// A provider's file format for agents and slash commands.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// `.claude/agents/*.md` and `.claude/commands/*.md`.
+    Claude,
+    /// `.gemini/agents/*.md` and `.gemini/commands/*.toml`.
+    Gemini,
+    /// `.cursor/rules/*.mdc` rules and `.cursor/commands/*.md`.
+    Cursor,
+    /// `.roomodes` custom modes and `.roo/commands/*.md`.
+    Roo,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Agent,
+    Command,
+}
+
+#[derive(Parser, Debug)]
+pub struct ConvertArgs {
+    /// Agent or command file to convert.
+    file: PathBuf,
+    /// Format to convert to.
+    #[arg(long, value_enum)]
+    to: Format,
+    /// Format of FILE; detected from its path and extension when omitted.
+    #[arg(long, visible_alias = "from", value_enum)]
+    source_format: Option<Format>,
+    /// Whether FILE is an agent or a command; detected when omitted.
+    #[arg(long, value_enum)]
+    kind: Option<Kind>,
+    /// Write the result here instead of to stdout.
+    #[arg(long, short, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+/// Placeholder Claude and Roo commands use for the text typed after them.
+const ARGUMENTS: &str = "$ARGUMENTS";
+
+/// Gemini CLI's spelling of [`ARGUMENTS`].
+const GEMINI_ARGUMENTS: &str = "{{args}}";
+
+/// Claude tool names and their Gemini CLI equivalents. Claude's names are
+/// the common vocabulary; the first entry for a Gemini tool wins on the way
+/// back.
+const GEMINI_TOOLS: &[(&str, &str)] = &[
+    ("Read", "read_file"),
+    ("Write", "write_file"),
+    ("Edit", "replace"),
+    ("MultiEdit", "replace"),
+    ("Glob", "glob"),
+    ("Grep", "search_file_content"),
+    ("LS", "list_directory"),
+    ("Bash", "run_shell_command"),
+    ("WebFetch", "web_fetch"),
+    ("WebSearch", "google_web_search"),
+    ("TodoWrite", "write_todos"),
+];
+
+/// Roo tool groups and the Claude tools each grants. Converting from Roo
+/// grants the first few, which cover the group's everyday use.
+const ROO_GROUPS: &[(&str, &[&str])] = &[
+    ("read", &["Read", "Glob", "Grep", "LS", "NotebookRead"]),
+    ("edit", &["Edit", "Write", "MultiEdit", "NotebookEdit"]),
+    ("command", &["Bash", "BashOutput", "KillShell"]),
+];
+
+/// An agent or command in a provider-neutral shape.
+#[derive(Debug, Default)]
+struct Definition {
+    name: String,
+    description: Option<String>,
+    /// Tool names in Claude's vocabulary, with any permission pattern such
+    /// as `Bash(git add:*)`.
+    tools: Vec<String>,
+    model: Option<String>,
+    argument_hint: Option<String>,
+    /// Cursor rule settings.
+    globs: Option<String>,
+    always_apply: Option<bool>,
+    /// Instructions, with [`ARGUMENTS`] for the command's arguments.
+    body: String,
+}
+
+/// A Gemini CLI command file.
+#[derive(Serialize, Deserialize, Debug)]
+struct GeminiCommand {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    prompt: String,
+}
+
+/// A `.roomodes` file.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RooModes {
+    custom_modes: Vec<RooMode>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RooMode {
+    slug: String,
+    name: String,
+    role_definition: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    when_to_use: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Tool group names, or `[group, {fileRegex, …}]` restrictions.
+    #[serde(default)]
+    groups: Vec<serde_yaml::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    custom_instructions: Option<String>,
+}
+
+pub fn handle_convert(args: ConvertArgs) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(&args.file)
+        .with_context(|| format!("Failed to read file: {:?}", args.file))?;
+    let source = match args.source_format {
+        Some(format) => format,
+        None => detect_format(&args.file)?,
+    };
+    if source == args.to {
+        anyhow::bail!("{:?} is already in the {:?} format.", args.file, args.to);
+    }
+    let kind = match args.kind {
+        Some(kind) => kind,
+        None => detect_kind(&args.file, source, &text),
+    };
+
+    let mut dropped = Vec::new();
+    let definition = parse(&args.file, &text, source, kind, &mut dropped)?;
+    let converted = emit(&definition, args.to, kind, &mut dropped)?;
+    for note in &dropped {
+        tracing::warn!("{}", note);
+    }
+
+    match &args.output {
+        Some(output) => {
+            if let Some(parent) = output.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+            }
+            std::fs::write(output, converted)
+                .with_context(|| format!("Failed to write file: {:?}", output))?;
+            tracing::info!(
+                "Converted {:?} ({:?} {:?}) to {:?} ({} field(s) dropped)",
+                args.file,
+                source,
+                kind,
+                output,
+                dropped.len()
+            );
+        }
+        None => print!("{converted}"),
+    }
+    Ok(())
+}
+
+/// Works out a file's format from the provider directory it sits in, such
+/// as `.gemini/` or `templates/gemini/`, and the names only one format uses:
+/// `.roomodes`, `*.mdc` and `*.toml`. Files these do not settle, or settle
+/// two ways, need `--from`.
+fn detect_format(path: &Path) -> anyhow::Result<Format> {
+    const DIRECTORIES: &[(&str, Format)] = &[
+        ("claude", Format::Claude),
+        ("gemini", Format::Gemini),
+        ("cursor", Format::Cursor),
+        ("roo", Format::Roo),
+    ];
+    let components: Vec<&str> = path
+        .components()
+        .filter_map(|component| component.as_os_str().to_str())
+        .collect();
+    let mut formats: Vec<Format> = Vec::new();
+    for (index, component) in components.iter().enumerate() {
+        let in_templates = index > 0
+            && matches!(components[index - 1], "templates" | "embedded-templates")
+            && index + 1 < components.len();
+        let name = match component.strip_prefix('.') {
+            Some(name) => name,
+            None if in_templates => component,
+            None => continue,
+        };
+        if let Some((_, format)) = DIRECTORIES.iter().find(|(dir, _)| *dir == name)
+            && !formats.contains(format)
+        {
+            formats.push(*format);
+        }
+    }
+    let file = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    let named = match (file, extension) {
+        (".roomodes", _) => Some(Format::Roo),
+        (_, "mdc") => Some(Format::Cursor),
+        (_, "toml") => Some(Format::Gemini),
+        _ => None,
+    };
+    if let Some(format) = named
+        && !formats.contains(&format)
+    {
+        formats.push(format);
+    }
+
+    match formats.as_slice() {
+        [format] => Ok(*format),
+        [] => anyhow::bail!(
+            "Cannot tell the format of {:?} from its path; pass --from claude, gemini, cursor \
+             or roo.",
+            path
+        ),
+        _ => anyhow::bail!(
+            "{:?} could be {}; pass --from to say which.",
+            path,
+            formats
+                .iter()
+                .map(|format| format!("{format:?}"))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        ),
+    }
+}
+
+/// Tells agents from commands: Roo modes and Cursor rules are agents,
+/// Gemini TOML files are commands, and Markdown agents carry a `name`.
+fn detect_kind(path: &Path, format: Format, text: &str) -> Kind {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    match (format, extension) {
+        (Format::Cursor, "mdc") => Kind::Agent,
+        (Format::Gemini, "toml") => Kind::Command,
+        (Format::Roo, "md") => Kind::Command,
+        (Format::Roo, _) => Kind::Agent,
+        _ => match Frontmatter::parse(text) {
+            Ok(Some(frontmatter)) if frontmatter.get("name").is_some() => Kind::Agent,
+            _ => Kind::Command,
+        },
+    }
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse(
+    path: &Path,
+    text: &str,
+    format: Format,
+    kind: Kind,
+    dropped: &mut Vec<String>,
+) -> anyhow::Result<Definition> {
+    match (format, kind) {
+        (Format::Gemini, Kind::Command) => {
+            let command: GeminiCommand = toml::from_str(text)
+                .with_context(|| format!("Failed to parse Gemini command {:?}", path))?;
+            return Ok(Definition {
+                name: file_stem(path),
+                description: command.description,
+                body: command.prompt.replace(GEMINI_ARGUMENTS, ARGUMENTS),
+                ..Definition::default()
+            });
+        }
+        (Format::Roo, Kind::Agent) => return parse_roo_mode(path, text, dropped),
+        _ => {}
+    }
+
+    let (frontmatter, body) = match Frontmatter::parse(text) {
+        Ok(Some(frontmatter)) => {
+            let body = frontmatter.body(text).to_string();
+            (frontmatter, body)
+        }
+        Ok(None) => (Frontmatter::default(), text.to_string()),
+        Err(err) => anyhow::bail!("{}:{}: {}", path.display(), err.line, err.message),
+    };
+    let mut definition = Definition {
+        name: frontmatter
+            .get("name")
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| file_stem(path)),
+        description: frontmatter.get("description").map(str::to_string),
+        body,
+        ..Definition::default()
+    };
+
+    let tools_key = match kind {
+        Kind::Agent => "tools",
+        Kind::Command => "allowed-tools",
+    };
+    for field in &frontmatter.fields {
+        let key = field.key.as_str();
+        let value = field.value.as_str();
+        match (format, key) {
+            (_, "name" | "description") => {}
+            (Format::Claude | Format::Gemini, "model") => definition.model = Some(value.into()),
+            (Format::Claude | Format::Roo, "argument-hint") if kind == Kind::Command => {
+                definition.argument_hint = Some(value.into())
+            }
+            (Format::Cursor, "globs") => definition.globs = Some(value.into()),
+            (Format::Cursor, "alwaysApply") => definition.always_apply = Some(value == "true"),
+            (Format::Claude, _) if key == tools_key => {
+                definition.tools = lint::tool_entries(value)
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+            }
+            (Format::Gemini, "tools") => {
+                for tool in lint::tool_names(value) {
+                    match GEMINI_TOOLS.iter().find(|(_, gemini)| *gemini == tool) {
+                        Some((claude, _)) => definition.tools.push(claude.to_string()),
+                        None => dropped.push(format!(
+                            "Gemini tool '{tool}' has no equivalent in other formats; dropped"
+                        )),
+                    }
+                }
+            }
+            _ => dropped.push(format!(
+                "field '{key}' ({}:{}) has no equivalent; dropped",
+                path.display(),
+                field.line
+            )),
+        }
+    }
+    Ok(definition)
+}
+
+fn parse_roo_mode(
+    path: &Path,
+    text: &str,
+    dropped: &mut Vec<String>,
+) -> anyhow::Result<Definition> {
+    let modes: RooModes = serde_yaml::from_str(text)
+        .with_context(|| format!("Failed to parse Roo modes {:?}", path))?;
+    let mut modes = modes.custom_modes.into_iter();
+    let (Some(mode), None) = (modes.next(), modes.next()) else {
+        anyhow::bail!(
+            "{:?} must hold exactly one custom mode to convert; split the others out first.",
+            path
+        );
+    };
+
+    let mut tools = Vec::new();
+    for group in &mode.groups {
+        // `[edit, {fileRegex: …}]` restricts a group to matching files.
+        let (name, restricted) = match group {
+            serde_yaml::Value::String(name) => (name.as_str(), false),
+            serde_yaml::Value::Sequence(items) => (
+                items.first().and_then(|item| item.as_str()).unwrap_or(""),
+                true,
+            ),
+            _ => ("", false),
+        };
+        if restricted {
+            dropped.push(format!(
+                "the file restriction on Roo group '{name}' is dropped"
+            ));
+        }
+        match ROO_GROUPS.iter().find(|(group, _)| *group == name) {
+            Some((_, granted)) => tools.extend(granted.iter().take(3).map(|tool| tool.to_string())),
+            None => dropped.push(format!(
+                "Roo group '{name}' has no equivalent in other formats; dropped"
+            )),
+        }
+    }
+
+    let mut body = mode.role_definition;
+    if let Some(instructions) = mode.custom_instructions {
+        body = format!("{}\n\n{}", body.trim_end(), instructions);
+    }
+    if !body.ends_with('\n') {
+        body.push('\n');
+    }
+    if mode.name != title_case(&mode.slug) {
+        dropped.push(format!(
+            "Roo display name '{}' is dropped; the slug '{}' becomes the name",
+            mode.name, mode.slug
+        ));
+    }
+    Ok(Definition {
+        name: mode.slug,
+        description: mode.when_to_use.or(mode.description),
+        tools,
+        body,
+        ..Definition::default()
+    })
+}
+
+fn emit(
+    definition: &Definition,
+    format: Format,
+    kind: Kind,
+    dropped: &mut Vec<String>,
+) -> anyhow::Result<String> {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    match (format, kind) {
+        (Format::Claude | Format::Gemini, Kind::Agent) => {
+            fields.push(("name", definition.name.clone()));
+            if let Some(description) = &definition.description {
+                fields.push(("description", description.clone()));
+            }
+            let tools = tools_for(format, &definition.tools, dropped);
+            if !tools.is_empty() {
+                fields.push(("tools", tools.join(", ")));
+            }
+            if let Some(model) = model_for(format, definition.model.as_deref(), dropped) {
+                fields.push(("model", model));
+            }
+        }
+        (Format::Claude, Kind::Command) => {
+            if let Some(description) = &definition.description {
+                fields.push(("description", description.clone()));
+            }
+            if let Some(hint) = &definition.argument_hint {
+                fields.push(("argument-hint", hint.clone()));
+            }
+            if !definition.tools.is_empty() {
+                fields.push(("allowed-tools", definition.tools.join(", ")));
+            }
+            if let Some(model) = model_for(format, definition.model.as_deref(), dropped) {
+                fields.push(("model", model));
+            }
+        }
+        (Format::Gemini, Kind::Command) => {
+            unsupported(
+                dropped,
+                format,
+                "argument-hint",
+                definition.argument_hint.is_some(),
+            );
+            unsupported(
+                dropped,
+                format,
+                "allowed-tools",
+                !definition.tools.is_empty(),
+            );
+            unsupported(dropped, format, "model", definition.model.is_some());
+            drop_cursor_settings(definition, format, dropped);
+            let command = GeminiCommand {
+                description: definition.description.clone(),
+                prompt: definition.body.replace(ARGUMENTS, GEMINI_ARGUMENTS),
+            };
+            return Ok(toml::to_string(&command)?);
+        }
+        (Format::Roo, Kind::Command) => {
+            if let Some(description) = &definition.description {
+                fields.push(("description", description.clone()));
+            }
+            if let Some(hint) = &definition.argument_hint {
+                fields.push(("argument-hint", hint.clone()));
+            }
+            unsupported(
+                dropped,
+                format,
+                "allowed-tools",
+                !definition.tools.is_empty(),
+            );
+            unsupported(dropped, format, "model", definition.model.is_some());
+        }
+        (Format::Roo, Kind::Agent) => {
+            unsupported(dropped, format, "model", definition.model.is_some());
+            drop_cursor_settings(definition, format, dropped);
+            let groups = roo_groups(&definition.tools, dropped);
+            let modes = RooModes {
+                custom_modes: vec![RooMode {
+                    slug: definition.name.clone(),
+                    name: title_case(&definition.name),
+                    role_definition: definition.body.clone(),
+                    when_to_use: definition.description.clone(),
+                    description: None,
+                    groups,
+                    custom_instructions: None,
+                }],
+            };
+            return Ok(serde_yaml::to_string(&modes)?);
+        }
+        (Format::Cursor, Kind::Agent) => {
+            // An agent becomes an "agent requested" rule: applied when its
+            // description matches the task.
+            dropped.push(format!(
+                "name '{}' is not kept in Cursor rules; save the rule as {}.mdc",
+                definition.name, definition.name
+            ));
+            unsupported(dropped, format, "tools", !definition.tools.is_empty());
+            unsupported(dropped, format, "model", definition.model.is_some());
+            fields.push((
+                "description",
+                definition.description.clone().unwrap_or_default(),
+            ));
+            fields.push(("globs", definition.globs.clone().unwrap_or_default()));
+            fields.push((
+                "alwaysApply",
+                definition.always_apply.unwrap_or(false).to_string(),
+            ));
+        }
+        (Format::Cursor, Kind::Command) => {
+            // Cursor commands are plain Markdown.
+            unsupported(
+                dropped,
+                format,
+                "description",
+                definition.description.is_some(),
+            );
+            unsupported(
+                dropped,
+                format,
+                "argument-hint",
+                definition.argument_hint.is_some(),
+            );
+            unsupported(
+                dropped,
+                format,
+                "allowed-tools",
+                !definition.tools.is_empty(),
+            );
+            unsupported(dropped, format, "model", definition.model.is_some());
+            if definition.body.contains(ARGUMENTS) {
+                dropped.push(format!(
+                    "Cursor commands have no {ARGUMENTS} placeholder; the text typed after \
+                     the command is appended instead"
+                ));
+            }
+            return Ok(definition.body.clone());
+        }
+    }
+    if format != Format::Cursor {
+        drop_cursor_settings(definition, format, dropped);
+    }
+
+    Ok(format!(
+        "{}\n{}",
+        frontmatter::render(&fields)?,
+        definition.body
+    ))
+}
+
+fn unsupported(dropped: &mut Vec<String>, format: Format, field: &str, present: bool) {
+    if present {
+        dropped.push(format!(
+            "field '{field}' has no {format:?} equivalent; dropped"
+        ));
+    }
+}
+
+/// Reports Cursor rule settings that other formats cannot express.
+fn drop_cursor_settings(definition: &Definition, format: Format, dropped: &mut Vec<String>) {
+    let globs = definition
+        .globs
+        .as_ref()
+        .is_some_and(|globs| !globs.is_empty());
+    unsupported(dropped, format, "globs", globs);
+    unsupported(
+        dropped,
+        format,
+        "alwaysApply",
+        definition.always_apply == Some(true),
+    );
+}
+
+/// `tools` in the naming `format` uses.
+fn tools_for(format: Format, tools: &[String], dropped: &mut Vec<String>) -> Vec<String> {
+    if format != Format::Gemini {
+        return tools.to_vec();
+    }
+    let mut mapped: Vec<String> = Vec::new();
+    for tool in tools {
+        let name = tool_name(tool);
+        match GEMINI_TOOLS.iter().find(|(claude, _)| *claude == name) {
+            Some((_, gemini)) => {
+                drop_pattern(tool, gemini, format, dropped);
+                if !mapped.iter().any(|existing| existing == gemini) {
+                    mapped.push(gemini.to_string());
+                }
+            }
+            None => dropped.push(format!("tool '{tool}' has no Gemini equivalent; dropped")),
+        }
+    }
+    mapped
+}
+
+/// The tool a `tools` entry grants, without its permission pattern.
+fn tool_name(tool: &str) -> &str {
+    tool.split('(').next().unwrap_or(tool).trim()
+}
+
+/// Reports a permission pattern `format` cannot express, so `tool` is granted
+/// as a whole through `granted`.
+fn drop_pattern(tool: &str, granted: &str, format: Format, dropped: &mut Vec<String>) {
+    if tool_name(tool) != tool {
+        dropped.push(format!(
+            "the permission pattern in '{tool}' has no {format:?} equivalent; dropped, so \
+             '{granted}' is granted without it"
+        ));
+    }
+}
+
+/// Models only carry over within a provider's own family of identifiers.
+fn model_for(format: Format, model: Option<&str>, dropped: &mut Vec<String>) -> Option<String> {
+    let model = model?;
+    let claude =
+        ["sonnet", "opus", "haiku", "opusplan"].contains(&model) || model.starts_with("claude-");
+    let fits = match format {
+        Format::Claude => model == "inherit" || claude,
+        Format::Gemini => model == "inherit" || model.starts_with("gemini-"),
+        Format::Cursor | Format::Roo => false,
+    };
+    if fits {
+        Some(model.to_string())
+    } else {
+        dropped.push(format!(
+            "model '{model}' has no {format:?} equivalent; dropped"
+        ));
+        None
+    }
+}
+
+fn roo_groups(tools: &[String], dropped: &mut Vec<String>) -> Vec<serde_yaml::Value> {
+    let mut groups: Vec<&str> = Vec::new();
+    let mut mcp = false;
+    for tool in tools {
+        if tool.starts_with("mcp__") {
+            mcp = true;
+            continue;
+        }
+        match ROO_GROUPS
+            .iter()
+            .find(|(_, granted)| granted.contains(&tool_name(tool)))
+        {
+            Some((group, _)) => {
+                drop_pattern(tool, group, Format::Roo, dropped);
+                if !groups.contains(group) {
+                    groups.push(group);
+                }
+            }
+            None => dropped.push(format!("tool '{tool}' has no Roo group; dropped")),
+        }
+    }
+    if mcp {
+        // Roo grants MCP servers as a whole rather than per tool.
+        dropped.push("MCP tools are granted as Roo's whole 'mcp' group".to_string());
+        groups.push("mcp");
+    }
+    groups
+        .into_iter()
+        .map(|group| serde_yaml::Value::String(group.to_string()))
+        .collect()
+}
+
+fn title_case(name: &str) -> String {
+    name.split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_of(path: &str) -> anyhow::Result<Format> {
+        detect_format(Path::new(path))
+    }
+
+    #[test]
+    fn format_comes_from_whole_path_components() {
+        for (path, format) in [
+            (".claude/agents/reviewer.md", Format::Claude),
+            ("/work/app/.gemini/agents/reviewer.md", Format::Gemini),
+            (".gemini/commands/review.toml", Format::Gemini),
+            (".cursor/rules/reviewer.mdc", Format::Cursor),
+            ("rules/reviewer.mdc", Format::Cursor),
+            (".roo/commands/review.md", Format::Roo),
+            (".roomodes", Format::Roo),
+            ("templates/claude/agents/reviewer.md", Format::Claude),
+            (
+                "crates/cli/embedded-templates/roo/commands/x.md",
+                Format::Roo,
+            ),
+        ] {
+            assert_eq!(format_of(path).unwrap(), format, "{path}");
+        }
+    }
+
+    #[test]
+    fn unclear_paths_ask_for_the_format() {
+        for path in [
+            "my-gemini/agents/reviewer.md",
+            "docs/roo/reviewer.md",
+            "modes.yaml",
+            "reviewer.md",
+            "templates/roo",
+        ] {
+            let err = format_of(path).unwrap_err().to_string();
+            assert!(err.contains("pass --from"), "{path}: {err}");
+        }
+        let err = format_of(".claude/commands/review.toml")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Claude or Gemini"), "{err}");
+    }
+
+    #[test]
+    fn permission_patterns_survive_or_are_reported() {
+        let text = "---\ndescription: Commit\nallowed-tools: Bash(git add:*), Bash(git commit:*), Read\n---\nCommit.\n";
+        let mut dropped = Vec::new();
+        let definition = parse(
+            Path::new(".claude/commands/commit.md"),
+            text,
+            Format::Claude,
+            Kind::Command,
+            &mut dropped,
+        )
+        .unwrap();
+        assert_eq!(
+            definition.tools,
+            ["Bash(git add:*)", "Bash(git commit:*)", "Read"]
+        );
+        let claude = emit(&definition, Format::Claude, Kind::Command, &mut dropped).unwrap();
+        assert!(
+            claude.contains("allowed-tools: Bash(git add:*), Bash(git commit:*), Read"),
+            "{claude}"
+        );
+        assert!(dropped.is_empty(), "{dropped:?}");
+
+        let tools = tools_for(Format::Gemini, &definition.tools, &mut dropped);
+        assert_eq!(tools, ["run_shell_command", "read_file"]);
+        assert_eq!(dropped.len(), 2, "{dropped:?}");
+        assert!(dropped[0].contains("'Bash(git add:*)'"), "{dropped:?}");
+
+        dropped.clear();
+        let groups = roo_groups(&definition.tools, &mut dropped);
+        assert_eq!(groups, ["command", "read"]);
+        assert_eq!(dropped.len(), 2, "{dropped:?}");
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::Context;
+
+use crate::render::TEMPLATE_SUFFIX;
+
+/// Fence opening and closing a YAML frontmatter block.
+pub const FENCE: &str = "---";
+
+/// The `key: value` pairs of a Markdown file's frontmatter. Only the flat
+/// subset used by agent and command templates is understood: scalar values,
+/// optionally quoted, and values continued on indented lines (such as block
+/// lists).
+#[derive(Debug, Default)]
+pub struct Frontmatter {
+    pub fields: Vec<Field>,
+    /// 1-based line of the closing fence.
+    pub end_line: usize,
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub key: String,
+    pub value: String,
+    /// 1-based line the key is on.
+    pub line: usize,
+}
+
+/// A frontmatter block that could not be read, and the 1-based line at fault.
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Frontmatter {
+    /// Parses the frontmatter at the top of `text`. Returns `Ok(None)` when
+    /// the file does not start with a `---` fence.
+    pub fn parse(text: &str) -> Result<Option<Self>, ParseError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        match lines.next() {
+            Some((_, first)) if first.trim_end() == FENCE => {}
+            _ => return Ok(None),
+        }
+
+        let mut frontmatter = Frontmatter::default();
+        for (number, line) in lines {
+            if line.trim_end() == FENCE {
+                frontmatter.end_line = number;
+                return Ok(Some(frontmatter));
+            }
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let Some(field) = frontmatter.fields.last_mut() else {
+                    return Err(ParseError {
+                        line: number,
+                        message: "indented line before any key".to_string(),
+                    });
+                };
+                if !field.value.is_empty() {
+                    field.value.push('\n');
+                }
+                field.value.push_str(line.trim());
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                return Err(ParseError {
+                    line: number,
+                    message: format!("expected `key: value`, found '{}'", line.trim()),
+                });
+            };
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(ParseError {
+                    line: number,
+                    message: format!("invalid key '{key}'"),
+                });
+            }
+            if let Some(previous) = frontmatter.get_field(key) {
+                return Err(ParseError {
+                    line: number,
+                    message: format!(
+                        "duplicate key '{key}' (first set on line {})",
+                        previous.line
+                    ),
+                });
+            }
+            frontmatter.fields.push(Field {
+                key: key.to_string(),
+                value: unquote(value.trim()).to_string(),
+                line: number,
+            });
+        }
+        Err(ParseError {
+            line: 1,
+            message: format!("frontmatter is not closed with '{FENCE}'"),
+        })
+    }
+
+    pub fn get_field(&self, key: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.key == key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.get_field(key).map(|field| field.value.as_str())
+    }
+
+    /// The part of `text`, the file this was parsed from, after the closing
+    /// fence, without leading blank lines.
+    pub fn body<'a>(&self, text: &'a str) -> &'a str {
+        let offset: usize = text
+            .split_inclusive('\n')
+            .take(self.end_line)
+            .map(str::len)
+            .sum();
+        text[offset..].trim_start_matches(['\n', '\r'])
+    }
+}
+
+/// Writes `fields` as a frontmatter block, fences included. Fails when a
+/// value would not read back as written.
+pub fn render(fields: &[(&str, String)]) -> anyhow::Result<String> {
+    let mut block = format!("{FENCE}\n");
+    for (key, value) in fields {
+        block.push_str(&format!("{key}: {}\n", yaml_scalar(value)));
+    }
+    block.push_str(&format!("{FENCE}\n"));
+
+    let parsed = Frontmatter::parse(&block)
+        .ok()
+        .flatten()
+        .context("Generated frontmatter does not parse")?;
+    for (key, value) in fields {
+        if parsed.get(key) != Some(value.as_str()) {
+            anyhow::bail!("The {} '{}' cannot be written as frontmatter.", key, value);
+        }
+    }
+    Ok(block)
+}
+
+/// Quotes a frontmatter value when YAML would otherwise read it as something
+/// other than the plain string.
+fn yaml_scalar(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.ends_with(':')
+        && !value.starts_with(|c: char| c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !value.ends_with(char::is_whitespace)
+        && !value.contains('\n');
+    // Frontmatter is read without escape sequences, so pick a quote the
+    // value does not contain; `render` rejects values needing both.
+    if plain {
+        value.to_string()
+    } else if value.contains('"') {
+        format!("'{value}'")
+    } else {
+        format!("\"{value}\"")
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// The name an agent file is known by: its frontmatter `name`, else its file
+/// stem.
+pub fn agent_name(path: &str, contents: &[u8]) -> String {
+    let from_frontmatter = std::str::from_utf8(contents)
+        .ok()
+        .and_then(|text| Frontmatter::parse(text).ok().flatten())
+        .and_then(|frontmatter| frontmatter.get("name").map(str::to_string))
+        .filter(|name| !name.is_empty());
+    from_frontmatter.unwrap_or_else(|| {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let file = file.strip_suffix(TEMPLATE_SUFFIX).unwrap_or(file);
+        file.strip_suffix(".md").unwrap_or(file).to_string()
+    })
+}
+
+/// One-line description of the template at `path`: its frontmatter
+/// `description`, else its first Markdown heading without template
+/// placeholders.
+pub fn describe(path: &str, contents: &[u8]) -> Option<String> {
+    let text = std::str::from_utf8(contents).ok()?;
+    match Frontmatter::parse(text) {
+        Ok(Some(frontmatter)) => {
+            if let Some(description) = frontmatter.get("description")
+                && !description.is_empty()
+            {
+                return Some(description.lines().next().unwrap_or_default().to_string());
+            }
+        }
+        Ok(None) => {}
+        Err(err) => tracing::warn!("{}:{}: {}", path, err.line, err.message),
+    }
+    text.lines()
+        .filter_map(|line| line.strip_prefix("# "))
+        .find(|heading| !heading.contains("{{"))
+        .map(|heading| heading.trim().to_string())
+}
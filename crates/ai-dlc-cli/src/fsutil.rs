@@ -0,0 +1,117 @@
+use anyhow::Context;
+use std::path::{Component, Path, PathBuf};
+
+use crate::plan::slash_path;
+
+/// Reads a file, treating a missing file as `None`.
+pub fn read_optional(path: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to read file: {:?}", path)),
+    }
+}
+
+/// Lists every file below `root/dir` as a `/`-separated path relative to
+/// `root`. A missing directory yields no files.
+pub fn walk_files(root: &Path, dir: &str) -> anyhow::Result<Vec<String>> {
+    fn visit(root: &Path, dir: &Path, files: &mut Vec<String>) -> anyhow::Result<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read directory: {:?}", dir));
+            }
+        };
+        for entry in entries {
+            let entry = entry.with_context(|| format!("Failed to read directory: {:?}", dir))?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                visit(root, &path, files)?;
+            } else {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                files.push(slash_path(relative));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    visit(root, &root.join(dir), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+/// Removes `root/path` and then each parent directory left empty by that,
+/// stopping at `root` or the first directory that still has entries.
+pub fn remove_file_and_prune(root: &Path, path: &str) -> anyhow::Result<()> {
+    let file = root.join(path);
+    match std::fs::remove_file(&file) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            return Err(err).with_context(|| format!("Failed to remove file: {:?}", file));
+        }
+        _ => {}
+    }
+    let mut dir = file.parent();
+    while let Some(current) = dir {
+        if current == root || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        tracing::debug!("Removed empty directory: {:?}", current);
+        dir = current.parent();
+    }
+    Ok(())
+}
+
+/// Whether the `/`-separated `path` from a template or manifest would land
+/// outside the directory it is joined to: an absolute path, a Windows drive
+/// or any `..` part.
+pub fn escapes(path: &str) -> bool {
+    let mut parts = path.split(['/', '\\']);
+    path.starts_with(['/', '\\'])
+        || parts
+            .clone()
+            .next()
+            .is_some_and(|first| first.ends_with(':'))
+        || parts.any(|part| part == "..")
+}
+
+/// Resolves `.` and `..` in `path` without touching the file system, so a
+/// folder that does not exist yet can still be compared with others.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_flags_paths_leaving_the_root() {
+        for path in [
+            "../x",
+            "a/../../x",
+            "/etc/passwd",
+            "\\\\server\\x",
+            "C:/x",
+            "a\\..\\x",
+        ] {
+            assert!(escapes(path), "{path}");
+        }
+        for path in [".claude/agents/x.md", "docs/a..b.md", "a/./b"] {
+            assert!(!escapes(path), "{path}");
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::frontmatter;
+use crate::fsutil;
+use crate::lint;
+use crate::manifest::{AGENTS_GROUP, COMMANDS_GROUP};
+use crate::render::TEMPLATE_SUFFIX;
+use crate::templates::{TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct NewArgs {
+    #[command(subcommand)]
+    kind: NewKind,
+}
+
+#[derive(Subcommand, Debug)]
+enum NewKind {
+    /// Create a sub-agent definition.
+    Agent(AgentArgs),
+    /// Create a slash command.
+    Command(CommandArgs),
+}
+
+#[derive(Args, Debug)]
+struct AgentArgs {
+    /// Agent name, such as `code-reviewer`; also its file name.
+    name: String,
+    /// Subdirectory of the agents folder, such as `01-core-development`.
+    #[arg(long)]
+    category: Option<String>,
+    /// When the agent should be used. Prompted for when omitted on a terminal.
+    #[arg(long)]
+    description: Option<String>,
+    /// Tools the agent may use, comma separated. Omit to inherit every tool.
+    #[arg(long, value_delimiter = ',')]
+    tools: Vec<String>,
+    /// Model to run the agent on, such as `sonnet`, `opus` or `haiku`.
+    #[arg(long, default_value = "inherit")]
+    model: String,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args, Debug)]
+struct CommandArgs {
+    /// Command as `<folder>:<command>`, invoked as `/<folder>:<command>`, or
+    /// just `<command>`.
+    name: String,
+    /// What the command does. Prompted for when omitted on a terminal.
+    #[arg(long)]
+    description: Option<String>,
+    /// Arguments the command expects, shown when it is typed, such as
+    /// `<feature-name>`.
+    #[arg(long)]
+    argument_hint: Option<String>,
+    #[command(flatten)]
+    common: CommonArgs,
+}
+
+#[derive(Args, Debug)]
+struct CommonArgs {
+    /// Provider whose folder the file is created in.
+    #[arg(long, default_value = "claude")]
+    provider: String,
+    /// Print the file instead of writing it.
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_new(args: NewArgs) -> anyhow::Result<()> {
+    match args.kind {
+        NewKind::Agent(args) => new_agent(args),
+        NewKind::Command(args) => new_command(args),
+    }
+}
+
+fn new_agent(args: AgentArgs) -> anyhow::Result<()> {
+    check_name("agent name", &args.name)?;
+    if let Some(category) = &args.category {
+        check_name("category", category)?;
+    }
+    let common = &args.common;
+    if let Some(rules) = lint::rules(&common.provider) {
+        for tool in &args.tools {
+            if !rules.known_tool(tool.trim()) {
+                anyhow::bail!(
+                    "Unknown tool '{}' for provider '{}'; check the spelling and case.",
+                    tool.trim(),
+                    common.provider
+                );
+            }
+        }
+        if !rules.known_model(&args.model) {
+            anyhow::bail!(
+                "Unknown model '{}' for provider '{}'; expected one of {}.",
+                args.model,
+                common.provider,
+                rules.model_choices()
+            );
+        }
+    }
+    let root = &common.root.resolve()?;
+    let templates = Templates::load(root, &Config::load(root)?, &common.templates, None)?;
+    let dir = asset_dir(&templates, &common.provider, AGENTS_GROUP, "agents")?;
+
+    // Agents are addressed by name alone, so one in any category collides.
+    for file in templates
+        .provider_templates(&common.provider)?
+        .unwrap_or_default()
+    {
+        if file.group == AGENTS_GROUP
+            && frontmatter::agent_name(&file.path, &file.contents) == args.name
+        {
+            anyhow::bail!(
+                "Agent '{}' already exists in the {} templates ({}/{}); pick another name.",
+                args.name,
+                common.provider,
+                common.provider,
+                file.source
+            );
+        }
+    }
+    for path in fsutil::walk_files(root, &dir)? {
+        let contents = std::fs::read(root.join(&path))
+            .with_context(|| format!("Failed to read file: {:?}", root.join(&path)))?;
+        if frontmatter::agent_name(&path, &contents) == args.name {
+            anyhow::bail!(
+                "Agent '{}' is already installed at {}; pick another name.",
+                args.name,
+                path
+            );
+        }
+    }
+
+    let description = description(args.description, &format!("agent '{}'", args.name))?;
+    let mut fields = vec![("name", args.name.clone()), ("description", description)];
+    if !args.tools.is_empty() {
+        let tools: Vec<&str> = args.tools.iter().map(|tool| tool.trim()).collect();
+        fields.push(("tools", tools.join(", ")));
+    }
+    fields.push(("model", args.model));
+    let body = format!(
+        "You are {name}. Describe the agent's expertise and responsibilities here.\n\
+         \n\
+         When invoked:\n\
+         1. Gather the context the task needs.\n\
+         2. Carry out the work.\n\
+         3. Report what was done and anything left open.\n",
+        name = args.name
+    );
+
+    let path = match &args.category {
+        Some(category) => format!("{dir}{category}/{}.md", args.name),
+        None => format!("{dir}{}.md", args.name),
+    };
+    write_asset(root, &path, &fields, &body, common.dry_run)?;
+    if !common.dry_run {
+        println!();
+        println!(
+            "Mention it as @{} or let the model delegate to it.",
+            args.name
+        );
+    }
+    Ok(())
+}
+
+fn new_command(args: CommandArgs) -> anyhow::Result<()> {
+    let (folder, command) = match args.name.split_once(':') {
+        Some((folder, command)) => (Some(folder), command),
+        None => (None, args.name.as_str()),
+    };
+    if let Some(folder) = folder {
+        check_name("command folder", folder)?;
+    }
+    check_name("command name", command)?;
+    let common = &args.common;
+    let root = &common.root.resolve()?;
+    let templates = Templates::load(root, &Config::load(root)?, &common.templates, None)?;
+    let dir = asset_dir(
+        &templates,
+        &common.provider,
+        COMMANDS_GROUP,
+        "slash commands",
+    )?;
+
+    let path = match folder {
+        Some(folder) => format!("{dir}{folder}/{command}.md"),
+        None => format!("{dir}{command}.md"),
+    };
+    let invocation = format!("/{}", args.name);
+    if let Some(file) = templates
+        .provider_templates(&common.provider)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|file| {
+            file.path
+                .strip_suffix(TEMPLATE_SUFFIX)
+                .unwrap_or(&file.path)
+                == path
+        })
+    {
+        anyhow::bail!(
+            "Command {} already exists in the {} templates ({}/{}); pick another name.",
+            invocation,
+            common.provider,
+            common.provider,
+            file.source
+        );
+    }
+    if root.join(&path).exists() {
+        anyhow::bail!(
+            "Command {} is already installed at {}; pick another name.",
+            invocation,
+            path
+        );
+    }
+
+    let description = description(args.description, &format!("command {invocation}"))?;
+    let (fields, body) = if lint::PLAIN_COMMAND_PROVIDERS.contains(&common.provider.as_str()) {
+        // Plain Markdown commands take no frontmatter and no arguments; the
+        // description opens the instructions instead.
+        if args.argument_hint.is_some() {
+            tracing::warn!(
+                "Provider '{}' commands take no arguments; ignoring --argument-hint.",
+                common.provider
+            );
+        }
+        let body = format!(
+            "# {invocation}\n\
+             \n\
+             {description}\n\
+             \n\
+             Describe the steps to follow here.\n"
+        );
+        (Vec::new(), body)
+    } else {
+        let mut fields = vec![("description", description)];
+        if let Some(hint) = args.argument_hint {
+            fields.push(("argument-hint", hint));
+        }
+        let body = format!(
+            "# {invocation}\n\
+             \n\
+             Describe the steps to follow here.\n\
+             \n\
+             Arguments: $ARGUMENTS\n"
+        );
+        (fields, body)
+    };
+
+    write_asset(root, &path, &fields, &body, common.dry_run)?;
+    if !common.dry_run {
+        println!();
+        println!("Invoke it as {invocation}.");
+    }
+    Ok(())
+}
+
+/// Where `provider` keeps assets of `group`, from its manifest.
+fn asset_dir(
+    templates: &Templates,
+    provider: &str,
+    group: &str,
+    kind: &str,
+) -> anyhow::Result<String> {
+    let Some(manifest) = templates.manifest(provider)? else {
+        anyhow::bail!(
+            "Provider '{}' not found; run `ai-dlc-cli list` to see the providers.",
+            provider
+        );
+    };
+    let Some(dir) = manifest.group_dir(group) else {
+        anyhow::bail!(
+            "Provider '{}' has no '{}' directory mapping, so it does not take {}.",
+            provider,
+            group,
+            kind
+        );
+    };
+    Ok(dir.to_string())
+}
+
+/// Names become file and directory names and `/folder:command` invocations,
+/// so they follow the same rule `lint` applies to the templates.
+fn check_name(what: &str, name: &str) -> anyhow::Result<()> {
+    if !lint::legal_name(name) {
+        anyhow::bail!(
+            "Invalid {} '{}'; use lower-case letters, digits, '-' and '_', such as \
+             'code-reviewer'.",
+            what,
+            name
+        );
+    }
+    Ok(())
+}
+
+fn description(given: Option<String>, what: &str) -> anyhow::Result<String> {
+    if let Some(description) = given {
+        return Ok(description);
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Pass --description for the {}.", what);
+    }
+    Ok(dialoguer::Input::new()
+        .with_prompt(format!("Description of the {what}"))
+        .interact_text()?)
+}
+
+/// Writes a Markdown file with `fields` as its frontmatter, or none when
+/// there are no fields, or prints it with `dry_run`.
+fn write_asset(
+    root: &Path,
+    path: &str,
+    fields: &[(&str, String)],
+    body: &str,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let contents = if fields.is_empty() {
+        body.to_string()
+    } else {
+        format!("{}\n{body}", frontmatter::render(fields)?)
+    };
+
+    if dry_run {
+        print!("{contents}");
+        return Ok(());
+    }
+    let target = root.join(path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+    std::fs::write(&target, contents)
+        .with_context(|| format!("Failed to write file: {:?}", target))?;
+    println!("{:<10} {}", "create", path);
+    Ok(())
+}
@@ -0,0 +1,589 @@
+use anyhow::Context;
+use clap::Parser;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::fsutil;
+use crate::inspect;
+use crate::plan::slash_path;
+use crate::symbols::{self, FileOutline, SourceLanguage};
+use crate::workspace::RootArgs;
+
+/// Folder the dossiers are written to, relative to the project root. It is
+/// where the `/initref` playbook step expects them.
+const REF_DIR: &str = "ref";
+
+/// Dossier listing every module, written next to the module dossiers.
+const INDEX_FILE: &str = "index.md";
+
+/// Start of the first line of every generated dossier. Files without it are
+/// hand-written and never touched.
+const HEADER_PREFIX: &str = "<!-- ai-dlc initref:";
+
+/// Bumped when the dossier layout changes, so every dossier is regenerated.
+const DOSSIER_VERSION: &str = "1";
+
+/// Source files larger than this are listed but not parsed; they are
+/// usually generated.
+const MAX_SOURCE_BYTES: u64 = 1024 * 1024;
+
+/// Commits listed under recent changes.
+const RECENT_COMMITS: usize = 5;
+
+#[derive(Parser, Debug)]
+pub struct InitrefArgs {
+    /// Folder to write the dossiers to, relative to the project root.
+    #[arg(long, value_name = "DIR", default_value = REF_DIR)]
+    out: PathBuf,
+    /// Report missing, stale and orphaned dossiers without writing anything,
+    /// and exit with an error when there are any, for use in CI.
+    #[arg(long, conflicts_with_all = ["dry_run", "force"])]
+    check: bool,
+    /// Report what would be written or removed without touching disk.
+    #[arg(long)]
+    dry_run: bool,
+    /// Regenerate every dossier, not only those whose sources changed.
+    #[arg(long)]
+    force: bool,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+/// A directory holding Rust, Python or TypeScript sources, which gets one
+/// dossier.
+struct Module {
+    /// `/`-separated path relative to the root; empty for the root itself.
+    dir: String,
+    /// File name of its dossier within the output folder.
+    dossier: String,
+    files: Vec<SourceFile>,
+    hash: String,
+}
+
+struct SourceFile {
+    /// Path relative to the module directory.
+    name: String,
+    language: SourceLanguage,
+    outline: Option<FileOutline>,
+}
+
+/// What a run does, or in `--check` mode would need to do, to one dossier.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Create,
+    /// The recorded source hash no longer matches.
+    Regenerate,
+    /// The module it describes has no sources any more.
+    Remove,
+    Fresh,
+}
+
+impl Action {
+    fn label(self, check: bool) -> &'static str {
+        match (self, check) {
+            (Action::Create, false) => "create",
+            (Action::Create, true) => "missing",
+            (Action::Regenerate, false) => "update",
+            (Action::Regenerate, true) => "stale",
+            (Action::Remove, false) => "remove",
+            (Action::Remove, true) => "orphaned",
+            (Action::Fresh, _) => "fresh",
+        }
+    }
+}
+
+pub fn handle_initref(args: InitrefArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let out = fsutil::normalize(&root.join(&args.out));
+    let out_prefix = out
+        .strip_prefix(root)
+        .map(|relative| format!("{}/", slash_path(relative)))
+        .unwrap_or_default();
+
+    let mut modules = modules(root, &out_prefix)?;
+    let recorded = recorded_dossiers(&out)?;
+
+    let mut actions: Vec<(String, Action)> = Vec::new();
+    for module in &modules {
+        let action = match recorded.get(&module.dossier) {
+            None if out.join(&module.dossier).exists() => {
+                tracing::warn!(
+                    "Leaving {:?} alone; it was not written by initref.",
+                    out.join(&module.dossier)
+                );
+                Action::Fresh
+            }
+            None => Action::Create,
+            Some(header) if args.force || header.hash != module.hash => Action::Regenerate,
+            Some(_) => Action::Fresh,
+        };
+        actions.push((module.dossier.clone(), action));
+    }
+    let current: BTreeSet<&str> = modules.iter().map(|m| m.dossier.as_str()).collect();
+    for (dossier, header) in &recorded {
+        if header.dir.is_some() && !current.contains(dossier.as_str()) {
+            actions.push((dossier.clone(), Action::Remove));
+        }
+    }
+
+    // Parse only the modules whose dossier is rewritten.
+    let rewrite: BTreeSet<String> = actions
+        .iter()
+        .filter(|(_, action)| matches!(action, Action::Create | Action::Regenerate))
+        .map(|(dossier, _)| dossier.clone())
+        .collect();
+    for module in modules.iter_mut().filter(|m| rewrite.contains(&m.dossier)) {
+        parse_module(root, module)?;
+    }
+
+    let index = render_index(&modules);
+    let index_path = out.join(INDEX_FILE);
+    let index_action = match fsutil::read_optional(&index_path)? {
+        None => Action::Create,
+        Some(existing) if existing == index.as_bytes() => Action::Fresh,
+        Some(existing) if String::from_utf8_lossy(&existing).starts_with(HEADER_PREFIX) => {
+            Action::Regenerate
+        }
+        Some(_) => {
+            tracing::warn!(
+                "Leaving {:?} alone; it was not written by initref.",
+                index_path
+            );
+            Action::Fresh
+        }
+    };
+
+    let changes: Vec<&(String, Action)> = actions
+        .iter()
+        .filter(|(_, action)| *action != Action::Fresh)
+        .collect();
+    for (dossier, action) in &changes {
+        println!("{:<10} {}{}", action.label(args.check), out_prefix, dossier);
+    }
+    if index_action != Action::Fresh {
+        println!(
+            "{:<10} {}{}",
+            index_action.label(args.check),
+            out_prefix,
+            INDEX_FILE
+        );
+    }
+    let count = |wanted: Action| actions.iter().filter(|(_, a)| *a == wanted).count();
+    println!(
+        "\n{} modules: {} {}, {} {}, {} {}, {} up to date",
+        modules.len(),
+        count(Action::Create),
+        if args.check { "missing" } else { "created" },
+        count(Action::Regenerate),
+        if args.check { "stale" } else { "regenerated" },
+        count(Action::Remove),
+        if args.check { "orphaned" } else { "removed" },
+        count(Action::Fresh)
+    );
+
+    if args.check {
+        if !changes.is_empty() || index_action != Action::Fresh {
+            anyhow::bail!("Reference dossiers are out of date; run `ai-dlc-cli initref`.");
+        }
+        return Ok(());
+    }
+    if args.dry_run {
+        return Ok(());
+    }
+
+    let git = has_git(root);
+    for module in modules.iter().filter(|m| rewrite.contains(&m.dossier)) {
+        let recent = git.then(|| recent_changes(root, &module.dir));
+        let path = out.join(&module.dossier);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        std::fs::write(&path, render_dossier(module, recent.as_deref()))
+            .with_context(|| format!("Failed to write file: {:?}", path))?;
+    }
+    for (dossier, action) in &actions {
+        if *action == Action::Remove {
+            fsutil::remove_file_and_prune(&out, dossier)?;
+        }
+    }
+    if index_action != Action::Fresh {
+        std::fs::create_dir_all(&out)
+            .with_context(|| format!("Failed to create directory: {:?}", out))?;
+        std::fs::write(&index_path, index)
+            .with_context(|| format!("Failed to write file: {:?}", index_path))?;
+    }
+    tracing::info!(
+        created = count(Action::Create),
+        regenerated = count(Action::Regenerate),
+        removed = count(Action::Remove),
+        fresh = count(Action::Fresh),
+        "Reference dossiers written to {:?}.",
+        out
+    );
+    Ok(())
+}
+
+/// Groups the project's Rust, Python and TypeScript files by directory and
+/// hashes each group. Files under `skip`, the output folder, are left out.
+fn modules(root: &Path, skip: &str) -> anyhow::Result<Vec<Module>> {
+    let mut by_dir: BTreeMap<String, Vec<SourceFile>> = BTreeMap::new();
+    for path in inspect::source_files(root)? {
+        let Some(language) = SourceLanguage::of(&path) else {
+            continue;
+        };
+        if !skip.is_empty() && path.starts_with(skip) {
+            continue;
+        }
+        let (dir, name) = match path.rsplit_once('/') {
+            Some((dir, name)) => (dir.to_string(), name.to_string()),
+            None => (String::new(), path.clone()),
+        };
+        by_dir.entry(dir).or_default().push(SourceFile {
+            name,
+            language,
+            outline: None,
+        });
+    }
+
+    let mut taken = BTreeSet::new();
+    let mut modules = Vec::new();
+    for (dir, files) in by_dir {
+        let stem = if dir.is_empty() {
+            "root".to_string()
+        } else {
+            dir.replace('/', "-")
+        };
+        // Different directories can flatten to the same name, such as `a-b`
+        // and `a/b`.
+        let mut dossier = format!("{stem}.md");
+        let mut suffix = 2;
+        while dossier == INDEX_FILE || !taken.insert(dossier.clone()) {
+            dossier = format!("{stem}-{suffix}.md");
+            suffix += 1;
+        }
+        let hash = source_hash(root, &dir, &files)?;
+        modules.push(Module {
+            dir,
+            dossier,
+            files,
+            hash,
+        });
+    }
+    Ok(modules)
+}
+
+/// Hashes the paths and contents of a module's source files.
+fn source_hash(root: &Path, dir: &str, files: &[SourceFile]) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(DOSSIER_VERSION.as_bytes());
+    hasher.update(dir.as_bytes());
+    for file in files {
+        let path = root.join(dir).join(&file.name);
+        let contents =
+            std::fs::read(&path).with_context(|| format!("Failed to read file: {:?}", path))?;
+        hasher.update([0]);
+        hasher.update(file.name.as_bytes());
+        hasher.update([0]);
+        hasher.update(&contents);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn parse_module(root: &Path, module: &mut Module) -> anyhow::Result<()> {
+    for file in &mut module.files {
+        let path = root.join(&module.dir).join(&file.name);
+        let size = std::fs::metadata(&path)
+            .with_context(|| format!("Failed to read file: {:?}", path))?
+            .len();
+        if size > MAX_SOURCE_BYTES {
+            tracing::info!("Not parsing {:?}, which is {} bytes.", path, size);
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            tracing::warn!("Not parsing {:?}, which is not valid UTF-8.", path);
+            continue;
+        };
+        let relative = module_path(&module.dir, &file.name);
+        file.outline = Some(symbols::outline(&relative, file.language, &source)?);
+    }
+    Ok(())
+}
+
+fn module_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// The first line of a generated dossier: the directory it describes, or
+/// none for the index, and the hash of the sources it was built from.
+struct Header {
+    dir: Option<String>,
+    hash: String,
+}
+
+impl Header {
+    fn render(&self) -> String {
+        match &self.dir {
+            Some(dir) => format!(
+                "{HEADER_PREFIX} dir=\"{dir}\" source-hash={} -->",
+                self.hash
+            ),
+            None => format!("{HEADER_PREFIX} index source-hash={} -->", self.hash),
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let rest = line
+            .strip_prefix(HEADER_PREFIX)?
+            .strip_suffix("-->")?
+            .trim();
+        let (target, hash) = rest.rsplit_once(" source-hash=")?;
+        let dir = match target {
+            "index" => None,
+            _ => Some(
+                target
+                    .strip_prefix("dir=\"")?
+                    .strip_suffix('"')?
+                    .to_string(),
+            ),
+        };
+        Some(Self {
+            dir,
+            hash: hash.to_string(),
+        })
+    }
+}
+
+/// Generated dossiers already in `out`, keyed by file name.
+fn recorded_dossiers(out: &Path) -> anyhow::Result<BTreeMap<String, Header>> {
+    let mut recorded = BTreeMap::new();
+    for name in fsutil::walk_files(out, "")? {
+        if !name.ends_with(".md") || name.contains('/') {
+            continue;
+        }
+        let path = out.join(&name);
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        if let Some(header) = text.lines().next().and_then(Header::parse) {
+            recorded.insert(name, header);
+        }
+    }
+    Ok(recorded)
+}
+
+fn has_git(root: &Path) -> bool {
+    root.join(".git").exists()
+        && Command::new("git")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+}
+
+/// The latest commits touching files directly in `dir`, as
+/// `hash date subject` lines.
+fn recent_changes(root: &Path, dir: &str) -> Vec<String> {
+    let pathspec = if dir.is_empty() {
+        ":(glob)*".to_string()
+    } else {
+        format!(":(glob){dir}/*")
+    };
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args([
+            "log",
+            &format!("-n{RECENT_COMMITS}"),
+            "--format=%h %as %s",
+            "--",
+        ])
+        .arg(pathspec)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        Ok(output) => {
+            tracing::debug!(
+                "git log failed for '{}': {}",
+                dir,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Vec::new()
+        }
+        Err(err) => {
+            tracing::debug!("Failed to run git log for '{}': {}", dir, err);
+            Vec::new()
+        }
+    }
+}
+
+fn display_dir(dir: &str) -> &str {
+    if dir.is_empty() { "." } else { dir }
+}
+
+/// Languages of a module with their file counts, most used first.
+fn languages(module: &Module) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for file in &module.files {
+        *counts.entry(file.language.name()).or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|(name, files)| format!("{name} ({files})"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Writes the dossier of `module`. `recent` is `None` outside a git
+/// checkout.
+fn render_dossier(module: &Module, recent: Option<&[String]>) -> String {
+    let header = Header {
+        dir: Some(module.dir.clone()),
+        hash: module.hash.clone(),
+    };
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", header.render());
+    let _ = writeln!(out, "# `{}`\n", display_dir(&module.dir));
+    let _ = writeln!(
+        out,
+        "Generated by `ai-dlc-cli initref` from the sources; rerun it when they change \
+         instead of editing this file.\n"
+    );
+    let _ = writeln!(out, "Languages: {}.\n", languages(module));
+
+    let outlines: Vec<(&SourceFile, &FileOutline)> = module
+        .files
+        .iter()
+        .filter_map(|file| Some((file, file.outline.as_ref()?)))
+        .collect();
+
+    let _ = writeln!(out, "## Entry points\n");
+    let entries: Vec<_> = outlines
+        .iter()
+        .filter_map(|(file, outline)| Some((file, outline.entry_point.as_ref()?)))
+        .collect();
+    if entries.is_empty() {
+        let _ = writeln!(out, "None.\n");
+    } else {
+        for (file, entry) in entries {
+            let _ = writeln!(out, "- `{}`: {}", file.name, entry);
+        }
+        out.push('\n');
+    }
+
+    let _ = writeln!(out, "## Public symbols\n");
+    let mut any = false;
+    for (file, outline) in &outlines {
+        if outline.symbols.is_empty() {
+            continue;
+        }
+        any = true;
+        let _ = writeln!(out, "### `{}`\n", file.name);
+        for symbol in &outline.symbols {
+            let owner = symbol
+                .parent
+                .as_ref()
+                .map(|parent| format!(" in `{parent}`"))
+                .unwrap_or_default();
+            let _ = write!(
+                out,
+                "- `{}`{} (line {})",
+                symbol.signature, owner, symbol.line
+            );
+            if let Some(doc) = &symbol.doc {
+                let _ = write!(out, ": {doc}");
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    if !any {
+        let _ = writeln!(out, "None.\n");
+    }
+
+    let _ = writeln!(out, "## Dependencies\n");
+    let mut internal = BTreeSet::new();
+    let mut external = BTreeSet::new();
+    for (_, outline) in &outlines {
+        for import in &outline.imports {
+            if import.internal {
+                internal.insert(import.path.as_str());
+            } else {
+                external.insert(import.path.as_str());
+            }
+        }
+    }
+    for (label, paths) in [("Internal", internal), ("External", external)] {
+        let list = if paths.is_empty() {
+            "none".to_string()
+        } else {
+            paths
+                .iter()
+                .map(|path| format!("`{path}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let _ = writeln!(out, "- {label}: {list}");
+    }
+    out.push('\n');
+
+    let _ = writeln!(out, "## Recent changes\n");
+    match recent {
+        None => {
+            let _ = writeln!(out, "Not a git checkout.");
+        }
+        Some([]) => {
+            let _ = writeln!(out, "No commits yet.");
+        }
+        Some(commits) => {
+            for commit in commits {
+                let _ = writeln!(out, "- {commit}");
+            }
+        }
+    }
+    out
+}
+
+/// The index of every module, hashed over the module hashes so it changes
+/// whenever any dossier does.
+fn render_index(modules: &[Module]) -> String {
+    let mut hasher = Sha256::new();
+    for module in modules {
+        hasher.update(module.dossier.as_bytes());
+        hasher.update(module.hash.as_bytes());
+    }
+    let header = Header {
+        dir: None,
+        hash: format!("{:x}", hasher.finalize()),
+    };
+    let mut out = String::new();
+    let _ = writeln!(out, "{}", header.render());
+    let _ = writeln!(out, "# Reference dossiers\n");
+    let _ = writeln!(
+        out,
+        "One dossier per source directory, generated by `ai-dlc-cli initref`.\n"
+    );
+    let _ = writeln!(out, "| Module | Languages | Files | Dossier |");
+    let _ = writeln!(out, "| --- | --- | --- | --- |");
+    for module in modules {
+        let _ = writeln!(
+            out,
+            "| `{}` | {} | {} | [{}]({}) |",
+            display_dir(&module.dir),
+            languages(module),
+            module.files.len(),
+            module.dossier,
+            module.dossier
+        );
+    }
+    out
+}
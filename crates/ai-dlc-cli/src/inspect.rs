@@ -0,0 +1,561 @@
+use anyhow::Context;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::plan::slash_path;
+
+/// Directories that hold dependencies, build output or tool state rather
+/// than the project's own sources. Hidden directories are skipped as well.
+pub const IGNORED_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "vendor",
+    "dist",
+    "build",
+    "out",
+    "__pycache__",
+    "venv",
+    "env",
+    "site-packages",
+    "coverage",
+];
+
+/// Hidden directories that still say something about the project.
+const KNOWN_HIDDEN_DIRS: &[&str] = &[".github", ".gitlab", ".circleci"];
+
+/// Stop counting files after this many, so huge trees stay quick.
+const MAX_FILES: usize = 50_000;
+
+/// What `init-context` learned about a repository without running anything
+/// or going online.
+#[derive(Serialize, Debug, Default)]
+pub struct RepoFacts {
+    /// Languages by number of source files, most used first.
+    pub languages: Vec<Language>,
+    /// Build and package manifests found at the root, such as `Cargo.toml`.
+    pub build_files: Vec<String>,
+    /// Commands to build, test, lint and format the project.
+    pub commands: Vec<ProjectCommand>,
+    pub ci: Vec<Workflow>,
+    /// Top-level directories.
+    pub layout: Vec<LayoutEntry>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Language {
+    pub name: String,
+    pub files: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProjectCommand {
+    /// `build`, `test`, `lint`, `format`, `typecheck` or `run`.
+    pub kind: String,
+    pub run: String,
+    /// The file the command was read from or inferred from.
+    pub source: String,
+}
+
+/// A CI pipeline definition and the shell commands it runs.
+#[derive(Serialize, Debug)]
+pub struct Workflow {
+    pub system: String,
+    pub path: String,
+    pub name: String,
+    /// Events that start it, for GitHub Actions.
+    pub triggers: Vec<String>,
+    pub commands: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LayoutEntry {
+    /// Directory name with a trailing `/`.
+    pub path: String,
+    pub files: usize,
+    /// What the directory conventionally holds, for well-known names.
+    pub description: Option<String>,
+}
+
+/// Inspects the repository at `root`.
+pub fn inspect(root: &Path) -> anyhow::Result<RepoFacts> {
+    let files = source_files(root)?;
+    let mut facts = RepoFacts {
+        languages: languages(&files),
+        layout: layout(root, &files)?,
+        ..RepoFacts::default()
+    };
+    detect_build(root, &mut facts)?;
+    facts.ci = workflows(root)?;
+    Ok(facts)
+}
+
+/// Every file below `root` outside ignored and hidden directories, as
+/// `/`-separated relative paths.
+pub fn source_files(root: &Path) -> anyhow::Result<Vec<String>> {
+    fn visit(root: &Path, dir: &Path, files: &mut Vec<String>) -> anyhow::Result<()> {
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory: {:?}", dir))?;
+        for entry in entries {
+            if files.len() >= MAX_FILES {
+                return Ok(());
+            }
+            let entry = entry.with_context(|| format!("Failed to read directory: {:?}", dir))?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                if !ignored_dir(&name) {
+                    visit(root, &entry.path(), files)?;
+                }
+            } else if file_type.is_file() {
+                let path = entry.path();
+                files.push(slash_path(path.strip_prefix(root).unwrap_or(&path)));
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    visit(root, root, &mut files)?;
+    if files.len() >= MAX_FILES {
+        tracing::warn!("Stopped inspecting after {} files.", MAX_FILES);
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether a directory named `name` is skipped when inspecting sources.
+pub fn ignored_dir(name: &str) -> bool {
+    (name.starts_with('.') && !KNOWN_HIDDEN_DIRS.contains(&name)) || IGNORED_DIRS.contains(&name)
+}
+
+/// The language a file is written in, from its extension.
+pub fn language_of(path: &str) -> Option<&'static str> {
+    let (_, extension) = path
+        .rsplit_once('/')
+        .unwrap_or(("", path))
+        .1
+        .rsplit_once('.')?;
+    Some(match extension {
+        "rs" => "Rust",
+        "py" | "pyi" => "Python",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "scala" => "Scala",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "C++",
+        "swift" => "Swift",
+        "m" | "mm" => "Objective-C",
+        "dart" => "Dart",
+        "ex" | "exs" => "Elixir",
+        "erl" => "Erlang",
+        "hs" => "Haskell",
+        "lua" => "Lua",
+        "zig" => "Zig",
+        "sh" | "bash" => "Shell",
+        "ps1" => "PowerShell",
+        "sql" => "SQL",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        _ => return None,
+    })
+}
+
+fn languages(files: &[String]) -> Vec<Language> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for language in files.iter().filter_map(|path| language_of(path)) {
+        *counts.entry(language).or_default() += 1;
+    }
+    let mut languages: Vec<Language> = counts
+        .into_iter()
+        .map(|(name, files)| Language {
+            name: name.to_string(),
+            files,
+        })
+        .collect();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then_with(|| a.name.cmp(&b.name)));
+    languages
+}
+
+fn layout(root: &Path, files: &[String]) -> anyhow::Result<Vec<LayoutEntry>> {
+    let mut entries = Vec::new();
+    let dir_entries =
+        std::fs::read_dir(root).with_context(|| format!("Failed to read directory: {:?}", root))?;
+    for entry in dir_entries {
+        let entry = entry.with_context(|| format!("Failed to read directory: {:?}", root))?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !entry.file_type()?.is_dir() || ignored_dir(&name) {
+            continue;
+        }
+        let prefix = format!("{name}/");
+        entries.push(LayoutEntry {
+            files: files
+                .iter()
+                .filter(|path| path.starts_with(&prefix))
+                .count(),
+            description: describe_dir(&name).map(str::to_string),
+            path: prefix,
+        });
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+fn describe_dir(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "src" | "lib" => "Source code",
+        "tests" | "test" | "spec" | "__tests__" => "Tests",
+        "docs" | "doc" => "Documentation",
+        "examples" | "example" => "Examples",
+        "benches" | "bench" | "benchmarks" => "Benchmarks",
+        "crates" => "Rust workspace members",
+        "packages" | "libs" => "Workspace packages",
+        "apps" => "Applications",
+        "cmd" => "Command entry points",
+        "pkg" => "Library packages",
+        "internal" => "Private packages",
+        "bin" => "Executables",
+        "scripts" | "tools" => "Development scripts",
+        "config" | "configs" => "Configuration",
+        "migrations" => "Database migrations",
+        "public" | "static" | "assets" => "Static assets",
+        "templates" => "Templates",
+        "deploy" | "infra" | "terraform" | "k8s" => "Deployment and infrastructure",
+        ".github" => "GitHub configuration and workflows",
+        ".gitlab" => "GitLab configuration",
+        ".circleci" => "CircleCI configuration",
+        _ => return None,
+    })
+}
+
+/// Records the build files at the root and the commands they define.
+fn detect_build(root: &Path, facts: &mut RepoFacts) -> anyhow::Result<()> {
+    let mut commands = Vec::new();
+    let mut command = |kind: &str, run: String, source: &str| {
+        commands.push(ProjectCommand {
+            kind: kind.to_string(),
+            run,
+            source: source.to_string(),
+        });
+    };
+
+    if let Some(text) = read_text(root, "Cargo.toml")? {
+        facts.build_files.push("Cargo.toml".to_string());
+        let manifest: toml::Table = toml::from_str(&text).unwrap_or_default();
+        let scope = if manifest.contains_key("workspace") {
+            " --workspace"
+        } else {
+            ""
+        };
+        command("build", format!("cargo build{scope}"), "Cargo.toml");
+        command("test", format!("cargo test{scope}"), "Cargo.toml");
+        command(
+            "lint",
+            format!("cargo clippy{scope} --all-targets -- -D warnings"),
+            "Cargo.toml",
+        );
+        command("format", "cargo fmt --all".to_string(), "Cargo.toml");
+    }
+
+    if let Some(text) = read_text(root, "package.json")? {
+        facts.build_files.push("package.json".to_string());
+        let manager = if root.join("pnpm-lock.yaml").is_file() {
+            "pnpm"
+        } else if root.join("yarn.lock").is_file() {
+            "yarn"
+        } else if root.join("bun.lockb").is_file() || root.join("bun.lock").is_file() {
+            "bun"
+        } else {
+            "npm"
+        };
+        let package: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        if let Some(scripts) = package.get("scripts").and_then(|s| s.as_object()) {
+            for (script, body) in scripts {
+                let kind = match script.as_str() {
+                    "build" | "compile" => "build",
+                    "test" | "test:unit" | "test:e2e" => "test",
+                    "lint" => "lint",
+                    "format" | "fmt" => "format",
+                    "typecheck" | "type-check" | "tsc" => "typecheck",
+                    "dev" | "start" => "run",
+                    _ => continue,
+                };
+                // `npm init` writes a test script that only fails.
+                if body
+                    .as_str()
+                    .is_some_and(|body| body.contains("no test specified"))
+                {
+                    continue;
+                }
+                command(kind, format!("{manager} run {script}"), "package.json");
+            }
+        }
+    }
+
+    if let Some(text) = read_text(root, "pyproject.toml")? {
+        facts.build_files.push("pyproject.toml".to_string());
+        let project: toml::Table = toml::from_str(&text).unwrap_or_default();
+        let tool = project.get("tool").and_then(|tool| tool.as_table());
+        let has_tool = |name: &str| tool.is_some_and(|tool| tool.contains_key(name));
+        let runner = if has_tool("poetry") {
+            "poetry run "
+        } else if has_tool("uv") || root.join("uv.lock").is_file() {
+            "uv run "
+        } else if has_tool("hatch") {
+            "hatch run "
+        } else {
+            ""
+        };
+        if has_tool("pytest") || text.contains("pytest") || root.join("tests").is_dir() {
+            command("test", format!("{runner}pytest"), "pyproject.toml");
+        }
+        if has_tool("ruff") || text.contains("ruff") {
+            command("lint", format!("{runner}ruff check ."), "pyproject.toml");
+            command("format", format!("{runner}ruff format ."), "pyproject.toml");
+        } else if has_tool("black") {
+            command("format", format!("{runner}black ."), "pyproject.toml");
+        }
+        if has_tool("mypy") {
+            command("typecheck", format!("{runner}mypy ."), "pyproject.toml");
+        }
+    } else {
+        for name in ["setup.py", "requirements.txt"] {
+            if let Some(text) = read_text(root, name)? {
+                facts.build_files.push(name.to_string());
+                if text.contains("pytest") || root.join("pytest.ini").is_file() {
+                    command("test", "pytest".to_string(), name);
+                }
+            }
+        }
+    }
+
+    if root.join("go.mod").is_file() {
+        facts.build_files.push("go.mod".to_string());
+        command("build", "go build ./...".to_string(), "go.mod");
+        command("test", "go test ./...".to_string(), "go.mod");
+        command("lint", "go vet ./...".to_string(), "go.mod");
+        command("format", "gofmt -w .".to_string(), "go.mod");
+    }
+
+    if root.join("pom.xml").is_file() {
+        facts.build_files.push("pom.xml".to_string());
+        let maven = if root.join("mvnw").is_file() {
+            "./mvnw"
+        } else {
+            "mvn"
+        };
+        command("build", format!("{maven} -B package"), "pom.xml");
+        command("test", format!("{maven} -B test"), "pom.xml");
+    }
+
+    for name in ["build.gradle.kts", "build.gradle"] {
+        if root.join(name).is_file() {
+            facts.build_files.push(name.to_string());
+            let gradle = if root.join("gradlew").is_file() {
+                "./gradlew"
+            } else {
+                "gradle"
+            };
+            command("build", format!("{gradle} build"), name);
+            command("test", format!("{gradle} test"), name);
+            break;
+        }
+    }
+
+    if root.join("CMakeLists.txt").is_file() {
+        facts.build_files.push("CMakeLists.txt".to_string());
+        command(
+            "build",
+            "cmake -B build && cmake --build build".to_string(),
+            "CMakeLists.txt",
+        );
+        command(
+            "test",
+            "ctest --test-dir build".to_string(),
+            "CMakeLists.txt",
+        );
+    }
+
+    if root.join("Gemfile").is_file() {
+        facts.build_files.push("Gemfile".to_string());
+        if root.join("spec").is_dir() {
+            command("test", "bundle exec rspec".to_string(), "Gemfile");
+        }
+    }
+
+    for name in ["Makefile", "makefile", "GNUmakefile"] {
+        if let Some(text) = read_text(root, name)? {
+            facts.build_files.push(name.to_string());
+            for target in make_targets(&text) {
+                let kind = match target {
+                    "build" | "all" => "build",
+                    "test" | "check" => "test",
+                    "lint" => "lint",
+                    "fmt" | "format" => "format",
+                    _ => continue,
+                };
+                command(kind, format!("make {target}"), name);
+            }
+            break;
+        }
+    }
+
+    facts.commands = commands;
+    Ok(())
+}
+
+/// Targets defined in a Makefile, in order, without special targets such
+/// as `.PHONY` or pattern rules.
+fn make_targets(text: &str) -> Vec<&str> {
+    let mut targets = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(['\t', ' ', '#', '.']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        // `VAR := value` and `VAR ::= value` are assignments.
+        if rest.starts_with('=') || rest.starts_with(":=") || names.contains('=') {
+            continue;
+        }
+        for name in names.split_whitespace() {
+            if !name.contains(['%', '$']) && !targets.contains(&name) {
+                targets.push(name);
+            }
+        }
+    }
+    targets
+}
+
+/// CI definitions: GitHub Actions workflows with their commands, and the
+/// files of other well-known CI systems.
+fn workflows(root: &Path) -> anyhow::Result<Vec<Workflow>> {
+    let mut workflows = Vec::new();
+    let dir = root.join(".github/workflows");
+    if dir.is_dir() {
+        let mut paths: Vec<_> = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory: {:?}", dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "yml" || extension == "yaml")
+            })
+            .collect();
+        paths.sort();
+        for path in paths {
+            let relative = slash_path(path.strip_prefix(root).unwrap_or(&path));
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read file: {:?}", path))?;
+            let document: serde_yaml::Value = match serde_yaml::from_str(&text) {
+                Ok(document) => document,
+                Err(err) => {
+                    tracing::warn!("Skipping '{}', which is not valid YAML: {}", relative, err);
+                    continue;
+                }
+            };
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let triggers = match document.get("on") {
+                Some(serde_yaml::Value::String(event)) => vec![event.clone()],
+                Some(serde_yaml::Value::Sequence(events)) => events
+                    .iter()
+                    .filter_map(|event| event.as_str().map(str::to_string))
+                    .collect(),
+                Some(serde_yaml::Value::Mapping(events)) => events
+                    .keys()
+                    .filter_map(|event| event.as_str().map(str::to_string))
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let mut commands = Vec::new();
+            if let Some(jobs) = document.get("jobs").and_then(|jobs| jobs.as_mapping()) {
+                for job in jobs.values() {
+                    let steps = job.get("steps").and_then(|steps| steps.as_sequence());
+                    for step in steps.into_iter().flatten() {
+                        if let Some(run) = step.get("run").and_then(|run| run.as_str()) {
+                            push_lines(&mut commands, run);
+                        }
+                    }
+                }
+            }
+            workflows.push(Workflow {
+                system: "GitHub Actions".to_string(),
+                path: relative,
+                name: document
+                    .get("name")
+                    .and_then(|name| name.as_str())
+                    .map_or_else(|| stem.into_owned(), str::to_string),
+                triggers,
+                commands,
+            });
+        }
+    }
+
+    if let Some(text) = read_text(root, ".gitlab-ci.yml")? {
+        let mut commands = Vec::new();
+        if let Ok(serde_yaml::Value::Mapping(jobs)) = serde_yaml::from_str(&text) {
+            for job in jobs.values() {
+                let scripts = job.get("script").and_then(|script| script.as_sequence());
+                for line in scripts
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|line| line.as_str())
+                {
+                    push_lines(&mut commands, line);
+                }
+            }
+        }
+        workflows.push(Workflow {
+            system: "GitLab CI".to_string(),
+            path: ".gitlab-ci.yml".to_string(),
+            name: "GitLab CI".to_string(),
+            triggers: Vec::new(),
+            commands,
+        });
+    }
+
+    for (path, system) in [
+        (".circleci/config.yml", "CircleCI"),
+        ("azure-pipelines.yml", "Azure Pipelines"),
+        ("bitbucket-pipelines.yml", "Bitbucket Pipelines"),
+        (".travis.yml", "Travis CI"),
+        ("Jenkinsfile", "Jenkins"),
+    ] {
+        if root.join(path).is_file() {
+            workflows.push(Workflow {
+                system: system.to_string(),
+                path: path.to_string(),
+                name: system.to_string(),
+                triggers: Vec::new(),
+                commands: Vec::new(),
+            });
+        }
+    }
+    Ok(workflows)
+}
+
+/// Adds each non-empty line of a script to `commands`, once.
+fn push_lines(commands: &mut Vec<String>, script: &str) {
+    for line in script.lines().map(str::trim) {
+        if !line.is_empty() && !line.starts_with('#') && !commands.iter().any(|c| c == line) {
+            commands.push(line.to_string());
+        }
+    }
+}
+
+fn read_text(root: &Path, name: &str) -> anyhow::Result<Option<String>> {
+    let path = root.join(name);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    std::fs::read_to_string(&path)
+        .map(Some)
+        .with_context(|| format!("Failed to read file: {:?}", path))
+}
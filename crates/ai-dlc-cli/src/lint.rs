@@ -0,0 +1,816 @@
+use anyhow::Context;
+use clap::Parser;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::Config;
+use crate::frontmatter::Frontmatter;
+use crate::fsutil;
+use crate::manifest::{AGENTS_GROUP, COMMANDS_GROUP, RULES_GROUP};
+use crate::plan::OutputFormat;
+use crate::render::TEMPLATE_SUFFIX;
+use crate::templates::{TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct LintArgs {
+    /// Providers to check; defaults to every provider with agents or commands.
+    #[arg(long, short)]
+    provider: Vec<String>,
+    /// Check the provider templates instead of the files in the repository.
+    #[arg(long = "templates")]
+    check_templates: bool,
+    /// Output format for the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+/// A problem found in a file, at a 1-based line.
+#[derive(Serialize, Debug)]
+struct Diagnostic {
+    path: String,
+    line: usize,
+    message: String,
+}
+
+#[derive(Serialize, Debug)]
+struct LintReport {
+    files_checked: usize,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Agent,
+    Command,
+    Rule,
+}
+
+/// Providers whose commands are plain Markdown, where frontmatter is optional.
+pub const PLAIN_COMMAND_PROVIDERS: &[&str] = &["cursor"];
+
+/// Keys a Cursor `.mdc` rule understands.
+const RULE_KEYS: &[&str] = &["description", "globs", "alwaysApply"];
+
+/// What a provider accepts in frontmatter. Providers without rules only get
+/// the structural checks.
+pub struct Rules {
+    tools: &'static [&'static str],
+    /// Prefixes of tool names that are open-ended, such as MCP tools.
+    tool_prefixes: &'static [&'static str],
+    models: &'static [&'static str],
+    model_prefixes: &'static [&'static str],
+}
+
+pub fn rules(provider: &str) -> Option<Rules> {
+    match provider {
+        "claude" => Some(Rules {
+            tools: &[
+                "Bash",
+                "BashOutput",
+                "Edit",
+                "ExitPlanMode",
+                "Glob",
+                "Grep",
+                "KillShell",
+                "LS",
+                "MultiEdit",
+                "NotebookEdit",
+                "NotebookRead",
+                "Read",
+                "SlashCommand",
+                "Task",
+                "TodoWrite",
+                "WebFetch",
+                "WebSearch",
+                "Write",
+            ],
+            tool_prefixes: &["mcp__"],
+            models: &["inherit", "sonnet", "opus", "haiku", "opusplan"],
+            model_prefixes: &["claude-"],
+        }),
+        "gemini" => Some(Rules {
+            tools: &[
+                "glob",
+                "google_web_search",
+                "list_directory",
+                "read_file",
+                "read_many_files",
+                "replace",
+                "run_shell_command",
+                "save_memory",
+                "search_file_content",
+                "web_fetch",
+                "write_file",
+                "write_todos",
+            ],
+            tool_prefixes: &[],
+            models: &["inherit"],
+            model_prefixes: &["gemini-"],
+        }),
+        _ => None,
+    }
+}
+
+impl Rules {
+    pub fn known_tool(&self, tool: &str) -> bool {
+        self.tools.contains(&tool)
+            || self
+                .tool_prefixes
+                .iter()
+                .any(|prefix| tool.starts_with(prefix))
+    }
+
+    pub fn known_model(&self, model: &str) -> bool {
+        self.models.contains(&model)
+            || self
+                .model_prefixes
+                .iter()
+                .any(|prefix| model.starts_with(prefix))
+    }
+
+    /// The accepted models, with prefixes shown as `prefix*`.
+    pub fn model_choices(&self) -> String {
+        self.models
+            .iter()
+            .map(|model| model.to_string())
+            .chain(
+                self.model_prefixes
+                    .iter()
+                    .map(|prefix| format!("{prefix}*")),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+pub fn handle_lint(args: LintArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+
+    let providers: Vec<String> = if args.provider.is_empty() {
+        templates
+            .available_providers()?
+            .into_iter()
+            .map(|info| info.name)
+            .collect()
+    } else {
+        args.provider.clone()
+    };
+
+    let mut report = LintReport {
+        files_checked: 0,
+        diagnostics: Vec::new(),
+    };
+    for provider in &providers {
+        // (path shown in diagnostics, kind, contents)
+        let mut files: Vec<(String, Kind, Vec<u8>)> = Vec::new();
+        if args.check_templates {
+            for file in templates.provider_templates(provider)?.unwrap_or_default() {
+                if let Some(kind) = kind_of(&file.group) {
+                    files.push((format!("{provider}/{}", file.source), kind, file.contents));
+                }
+            }
+        } else {
+            let Some(manifest) = templates.manifest(provider)? else {
+                anyhow::bail!(
+                    "Provider '{}' not found; run `ai-dlc-cli list` to see the providers.",
+                    provider
+                );
+            };
+            for group in [AGENTS_GROUP, COMMANDS_GROUP, RULES_GROUP] {
+                let (Some(dir), Some(kind)) = (manifest.group_dir(group), kind_of(group)) else {
+                    continue;
+                };
+                for path in fsutil::walk_files(root, dir)? {
+                    let contents = std::fs::read(root.join(&path))
+                        .with_context(|| format!("Failed to read file: {:?}", root.join(&path)))?;
+                    files.push((path, kind, contents));
+                }
+            }
+        }
+
+        let rules = rules(provider);
+        // Agent name -> file that first defined it.
+        let mut agents: BTreeMap<String, String> = BTreeMap::new();
+        for (path, kind, contents) in files {
+            if is_readme(&path) {
+                continue;
+            }
+            report.files_checked += 1;
+            lint_file(
+                provider,
+                &path,
+                kind,
+                &contents,
+                rules.as_ref(),
+                &mut agents,
+                &mut report.diagnostics,
+            );
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            for diagnostic in &report.diagnostics {
+                println!(
+                    "{}:{}: {}",
+                    diagnostic.path, diagnostic.line, diagnostic.message
+                );
+            }
+            let files: BTreeSet<&str> = report
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.path.as_str())
+                .collect();
+            if !report.diagnostics.is_empty() {
+                println!();
+            }
+            println!(
+                "{} files checked: {} problem(s) in {} file(s)",
+                report.files_checked,
+                report.diagnostics.len(),
+                files.len()
+            );
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    if !report.diagnostics.is_empty() {
+        anyhow::bail!("lint found {} problem(s)", report.diagnostics.len());
+    }
+    Ok(())
+}
+
+fn kind_of(group: &str) -> Option<Kind> {
+    match group {
+        AGENTS_GROUP => Some(Kind::Agent),
+        COMMANDS_GROUP => Some(Kind::Command),
+        RULES_GROUP => Some(Kind::Rule),
+        _ => None,
+    }
+}
+
+/// READMEs document a folder of agents or commands rather than define one.
+fn is_readme(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .is_some_and(|file| file.eq_ignore_ascii_case("README.md"))
+}
+
+fn lint_file(
+    provider: &str,
+    path: &str,
+    kind: Kind,
+    contents: &[u8],
+    rules: Option<&Rules>,
+    agents: &mut BTreeMap<String, String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut report = |line: usize, message: String| {
+        diagnostics.push(Diagnostic {
+            path: path.to_string(),
+            line,
+            message,
+        })
+    };
+
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let file = file.strip_suffix(TEMPLATE_SUFFIX).unwrap_or(file);
+    let extension = match kind {
+        Kind::Rule => ".mdc",
+        Kind::Agent | Kind::Command => ".md",
+    };
+    match file.strip_suffix(extension) {
+        Some(stem) if legal_name(stem) => {}
+        Some(_) => report(
+            1,
+            format!(
+                "file name '{file}' is not allowed; use lower-case letters, digits, '-' and '_'"
+            ),
+        ),
+        None => report(1, format!("'{file}' is not a {extension} file")),
+    }
+
+    if contents.is_empty() {
+        report(1, "file is empty".to_string());
+        return;
+    }
+    let Ok(text) = std::str::from_utf8(contents) else {
+        report(1, "file is not valid UTF-8".to_string());
+        return;
+    };
+    let frontmatter = match Frontmatter::parse(text) {
+        Ok(Some(frontmatter)) => frontmatter,
+        Ok(None) if kind == Kind::Command && PLAIN_COMMAND_PROVIDERS.contains(&provider) => {
+            if text.trim().is_empty() {
+                report(1, "no instructions in the command".to_string());
+            }
+            return;
+        }
+        Ok(None) => {
+            report(
+                1,
+                "missing frontmatter; the file must start with '---'".to_string(),
+            );
+            return;
+        }
+        Err(err) => {
+            report(err.line, err.message);
+            return;
+        }
+    };
+
+    let required: &[&str] = match kind {
+        Kind::Agent => &["name", "description"],
+        Kind::Command if PLAIN_COMMAND_PROVIDERS.contains(&provider) => &[],
+        Kind::Command => &["description"],
+        Kind::Rule => &[],
+    };
+    for key in required {
+        match frontmatter.get_field(key) {
+            Some(field) if field.value.trim().is_empty() => {
+                report(field.line, format!("'{key}' is empty"))
+            }
+            Some(_) => {}
+            None => report(1, format!("missing required frontmatter key '{key}'")),
+        }
+    }
+
+    if kind == Kind::Agent
+        && let Some(field) = frontmatter.get_field("name")
+        && !templated(&field.value)
+        && !field.value.is_empty()
+    {
+        if !legal_name(&field.value) {
+            report(
+                field.line,
+                format!(
+                    "agent name '{}' is not allowed; use lower-case letters, digits, '-' and '_'",
+                    field.value
+                ),
+            );
+        }
+        if let Some(first) = agents.get(&field.value) {
+            report(
+                field.line,
+                format!("agent name '{}' is already used by {}", field.value, first),
+            );
+        } else {
+            agents.insert(field.value.clone(), path.to_string());
+        }
+    }
+
+    if kind == Kind::Rule {
+        lint_rule(&frontmatter, &mut report);
+    } else if let Some(rules) = rules {
+        let tools_key = match kind {
+            Kind::Agent => "tools",
+            Kind::Command | Kind::Rule => "allowed-tools",
+        };
+        if let Some(field) = frontmatter.get_field(tools_key)
+            && !templated(&field.value)
+        {
+            for tool in tool_names(&field.value) {
+                if !rules.known_tool(tool) {
+                    report(
+                        field.line,
+                        format!("unknown tool '{tool}' in '{tools_key}'"),
+                    );
+                }
+            }
+        }
+        if let Some(field) = frontmatter.get_field("model")
+            && !templated(&field.value)
+        {
+            let model = field.value.trim();
+            if !rules.known_model(model) {
+                report(
+                    field.line,
+                    format!(
+                        "unknown model '{model}'; expected one of {}",
+                        rules.model_choices()
+                    ),
+                );
+            }
+        }
+    }
+
+    let body = text
+        .lines()
+        .skip(frontmatter.end_line)
+        .any(|line| !line.trim().is_empty());
+    if !body {
+        report(
+            frontmatter.end_line,
+            "no instructions after the frontmatter".to_string(),
+        );
+    }
+}
+
+/// Checks the frontmatter of a Cursor rule: only its own keys, a boolean
+/// `alwaysApply` and well-formed `globs`.
+fn lint_rule(frontmatter: &Frontmatter, report: &mut impl FnMut(usize, String)) {
+    for field in &frontmatter.fields {
+        if !RULE_KEYS.contains(&field.key.as_str()) {
+            report(
+                field.line,
+                format!(
+                    "unknown rule key '{}'; expected {}",
+                    field.key,
+                    RULE_KEYS.join(", ")
+                ),
+            );
+        }
+    }
+    if let Some(field) = frontmatter.get_field("alwaysApply")
+        && !matches!(field.value.as_str(), "true" | "false")
+    {
+        report(
+            field.line,
+            format!("alwaysApply must be true or false, found '{}'", field.value),
+        );
+    }
+    if let Some(field) = frontmatter.get_field("globs")
+        && !templated(&field.value)
+    {
+        for pattern in field.value.split([',', '\n']).map(str::trim) {
+            let pattern = pattern.strip_prefix("- ").unwrap_or(pattern);
+            if pattern.is_empty() {
+                continue;
+            }
+            if let Err(err) = globset::Glob::new(pattern) {
+                report(
+                    field.line,
+                    format!("invalid glob '{pattern}': {}", err.kind()),
+                );
+            }
+        }
+    }
+}
+
+/// Whether `name` may name an agent or an asset file: lower-case letters,
+/// digits, '-' and '_', not starting with either separator.
+pub fn legal_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(['-', '_'])
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Values with template placeholders are only known once rendered.
+fn templated(value: &str) -> bool {
+    value.contains("{{") || value.contains("{%")
+}
+
+/// Tool names in a `tools` or `allowed-tools` value, which may be a comma
+/// separated list, a flow list or a block list. Permission patterns such as
+/// `Bash(git add:*)` name the tool before the parenthesis.
+pub fn tool_names(value: &str) -> Vec<&str> {
+    tool_entries(value)
+        .into_iter()
+        .map(|entry| entry.split('(').next().unwrap_or(entry).trim())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// The entries of a `tools` or `allowed-tools` value as written, keeping
+/// permission patterns such as `Bash(git add:*)`.
+pub fn tool_entries(value: &str) -> Vec<&str> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .unwrap_or(value);
+    let mut names = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' | '\n' if depth == 0 => {
+                names.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    names.push(&value[start..]);
+    names
+        .into_iter()
+        .map(|name| {
+            let name = name.trim();
+            let name = name.strip_prefix("- ").unwrap_or(name).trim();
+            name.trim_matches(|c| c == '"' || c == '\'').trim()
+        })
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lints one file and returns `line: message` for each problem.
+    fn lint(provider: &str, path: &str, kind: Kind, text: &str) -> Vec<String> {
+        let mut diagnostics = Vec::new();
+        lint_file(
+            provider,
+            path,
+            kind,
+            text.as_bytes(),
+            rules(provider).as_ref(),
+            &mut BTreeMap::new(),
+            &mut diagnostics,
+        );
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| format!("{}: {}", diagnostic.line, diagnostic.message))
+            .collect()
+    }
+
+    fn agent(frontmatter: &str) -> String {
+        format!("---\n{frontmatter}---\n\nReview the change.\n")
+    }
+
+    #[test]
+    fn well_formed_files_pass() {
+        let text =
+            agent("name: reviewer\ndescription: Reviews code.\ntools: Read, Grep\nmodel: sonnet\n");
+        assert!(lint("claude", ".claude/agents/reviewer.md", Kind::Agent, &text).is_empty());
+        let text = "---\ndescription: Review a PR.\nallowed-tools: Bash(git diff:*), Read\n---\nReview $ARGUMENTS.\n";
+        assert!(
+            lint(
+                "claude",
+                ".claude/commands/review/pr.md",
+                Kind::Command,
+                text
+            )
+            .is_empty()
+        );
+        assert!(
+            lint(
+                "cursor",
+                ".cursor/commands/plan.md",
+                Kind::Command,
+                "# /plan\n\nPlan it.\n"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn file_names_must_be_legal() {
+        let text = agent("name: reviewer\ndescription: Reviews code.\n");
+        assert_eq!(
+            lint(
+                "claude",
+                ".claude/agents/Code Reviewer.md",
+                Kind::Agent,
+                &text
+            ),
+            [
+                "1: file name 'Code Reviewer.md' is not allowed; use lower-case letters, digits, '-' and '_'"
+            ]
+        );
+        assert_eq!(
+            lint("claude", ".claude/agents/reviewer.txt", Kind::Agent, &text),
+            ["1: 'reviewer.txt' is not a .md file"]
+        );
+        assert_eq!(
+            lint(
+                "cursor",
+                ".cursor/rules/style.md",
+                Kind::Rule,
+                "---\nalwaysApply: true\n---\nBe brief.\n"
+            ),
+            ["1: 'style.md' is not a .mdc file"]
+        );
+        // Templates are named after what they render.
+        assert!(
+            lint(
+                "claude",
+                "claude/agents/reviewer.md.jinja",
+                Kind::Agent,
+                &text
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn structure_problems_stop_the_checks() {
+        let path = ".claude/agents/reviewer.md";
+        assert_eq!(lint("claude", path, Kind::Agent, ""), ["1: file is empty"]);
+        assert_eq!(
+            lint("claude", path, Kind::Agent, "Review the change.\n"),
+            ["1: missing frontmatter; the file must start with '---'"]
+        );
+        assert_eq!(
+            lint(
+                "claude",
+                path,
+                Kind::Agent,
+                "---\nname reviewer\n---\nBody\n"
+            ),
+            ["2: expected `key: value`, found 'name reviewer'"]
+        );
+        assert_eq!(
+            lint(
+                "claude",
+                path,
+                Kind::Agent,
+                "---\nname: reviewer\ndescription: Reviews.\n---\n\n"
+            ),
+            ["4: no instructions after the frontmatter"]
+        );
+        assert_eq!(
+            lint("cursor", ".cursor/commands/plan.md", Kind::Command, " \n\n"),
+            ["1: no instructions in the command"]
+        );
+    }
+
+    #[test]
+    fn required_keys_must_be_set() {
+        let path = ".claude/agents/reviewer.md";
+        assert_eq!(
+            lint("claude", path, Kind::Agent, &agent("name: reviewer\n")),
+            ["1: missing required frontmatter key 'description'"]
+        );
+        assert_eq!(
+            lint(
+                "claude",
+                path,
+                Kind::Agent,
+                &agent("name: reviewer\ndescription:\n")
+            ),
+            ["3: 'description' is empty"]
+        );
+        assert_eq!(
+            lint(
+                "gemini",
+                ".gemini/commands/plan.md",
+                Kind::Command,
+                "---\nname: plan\n---\nPlan.\n"
+            ),
+            ["1: missing required frontmatter key 'description'"]
+        );
+        // Cursor commands need no frontmatter, so nothing in it is required.
+        assert!(
+            lint(
+                "cursor",
+                ".cursor/commands/plan.md",
+                Kind::Command,
+                "---\nname: plan\n---\nPlan.\n"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn agent_names_are_legal_and_unique() {
+        assert_eq!(
+            lint(
+                "claude",
+                ".claude/agents/reviewer.md",
+                Kind::Agent,
+                &agent("name: Code_Reviewer\ndescription: Reviews.\n")
+            ),
+            [
+                "2: agent name 'Code_Reviewer' is not allowed; use lower-case letters, digits, '-' and '_'"
+            ]
+        );
+        assert!(
+            lint(
+                "claude",
+                "claude/agents/x.md",
+                Kind::Agent,
+                &agent("name: {{ agent }}\ndescription: Reviews.\n")
+            )
+            .is_empty()
+        );
+
+        let mut agents = BTreeMap::new();
+        let mut diagnostics = Vec::new();
+        let text = agent("name: reviewer\ndescription: Reviews.\n");
+        for path in [
+            ".claude/agents/reviewer.md",
+            ".claude/agents/review/reviewer.md",
+        ] {
+            lint_file(
+                "claude",
+                path,
+                Kind::Agent,
+                text.as_bytes(),
+                None,
+                &mut agents,
+                &mut diagnostics,
+            );
+        }
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, ".claude/agents/review/reviewer.md");
+        assert_eq!(
+            diagnostics[0].message,
+            "agent name 'reviewer' is already used by .claude/agents/reviewer.md"
+        );
+    }
+
+    #[test]
+    fn tools_and_models_must_be_known() {
+        let path = ".claude/agents/reviewer.md";
+        assert_eq!(
+            lint(
+                "claude",
+                path,
+                Kind::Agent,
+                &agent(
+                    "name: reviewer\ndescription: Reviews.\ntools: Read, Reed, mcp__github__list_prs\nmodel: gpt-4\n"
+                )
+            ),
+            [
+                "4: unknown tool 'Reed' in 'tools'",
+                "5: unknown model 'gpt-4'; expected one of inherit, sonnet, opus, haiku, opusplan, claude-*",
+            ]
+        );
+        assert_eq!(
+            lint(
+                "claude",
+                ".claude/commands/x.md",
+                Kind::Command,
+                "---\ndescription: X.\nallowed-tools: [\"Bash(git:*)\", \"Shell\"]\n---\nDo X.\n"
+            ),
+            ["3: unknown tool 'Shell' in 'allowed-tools'"]
+        );
+        assert_eq!(
+            lint(
+                "gemini",
+                ".gemini/agents/x.md",
+                Kind::Agent,
+                &agent(
+                    "name: x\ndescription: X.\ntools:\n  - read_file\n  - Read\nmodel: gemini-2.5-pro\n"
+                )
+            ),
+            ["4: unknown tool 'Read' in 'tools'"]
+        );
+        // Providers without rules only get the structural checks.
+        assert!(
+            lint(
+                "roo",
+                ".roo/commands/x.md",
+                Kind::Command,
+                "---\ndescription: X.\nallowed-tools: Anything\nmodel: any\n---\nDo X.\n"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn cursor_rules_have_their_own_keys() {
+        let path = ".cursor/rules/style.mdc";
+        assert!(lint("cursor", path, Kind::Rule, "---\ndescription: Style.\nglobs: src/**/*.rs, *.toml\nalwaysApply: false\n---\nBe brief.\n").is_empty());
+        assert_eq!(
+            lint(
+                "cursor",
+                path,
+                Kind::Rule,
+                "---\nname: style\nalwaysApply: yes\nglobs: src/[a.rs\n---\nBe brief.\n"
+            ),
+            [
+                "2: unknown rule key 'name'; expected description, globs, alwaysApply",
+                "3: alwaysApply must be true or false, found 'yes'",
+                "4: invalid glob 'src/[a.rs': unclosed character class; missing ']'",
+            ]
+        );
+    }
+
+    #[test]
+    fn tool_lists_in_every_form() {
+        assert_eq!(tool_names("Read, Grep"), ["Read", "Grep"]);
+        assert_eq!(
+            tool_names("[\"Read\", 'Bash(git add:*)']"),
+            ["Read", "Bash"]
+        );
+        assert_eq!(tool_names("\n- Read\n- Bash(a, b)\n"), ["Read", "Bash"]);
+        assert_eq!(
+            tool_entries("Bash(git add:*, git commit:*), Read"),
+            ["Bash(git add:*, git commit:*)", "Read"]
+        );
+    }
+
+    #[test]
+    fn legal_names() {
+        for name in ["reviewer", "code-reviewer", "api_v2"] {
+            assert!(legal_name(name), "{name}");
+        }
+        for name in ["", "-x", "_x", "Reviewer", "code reviewer", "a.b"] {
+            assert!(!legal_name(name), "{name}");
+        }
+    }
+}
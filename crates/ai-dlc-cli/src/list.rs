@@ -0,0 +1,150 @@
+use clap::Parser;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::frontmatter;
+use crate::plan::OutputFormat;
+use crate::render::TEMPLATE_SUFFIX;
+use crate::templates::{TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct ListArgs {
+    /// Only list these providers.
+    #[arg(long, short)]
+    provider: Vec<String>,
+    /// Output format for the listing.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+#[derive(Serialize, Debug)]
+struct ProviderEntry {
+    name: String,
+    display_name: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_cli_version: Option<String>,
+    groups: Vec<GroupEntry>,
+}
+
+#[derive(Serialize, Debug)]
+struct GroupEntry {
+    name: String,
+    description: String,
+    optional: bool,
+    templates: Vec<TemplateEntry>,
+}
+
+#[derive(Serialize, Debug)]
+struct TemplateEntry {
+    /// `<provider>/<path>`, as accepted by `show`.
+    template: String,
+    /// Where scaffold writes it, before variables are rendered.
+    dest: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+pub fn handle_list(args: ListArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+
+    let mut providers = Vec::new();
+    for info in templates.available_providers()? {
+        if !args.provider.is_empty() && !args.provider.contains(&info.name) {
+            continue;
+        }
+        let Some(manifest) = templates.manifest(&info.name)? else {
+            continue;
+        };
+        let mut groups: BTreeMap<String, GroupEntry> = manifest
+            .groups
+            .iter()
+            .map(|(name, group)| {
+                let entry = GroupEntry {
+                    name: name.clone(),
+                    description: group.description.clone(),
+                    optional: group.optional,
+                    templates: Vec::new(),
+                };
+                (name.clone(), entry)
+            })
+            .collect();
+        for file in templates
+            .provider_templates(&info.name)?
+            .unwrap_or_default()
+        {
+            let group = groups
+                .entry(file.group.clone())
+                .or_insert_with(|| GroupEntry {
+                    name: file.group.clone(),
+                    description: String::new(),
+                    optional: file.optional,
+                    templates: Vec::new(),
+                });
+            let template = format!("{}/{}", info.name, file.source);
+            group.templates.push(TemplateEntry {
+                description: frontmatter::describe(&template, &file.contents),
+                template,
+                dest: file
+                    .path
+                    .strip_suffix(TEMPLATE_SUFFIX)
+                    .unwrap_or(&file.path)
+                    .to_string(),
+            });
+        }
+        providers.push(ProviderEntry {
+            name: manifest.name.clone(),
+            display_name: manifest.display_name().to_string(),
+            description: manifest.description.clone(),
+            min_cli_version: manifest.min_cli_version.as_ref().map(ToString::to_string),
+            groups: groups.into_values().collect(),
+        });
+    }
+    for name in &args.provider {
+        if !providers.iter().any(|provider| provider.name == *name) {
+            tracing::warn!("Provider '{}' not found in the templates.", name);
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text => {
+            for provider in &providers {
+                println!("{:<10} {}", provider.name, info_line(provider));
+                for group in &provider.groups {
+                    let optional = if group.optional { " (optional)" } else { "" };
+                    println!("  {:<10} {}{}", group.name, group.description, optional);
+                    for template in &group.templates {
+                        println!("    {} -> {}", template.template, template.dest);
+                        if let Some(description) = &template.description {
+                            println!("      {description}");
+                        }
+                    }
+                }
+            }
+            let count: usize = providers
+                .iter()
+                .flat_map(|provider| &provider.groups)
+                .map(|group| group.templates.len())
+                .sum();
+            println!("\n{} providers, {} templates", providers.len(), count);
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&providers)?),
+    }
+    Ok(())
+}
+
+fn info_line(provider: &ProviderEntry) -> String {
+    if provider.description.is_empty() {
+        provider.display_name.clone()
+    } else {
+        format!("{} - {}", provider.display_name, provider.description)
+    }
+}
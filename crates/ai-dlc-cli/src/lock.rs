@@ -0,0 +1,370 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::fsutil;
+use crate::plan::{FileAction, ScaffoldPlan};
+
+/// Location of the scaffold record, relative to the scaffold root.
+pub const LOCK_PATH: &str = ".ai-dlc/lock.json";
+
+/// Directory holding a copy of each managed file as it was written, used as
+/// the merge base by `upgrade`.
+pub const BASE_DIR: &str = ".ai-dlc/base";
+
+const LOCK_VERSION: u32 = 1;
+
+/// Record of everything ai-dlc has installed into a repository, keyed by
+/// provider. Files listed here are managed; anything else is hand-written.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Lockfile {
+    pub version: u32,
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderLock>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProviderLock {
+    /// Version of the CLI that last scaffolded this provider.
+    pub cli_version: String,
+    /// Content hash of the template tree the files were taken from.
+    pub templates_hash: String,
+    /// Asset groups that were selected; empty means every group that is not
+    /// optional.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    /// Template variable values the files were rendered with.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Template pack the files were taken from; absent for the embedded
+    /// templates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackRecord>,
+    /// Managed files keyed by their `/`-separated path under the scaffold root.
+    #[serde(default)]
+    pub files: BTreeMap<String, LockedFile>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackRecord {
+    /// The pack as passed to `--from`, for example `git+https://…#main` or
+    /// an archive path.
+    pub from: String,
+    /// Commit, or archive hash, the templates were read from, so the exact
+    /// version can be fetched again.
+    pub revision: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LockedFile {
+    /// Hash of the contents that were written: the template, or the template
+    /// merged into an existing file.
+    pub hash: String,
+    /// The file existed before ai-dlc first wrote to it, so it holds the
+    /// user's own contents and `clean` leaves it in place.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub adopted: bool,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCK_VERSION,
+            providers: BTreeMap::new(),
+        }
+    }
+}
+
+impl Lockfile {
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(LOCK_PATH)
+    }
+
+    /// Reads the lockfile under `root`, returning an empty record when the
+    /// repository has never been scaffolded.
+    pub fn load(root: &Path) -> anyhow::Result<Self> {
+        let path = Self::path(root);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read lockfile: {:?}", path));
+            }
+        };
+        let lock: Self = serde_json::from_str(&contents).with_context(|| {
+            format!(
+                "Failed to parse lockfile {:?}; restore it from version control, or delete \
+                     it to treat every file as unmanaged",
+                path
+            )
+        })?;
+        if lock.version > LOCK_VERSION {
+            anyhow::bail!(
+                "Lockfile {:?} has version {} but this CLI only understands version {}; \
+                 upgrade ai-dlc-cli.",
+                path,
+                lock.version,
+                LOCK_VERSION
+            );
+        }
+        Ok(lock)
+    }
+
+    /// Writes the record under `root`. An empty record removes the lockfile
+    /// instead, so a fully cleaned repository carries no ai-dlc state.
+    pub fn save(&self, root: &Path) -> anyhow::Result<()> {
+        let path = Self::path(root);
+        if self.providers.is_empty() {
+            // Every merge base has been unlocked by now, leaving at most an
+            // empty directory behind.
+            let _ = std::fs::remove_dir(root.join(BASE_DIR));
+            return fsutil::remove_file_and_prune(root, LOCK_PATH);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        std::fs::write(&path, json).with_context(|| format!("Failed to write lockfile: {:?}", path))
+    }
+
+    /// Folds the outcome of an applied plan into the record. Files now
+    /// holding the template are (re)locked with the template snapshotted as
+    /// the next merge base; skipped files keep whatever entry they had, so a
+    /// local edit is still recognisable as one.
+    pub fn record(
+        &mut self,
+        root: &Path,
+        plan: &ScaffoldPlan,
+        templates_hash: &str,
+        groups: &[String],
+    ) -> anyhow::Result<()> {
+        for file in &plan.files {
+            let existing = self.providers.contains_key(&file.provider);
+            let entry = self
+                .providers
+                .entry(file.provider.clone())
+                .or_insert_with(ProviderLock::new);
+            entry.select_groups(existing, groups);
+            entry.cli_version = env!("CARGO_PKG_VERSION").to_string();
+            entry.templates_hash = templates_hash.to_string();
+            match file.action {
+                FileAction::Create
+                | FileAction::Overwrite
+                | FileAction::Backup
+                | FileAction::Merge
+                | FileAction::Unchanged => {
+                    // A file that was already there, merged into or found
+                    // identical, is adopted rather than owned.
+                    let adopted = !entry.files.contains_key(&file.path)
+                        && matches!(file.action, FileAction::Merge | FileAction::Unchanged);
+                    let base = file.template.as_ref().unwrap_or(&file.contents);
+                    entry.lock_file(root, &file.path, &file.contents, base)?;
+                    if adopted {
+                        entry.adopt(&file.path);
+                    }
+                }
+                FileAction::Skip | FileAction::Conflict => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProviderLock {
+    pub fn new() -> Self {
+        Self {
+            cli_version: env!("CARGO_PKG_VERSION").to_string(),
+            templates_hash: String::new(),
+            groups: Vec::new(),
+            variables: BTreeMap::new(),
+            pack: None,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Widens the recorded group selection. Selecting nothing means every
+    /// group, which absorbs any narrower earlier selection.
+    fn select_groups(&mut self, existing: bool, groups: &[String]) {
+        if groups.is_empty() {
+            self.groups.clear();
+        } else if !existing {
+            self.groups = groups.to_vec();
+        } else if !self.groups.is_empty() {
+            for group in groups {
+                if !self.groups.contains(group) {
+                    self.groups.push(group.clone());
+                }
+            }
+            self.groups.sort();
+        }
+    }
+
+    /// Marks `path` as managed, written with `contents` from the template
+    /// `base`. A file that was adopted stays adopted.
+    pub fn lock_file(
+        &mut self,
+        root: &Path,
+        path: &str,
+        contents: &[u8],
+        base: &[u8],
+    ) -> anyhow::Result<()> {
+        save_base(root, path, base)?;
+        let adopted = self.files.get(path).is_some_and(|locked| locked.adopted);
+        self.files.insert(
+            path.to_string(),
+            LockedFile {
+                hash: hash_bytes(contents),
+                adopted,
+            },
+        );
+        Ok(())
+    }
+
+    /// Records that the locked `path` existed before ai-dlc wrote to it.
+    pub fn adopt(&mut self, path: &str) {
+        if let Some(locked) = self.files.get_mut(path) {
+            locked.adopted = true;
+        }
+    }
+
+    /// Stops managing `path`, dropping its entry and merge base.
+    pub fn unlock_file(&mut self, root: &Path, path: &str) -> anyhow::Result<()> {
+        self.files.remove(path);
+        fsutil::remove_file_and_prune(&root.join(BASE_DIR), path)
+    }
+}
+
+/// Reads the merge base recorded for `path`, if one was kept.
+pub fn load_base(root: &Path, path: &str) -> anyhow::Result<Option<Vec<u8>>> {
+    fsutil::read_optional(&root.join(BASE_DIR).join(path))
+}
+
+fn save_base(root: &Path, path: &str, contents: &[u8]) -> anyhow::Result<()> {
+    let base = root.join(BASE_DIR).join(path);
+    if let Some(parent) = base.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+    std::fs::write(&base, contents)
+        .with_context(|| format!("Failed to write merge base: {:?}", base))
+}
+
+/// Hashes file contents as `sha256:<hex>`.
+pub fn hash_bytes(contents: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::ConflictPolicy;
+
+    fn groups(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn records_and_reads_back_the_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut plan = ScaffoldPlan::new(ConflictPolicy::Skip);
+        plan.add(
+            "claude",
+            root,
+            "CLAUDE.md".to_string(),
+            b"# Memory\n".to_vec(),
+        )
+        .unwrap();
+        plan.add(
+            "claude",
+            root,
+            ".claude/agents/x.md".to_string(),
+            b"agent\n".to_vec(),
+        )
+        .unwrap();
+
+        let mut lock = Lockfile::default();
+        lock.record(root, &plan, "sha256:tree", &groups(&["core"]))
+            .unwrap();
+        lock.save(root).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(root.join(LOCK_PATH)).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "version": 1,
+                "providers": {
+                    "claude": {
+                        "cli_version": env!("CARGO_PKG_VERSION"),
+                        "templates_hash": "sha256:tree",
+                        "groups": ["core"],
+                        "files": {
+                            ".claude/agents/x.md": { "hash": hash_bytes(b"agent\n") },
+                            "CLAUDE.md": { "hash": hash_bytes(b"# Memory\n") },
+                        },
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            load_base(root, ".claude/agents/x.md").unwrap().as_deref(),
+            Some(&b"agent\n"[..])
+        );
+
+        let loaded = Lockfile::load(root).unwrap();
+        assert_eq!(
+            loaded.providers["claude"].files,
+            lock.providers["claude"].files
+        );
+
+        // Unlocking everything leaves no ai-dlc state behind.
+        let mut loaded = loaded;
+        let entry = loaded.providers.get_mut("claude").unwrap();
+        for path in ["CLAUDE.md", ".claude/agents/x.md"] {
+            entry.unlock_file(root, path).unwrap();
+        }
+        loaded.providers.clear();
+        loaded.save(root).unwrap();
+        assert!(!root.join(".ai-dlc").exists());
+    }
+
+    #[test]
+    fn group_selection_only_widens() {
+        let mut entry = ProviderLock::new();
+        entry.select_groups(false, &groups(&["commands"]));
+        assert_eq!(entry.groups, ["commands"]);
+        entry.select_groups(true, &groups(&["agents", "commands"]));
+        assert_eq!(entry.groups, ["agents", "commands"]);
+        // Selecting nothing means every group.
+        entry.select_groups(true, &[]);
+        assert!(entry.groups.is_empty());
+        entry.select_groups(true, &groups(&["agents"]));
+        assert!(entry.groups.is_empty());
+        // A provider recorded for the first time takes the selection as is.
+        let mut entry = ProviderLock::new();
+        entry.select_groups(false, &groups(&["agents"]));
+        assert_eq!(entry.groups, ["agents"]);
+    }
+
+    #[test]
+    fn unreadable_lockfiles_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert!(Lockfile::load(root).unwrap().providers.is_empty());
+
+        std::fs::create_dir_all(root.join(".ai-dlc")).unwrap();
+        std::fs::write(root.join(LOCK_PATH), "{\"version\": 1, \"providers\": ").unwrap();
+        let err = format!("{:#}", Lockfile::load(root).unwrap_err());
+        assert!(err.contains("Failed to parse lockfile"), "{err}");
+        assert!(err.contains("delete it"), "{err}");
+
+        std::fs::write(root.join(LOCK_PATH), "{\"version\": 2}").unwrap();
+        let err = format!("{:#}", Lockfile::load(root).unwrap_err());
+        assert!(err.contains("upgrade ai-dlc-cli"), "{err}");
+    }
+}
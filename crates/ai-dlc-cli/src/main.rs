@@ -1,8 +1,50 @@
-use anyhow::Context;
 use clap::{Parser, Subcommand};
-use include_dir::{Dir, DirEntry, include_dir};
-use std::ffi::OsStr;
-use std::path::Path;
+use include_dir::{Dir, include_dir};
+
+mod check_refs;
+mod clean;
+mod config;
+mod context;
+mod convert;
+mod frontmatter;
+mod fsutil;
+mod generate;
+mod initref;
+mod inspect;
+mod lint;
+mod list;
+mod lock;
+mod manifest;
+mod mcp;
+mod merge;
+mod pack;
+mod plan;
+mod probe;
+mod render;
+mod scaffold;
+mod show;
+mod signing;
+mod status;
+mod symbols;
+mod templates;
+mod upgrade;
+mod wizard;
+mod workspace;
+
+use check_refs::CheckRefsArgs;
+use clean::CleanArgs;
+use context::InitContextArgs;
+use convert::ConvertArgs;
+use generate::NewArgs;
+use initref::InitrefArgs;
+use lint::LintArgs;
+use list::ListArgs;
+use mcp::McpArgs;
+use pack::PackArgs;
+use scaffold::ScaffoldArgs;
+use show::ShowArgs;
+use status::StatusArgs;
+use upgrade::UpgradeArgs;
 
 // Embed provider templates directly from the crate so published packages
 // include the full asset set.
@@ -17,112 +59,62 @@ struct Cli {
 
 #[derive(Subcommand, Debug)]
 enum Commands {
+    /// Scaffold provider templates into the current repository.
     Scaffold(ScaffoldArgs),
-}
-
-#[derive(Parser, Debug)]
-struct ScaffoldArgs {
-    #[arg(long, short)]
-    provider: Vec<String>,
-    #[arg(long)]
-    all: bool,
+    /// Bring scaffolded files up to date with this release's templates,
+    /// merging local edits.
+    Upgrade(UpgradeArgs),
+    /// Report scaffolded files that drifted from the lockfile or templates.
+    Status(StatusArgs),
+    /// Remove files that ai-dlc scaffolded for a provider.
+    Clean(CleanArgs),
+    /// List providers, asset groups and templates.
+    List(ListArgs),
+    /// Print a template, optionally rendered.
+    Show(ShowArgs),
+    /// Build, sign and verify template pack archives.
+    Pack(PackArgs),
+    /// Create a new agent or slash command with the right frontmatter.
+    New(NewArgs),
+    /// Check agent and command files for frontmatter and structure problems.
+    Lint(LintArgs),
+    /// Report playbook references to commands, agents and paths that do not
+    /// exist.
+    CheckRefs(CheckRefsArgs),
+    /// Convert an agent or command between Claude, Gemini, Cursor and Roo
+    /// formats.
+    Convert(ConvertArgs),
+    /// Manage MCP servers across every provider's config from one definition.
+    Mcp(McpArgs),
+    /// Write a project briefing (CLAUDE.md, AGENTS.md, GEMINI.md) from what
+    /// the repository's build files, CI and layout say.
+    InitContext(InitContextArgs),
+    /// Write per-directory reference dossiers (ref/*.md) with public symbols,
+    /// entry points, dependencies and recent changes.
+    Initref(InitrefArgs),
 }
 
 fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    // Logs go to stderr so `show` output and JSON reports can be piped.
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .init();
     let cli = Cli::parse();
     match cli.command {
-        Commands::Scaffold(args) => handle_scaffold(args)?,
-    }
-    Ok(())
-}
-
-fn handle_scaffold(args: ScaffoldArgs) -> anyhow::Result<()> {
-    tracing::info!("Scaffolding templates...");
-
-    let providers_to_scaffold = if args.all {
-        TEMPLATES_DIR
-            .dirs()
-            .map(|d| d.path().to_str().unwrap().to_string())
-            .collect()
-    } else if args.provider.is_empty() {
-        tracing::warn!("No providers specified. Use --provider or --all. Exiting.");
-        return Ok(());
-    } else {
-        args.provider
-    };
-
-    tracing::info!("Scaffolding for providers: {:?}", providers_to_scaffold);
-
-    for provider_name in providers_to_scaffold {
-        if let Some(provider_dir) = TEMPLATES_DIR.get_dir(&provider_name) {
-            let hidden_dir_name = format!(".{}", provider_name);
-            let strip_prefix = provider_dir.path();
-
-            for dir in provider_dir.dirs() {
-                tracing::debug!(path = ?dir.path(), "provider subdir detected");
-            }
-
-            let target_name = OsStr::new(&hidden_dir_name);
-            let hidden_dir = provider_dir
-                .dirs()
-                .find(|dir| dir.path().file_name() == Some(target_name));
-
-            if let Some(hidden_dir) = hidden_dir {
-                tracing::info!(
-                    "Creating hidden provider directory: {} in current working directory",
-                    hidden_dir_name
-                );
-                extract_dir(hidden_dir, Path::new("."), strip_prefix)?;
-            } else {
-                tracing::warn!(
-                    "Provider '{}' does not contain '{}'; skipping.",
-                    provider_name,
-                    hidden_dir_name
-                );
-            }
-        } else {
-            tracing::warn!(
-                "Provider '{}' not found in embedded templates.",
-                provider_name
-            );
-        }
-    }
-
-    tracing::info!("Scaffolding complete.");
-    Ok(())
-}
-
-fn extract_dir(embedded_dir: &Dir, dest_root: &Path, strip_prefix: &Path) -> anyhow::Result<()> {
-    let relative_dir = embedded_dir
-        .path()
-        .strip_prefix(strip_prefix)
-        .unwrap_or_else(|_| embedded_dir.path());
-    let dir_path = dest_root.join(relative_dir);
-    std::fs::create_dir_all(&dir_path)
-        .with_context(|| format!("Failed to create directory: {:?}", dir_path))?;
-
-    for entry in embedded_dir.entries() {
-        let relative_path = entry
-            .path()
-            .strip_prefix(strip_prefix)
-            .unwrap_or_else(|_| entry.path());
-        let path = dest_root.join(relative_path);
-        match entry {
-            DirEntry::Dir(d) => {
-                extract_dir(d, dest_root, strip_prefix)?;
-            }
-            DirEntry::File(f) => {
-                if let Some(parent) = path.parent() {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directory: {:?}", parent)
-                    })?;
-                }
-                tracing::debug!("Writing file: {:?}", path);
-                std::fs::write(&path, f.contents())
-                    .with_context(|| format!("Failed to write file: {:?}", path))?;
-            }
-        }
+        Commands::Scaffold(args) => scaffold::handle_scaffold(args)?,
+        Commands::Upgrade(args) => upgrade::handle_upgrade(args)?,
+        Commands::Status(args) => status::handle_status(args)?,
+        Commands::Clean(args) => clean::handle_clean(args)?,
+        Commands::List(args) => list::handle_list(args)?,
+        Commands::Show(args) => show::handle_show(args)?,
+        Commands::Pack(args) => pack::handle_pack(args)?,
+        Commands::New(args) => generate::handle_new(args)?,
+        Commands::Lint(args) => lint::handle_lint(args)?,
+        Commands::CheckRefs(args) => check_refs::handle_check_refs(args)?,
+        Commands::Convert(args) => convert::handle_convert(args)?,
+        Commands::Mcp(args) => mcp::handle_mcp(args)?,
+        Commands::InitContext(args) => context::handle_init_context(args)?,
+        Commands::Initref(args) => initref::handle_initref(args)?,
     }
     Ok(())
 }
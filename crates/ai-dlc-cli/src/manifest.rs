@@ -0,0 +1,383 @@
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::fsutil;
+use crate::merge::{MergeStrategy, SettingsFormat};
+use crate::render::TEMPLATE_SUFFIX;
+use crate::scaffold::TemplateFile;
+
+/// File at the top of each provider directory describing what it installs.
+pub const MANIFEST_FILE: &str = "provider.toml";
+
+/// Group for mapped files that do not name one.
+pub const DEFAULT_GROUP: &str = "core";
+
+/// Group whose directory mapping says where a provider keeps agents.
+pub const AGENTS_GROUP: &str = "agents";
+
+/// Group whose directory mapping says where a provider keeps slash commands.
+pub const COMMANDS_GROUP: &str = "commands";
+
+/// Group whose directory mapping says where a provider keeps rules, such as
+/// Cursor's `.mdc` files.
+pub const RULES_GROUP: &str = "rules";
+
+/// A provider's `provider.toml`: its metadata and where each of its files is
+/// installed in the target repository.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Provider name; must match the directory holding the manifest.
+    pub name: String,
+    /// Human-readable name, such as `Claude Code`.
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Oldest CLI release that understands this provider's templates.
+    pub min_cli_version: Option<semver::Version>,
+    /// Project briefing file `init-context` writes for this provider, such as
+    /// `CLAUDE.md`, rendered from `context/<file>.jinja`.
+    pub context: Option<String>,
+    #[serde(default)]
+    pub groups: BTreeMap<String, GroupInfo>,
+    #[serde(default)]
+    pub mappings: Vec<Mapping>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct GroupInfo {
+    #[serde(default)]
+    pub description: String,
+    /// Only installed when explicitly selected.
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Installs `source`, relative to the provider directory, at `dest`,
+/// relative to the repository root. A `source` ending in `/` maps every file
+/// below that directory, and `dest` must then end in `/` as well.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Mapping {
+    pub source: String,
+    pub dest: String,
+    pub group: Option<String>,
+    /// Merge JSON, TOML and YAML files into existing ones instead of
+    /// replacing them, combining arrays this way.
+    pub merge: Option<MergeStrategy>,
+}
+
+impl Manifest {
+    /// Parses and validates the manifest of `provider`.
+    pub fn parse(provider: &str, contents: &str) -> anyhow::Result<Self> {
+        let manifest: Self = toml::from_str(contents)
+            .with_context(|| format!("Failed to parse {MANIFEST_FILE} of provider '{provider}'"))?;
+        if manifest.name != provider {
+            anyhow::bail!(
+                "{} in '{}' declares provider '{}'; the name must match its directory.",
+                MANIFEST_FILE,
+                provider,
+                manifest.name
+            );
+        }
+        for mapping in &manifest.mappings {
+            if mapping.source.ends_with('/') != mapping.dest.ends_with('/') {
+                anyhow::bail!(
+                    "{} of provider '{}': mapping '{}' -> '{}' must map a directory to a \
+                     directory (both ending in '/') or a file to a file.",
+                    MANIFEST_FILE,
+                    provider,
+                    mapping.source,
+                    mapping.dest
+                );
+            }
+            if mapping.merge.is_some()
+                && !mapping.dest.ends_with('/')
+                && SettingsFormat::of(&mapping.dest).is_none()
+            {
+                anyhow::bail!(
+                    "{} of provider '{}': '{}' sets merge, which only applies to JSON, TOML and \
+                     YAML files.",
+                    MANIFEST_FILE,
+                    provider,
+                    mapping.dest
+                );
+            }
+            if mapping.dest.is_empty() || fsutil::escapes(&mapping.dest) {
+                anyhow::bail!(
+                    "{} of provider '{}': destination '{}' must be a relative path inside \
+                     the repository.",
+                    MANIFEST_FILE,
+                    provider,
+                    mapping.dest
+                );
+            }
+        }
+        if let Some(context) = &manifest.context
+            && (context.is_empty() || context.ends_with('/') || fsutil::escapes(context))
+        {
+            anyhow::bail!(
+                "{} of provider '{}': context file '{}' must be a relative file path inside \
+                 the repository.",
+                MANIFEST_FILE,
+                provider,
+                context
+            );
+        }
+        Ok(manifest)
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Fails when this CLI is older than the provider requires.
+    pub fn check_cli_version(&self) -> anyhow::Result<()> {
+        let Some(required) = &self.min_cli_version else {
+            return Ok(());
+        };
+        let current = semver::Version::parse(env!("CARGO_PKG_VERSION"))?;
+        if current < *required {
+            anyhow::bail!(
+                "Provider '{}' requires ai-dlc-cli {} or newer, but this is {}; upgrade ai-dlc-cli.",
+                self.name,
+                required,
+                current
+            );
+        }
+        Ok(())
+    }
+
+    /// Destination of the first directory mapping in `group`, where new
+    /// assets of that kind belong.
+    pub fn group_dir(&self, group: &str) -> Option<&str> {
+        self.mappings
+            .iter()
+            .find(|mapping| {
+                mapping.dest.ends_with('/')
+                    && mapping.group.as_deref().unwrap_or(DEFAULT_GROUP) == group
+            })
+            .map(|mapping| mapping.dest.as_str())
+    }
+
+    /// Places the provider's files, given by their `/`-separated path within
+    /// the provider directory, at their destinations. Files no mapping covers,
+    /// such as the manifest itself, are not installed.
+    pub fn install_set(&self, files: Vec<(String, Vec<u8>)>) -> Vec<TemplateFile> {
+        files
+            .into_iter()
+            .filter_map(|(source, contents)| {
+                let (mapping, path) = self
+                    .mappings
+                    .iter()
+                    .find_map(|mapping| Some((mapping, mapping.destination(&source)?)))?;
+                let group = mapping
+                    .group
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_GROUP.to_string());
+                let merge = mapping
+                    .merge
+                    .filter(|_| SettingsFormat::of(&path).is_some());
+                Some(TemplateFile {
+                    path,
+                    source,
+                    optional: self.groups.get(&group).is_some_and(|info| info.optional),
+                    group,
+                    merge,
+                    contents,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Mapping {
+    /// Where `source` is installed, if this mapping covers it.
+    fn destination(&self, source: &str) -> Option<String> {
+        let dest = if self.source.ends_with('/') {
+            format!("{}{}", self.dest, source.strip_prefix(&self.source)?)
+        } else if source == self.source {
+            self.dest.clone()
+        } else {
+            return None;
+        };
+        // A template renamed by its mapping keeps the suffix that marks it
+        // for rendering; the renderer strips it again.
+        if source.ends_with(TEMPLATE_SUFFIX) && !dest.ends_with(TEMPLATE_SUFFIX) {
+            Some(dest + TEMPLATE_SUFFIX)
+        } else {
+            Some(dest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(body: &str) -> anyhow::Result<Manifest> {
+        Manifest::parse("demo", &format!("name = \"demo\"\n{body}"))
+    }
+
+    fn mapping(source: &str, dest: &str) -> Mapping {
+        Mapping {
+            source: source.to_string(),
+            dest: dest.to_string(),
+            group: None,
+            merge: None,
+        }
+    }
+
+    #[test]
+    fn destinations_of_directory_and_file_mappings() {
+        let dir = mapping("agents/", ".claude/agents/");
+        assert_eq!(
+            dir.destination("agents/review/pr.md").as_deref(),
+            Some(".claude/agents/review/pr.md")
+        );
+        assert_eq!(dir.destination("commands/pr.md"), None);
+        assert_eq!(dir.destination("agents-old/pr.md"), None);
+
+        let file = mapping("settings.json", ".claude/settings.json");
+        assert_eq!(
+            file.destination("settings.json").as_deref(),
+            Some(".claude/settings.json")
+        );
+        assert_eq!(file.destination("settings.json.bak"), None);
+    }
+
+    #[test]
+    fn renamed_templates_keep_their_suffix() {
+        let file = mapping("CLAUDE.md.jinja", "CLAUDE.md");
+        assert_eq!(
+            file.destination("CLAUDE.md.jinja").as_deref(),
+            Some("CLAUDE.md.jinja")
+        );
+        let file = mapping("memory.md.jinja", "CLAUDE.md.jinja");
+        assert_eq!(
+            file.destination("memory.md.jinja").as_deref(),
+            Some("CLAUDE.md.jinja")
+        );
+        let dir = mapping("agents/", ".claude/agents/");
+        assert_eq!(
+            dir.destination("agents/x.md.jinja").as_deref(),
+            Some(".claude/agents/x.md.jinja")
+        );
+    }
+
+    #[test]
+    fn install_set_places_files_in_their_groups() {
+        let manifest = manifest(
+            "[groups.extras]\noptional = true\n\
+             [[mappings]]\nsource = \"README.md\"\ndest = \"AGENTS.md\"\n\
+             [[mappings]]\nsource = \"extras/\"\ndest = \".demo/extras/\"\ngroup = \"extras\"\n\
+             [[mappings]]\nsource = \"settings.json\"\ndest = \".demo/settings.json\"\nmerge = \"union\"\n",
+        )
+        .unwrap();
+        let files = manifest.install_set(vec![
+            ("README.md".to_string(), b"readme".to_vec()),
+            ("extras/a.md".to_string(), b"a".to_vec()),
+            ("settings.json".to_string(), b"{}".to_vec()),
+            (MANIFEST_FILE.to_string(), Vec::new()),
+        ]);
+        let placed: Vec<(&str, &str, bool, bool)> = files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.as_str(),
+                    file.group.as_str(),
+                    file.optional,
+                    file.merge.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            placed,
+            [
+                ("AGENTS.md", "core", false, false),
+                (".demo/extras/a.md", "extras", true, false),
+                (".demo/settings.json", "core", false, true),
+            ]
+        );
+        assert_eq!(manifest.group_dir("extras"), Some(".demo/extras/"));
+        assert_eq!(manifest.group_dir("core"), None);
+    }
+
+    #[test]
+    fn destinations_and_context_must_stay_inside_the_repository() {
+        for dest in [
+            "",
+            "../outside.md",
+            "docs/../../outside.md",
+            "/etc/x.md",
+            "C:/x.md",
+        ] {
+            let err = manifest(&format!(
+                "[[mappings]]\nsource = \"x.md\"\ndest = \"{dest}\"\n"
+            ))
+            .unwrap_err();
+            assert!(
+                err.to_string().contains("must be a relative path inside"),
+                "{dest}: {err}"
+            );
+        }
+        for context in ["", "docs/", "../CLAUDE.md", "/CLAUDE.md"] {
+            let err = manifest(&format!("context = \"{context}\"\n")).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("must be a relative file path inside"),
+                "{context}: {err}"
+            );
+        }
+        assert!(manifest("context = \"docs/CLAUDE.md\"\n").is_ok());
+    }
+
+    #[test]
+    fn mappings_are_validated() {
+        let err = manifest("[[mappings]]\nsource = \"agents/\"\ndest = \".demo/agents.md\"\n")
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("must map a directory to a directory"),
+            "{err}"
+        );
+
+        let err = manifest(
+            "[[mappings]]\nsource = \"notes.md\"\ndest = \"notes.md\"\nmerge = \"union\"\n",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("sets merge"), "{err}");
+
+        let err = Manifest::parse("other", "name = \"demo\"\n").unwrap_err();
+        assert!(
+            err.to_string().contains("must match its directory"),
+            "{err}"
+        );
+
+        let err = manifest("unknown = 1\n").unwrap_err();
+        assert!(format!("{err:#}").contains("unknown field"), "{err:#}");
+    }
+
+    #[test]
+    fn min_cli_version_is_enforced() {
+        assert!(manifest("").unwrap().check_cli_version().is_ok());
+        let current = env!("CARGO_PKG_VERSION");
+        let same = manifest(&format!("min_cli_version = \"{current}\"\n")).unwrap();
+        assert!(same.check_cli_version().is_ok());
+
+        let newer = manifest("min_cli_version = \"99.0.0\"\n").unwrap();
+        let err = newer.check_cli_version().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Provider 'demo' requires ai-dlc-cli 99.0.0 or newer, but this is {current}; \
+                 upgrade ai-dlc-cli."
+            )
+        );
+
+        let err = manifest("min_cli_version = \"soon\"\n").unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to parse"), "{err:#}");
+    }
+}
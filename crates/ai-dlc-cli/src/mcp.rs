@@ -0,0 +1,1065 @@
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock::Lockfile;
+use crate::merge;
+use crate::plan::OutputFormat;
+use crate::probe::{self, ProbeArgs};
+use crate::render::parse_var;
+use crate::workspace::{CONFIG_FILE, RootArgs};
+
+/// Key holding the servers in every provider's MCP config.
+const SERVERS_KEY: &str = "mcpServers";
+
+/// Keys of a provider's server entry that a definition sets. Anything else in
+/// an entry, such as Roo's `alwaysAllow`, belongs to the user and is kept.
+const DEFINITION_KEYS: &[&str] = &[
+    "type", "command", "args", "env", "url", "httpUrl", "headers",
+];
+
+/// How a client talks to an MCP server.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    /// A local process spoken to over stdin and stdout.
+    #[default]
+    Stdio,
+    /// A remote server over streamable HTTP.
+    Http,
+    /// A remote server over server-sent events.
+    Sse,
+}
+
+/// An MCP server as defined under `[mcp.servers.<name>]` in ai-dlc.toml,
+/// written to each provider's config in its own schema.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct McpServer {
+    #[serde(default)]
+    pub transport: Transport,
+    /// Program started for a stdio server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Endpoint of an HTTP or SSE server.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// A provider whose MCP config ai-dlc can edit.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum McpProvider {
+    /// `.mcp.json`
+    Claude,
+    /// `.cursor/mcp.json`
+    Cursor,
+    /// `mcpServers` in `.gemini/settings.json`
+    Gemini,
+    /// `.roo/mcp.json`
+    Roo,
+}
+
+#[derive(Parser, Debug)]
+pub struct McpArgs {
+    #[command(subcommand)]
+    command: McpCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum McpCommand {
+    /// Define a server in ai-dlc.toml and add it to each provider's config.
+    Add(AddArgs),
+    /// Remove a server from ai-dlc.toml and every provider's config.
+    Remove(RemoveArgs),
+    /// Show the defined servers and whether each provider's config matches.
+    List(ListArgs),
+    /// Write every server defined in ai-dlc.toml to each provider's config.
+    Sync(TargetArgs),
+    /// Start each stdio server, run the initialize handshake and list what
+    /// it offers.
+    Probe(ProbeArgs),
+}
+
+#[derive(Args, Debug)]
+struct AddArgs {
+    /// Server name, the key it is listed under in every config.
+    name: String,
+    /// Transport; `http` when --url is given, else `stdio`.
+    #[arg(long, value_enum)]
+    transport: Option<Transport>,
+    /// Endpoint of an HTTP or SSE server.
+    #[arg(long)]
+    url: Option<String>,
+    /// Environment variable for a stdio server, such as `--env API_KEY=${API_KEY}`.
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_var)]
+    env: Vec<(String, String)>,
+    /// Header sent to an HTTP or SSE server.
+    #[arg(long = "header", value_name = "KEY=VALUE", value_parser = parse_var)]
+    headers: Vec<(String, String)>,
+    /// Replace a server already defined under this name.
+    #[arg(long)]
+    force: bool,
+    #[command(flatten)]
+    target: TargetArgs,
+    /// Command that starts a stdio server, and its arguments, after `--`.
+    #[arg(last = true, value_name = "COMMAND")]
+    command: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+struct RemoveArgs {
+    /// Server to remove.
+    name: String,
+    #[command(flatten)]
+    target: TargetArgs,
+}
+
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// Only compare these providers' configs. Defaults to those in use.
+    #[arg(long, short, value_enum)]
+    provider: Vec<McpProvider>,
+    /// Output format for the listing.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+#[derive(Args, Debug)]
+struct TargetArgs {
+    /// Providers whose configs are edited. Defaults to those scaffolded or
+    /// with an MCP config already.
+    #[arg(long, short, value_enum)]
+    provider: Vec<McpProvider>,
+    /// Report the files that would change without writing them.
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+/// New contents for a file, and what it held before.
+struct Edit {
+    path: String,
+    old: Option<String>,
+    new: String,
+}
+
+#[derive(Serialize, Debug)]
+struct ServerEntry {
+    name: String,
+    /// `None` for servers only found in a provider's config.
+    definition: Option<McpServer>,
+    /// How an undefined server is configured, as read from a provider.
+    #[serde(skip)]
+    configured: Option<McpServer>,
+    providers: Vec<ProviderState>,
+}
+
+#[derive(Serialize, Debug)]
+struct ProviderState {
+    provider: McpProvider,
+    path: &'static str,
+    state: State,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum State {
+    InSync,
+    Differs,
+    Missing,
+    /// Configured by hand, with no definition in ai-dlc.toml.
+    Unmanaged,
+}
+
+pub fn handle_mcp(args: McpArgs) -> anyhow::Result<()> {
+    match args.command {
+        McpCommand::Add(args) => add(args),
+        McpCommand::Remove(args) => remove(args),
+        McpCommand::List(args) => list(args),
+        McpCommand::Sync(args) => sync(args),
+        McpCommand::Probe(args) => probe::handle_probe(args),
+    }
+}
+
+fn add(args: AddArgs) -> anyhow::Result<()> {
+    check_name(&args.name)?;
+    let transport = args.transport.unwrap_or(if args.url.is_some() {
+        Transport::Http
+    } else {
+        Transport::Stdio
+    });
+    let mut command = args.command.into_iter();
+    let server = McpServer {
+        transport,
+        command: command.next(),
+        args: command.collect(),
+        env: args.env.into_iter().collect(),
+        url: args.url,
+        headers: args.headers.into_iter().collect(),
+    };
+    server.validate(&args.name)?;
+
+    let root = &args.target.root.resolve()?;
+    let config = Config::load(root)?;
+    match config.mcp.servers.get(&args.name) {
+        Some(existing) if *existing != server && !args.force => anyhow::bail!(
+            "Server '{}' is already defined in {}; pass --force to replace it.",
+            args.name,
+            CONFIG_FILE
+        ),
+        _ => {}
+    }
+    let providers = target_providers(root, &args.target.provider)?;
+
+    let mut edits = Vec::new();
+    edits.extend(edit_config(root, |servers| {
+        servers.insert(&args.name, toml_edit::Item::Table(server.to_toml()));
+    })?);
+    for provider in &providers {
+        edits.extend(edit_servers(root, *provider, |servers| {
+            provider.merge(servers, &args.name, &server);
+        })?);
+    }
+    apply(root, &edits, args.target.dry_run)?;
+    println!();
+    println!(
+        "Server '{}' defined in {} and {} {} provider config(s).",
+        args.name,
+        CONFIG_FILE,
+        if args.target.dry_run {
+            "would be added to"
+        } else {
+            "added to"
+        },
+        providers.len()
+    );
+    Ok(())
+}
+
+fn remove(args: RemoveArgs) -> anyhow::Result<()> {
+    let root = &args.target.root.resolve()?;
+    let config = Config::load(root)?;
+    let providers = target_providers(root, &args.target.provider)?;
+
+    let mut edits = Vec::new();
+    if config.mcp.servers.contains_key(&args.name) {
+        edits.extend(edit_config(root, |servers| {
+            servers.remove(&args.name);
+        })?);
+    }
+    let mut found = edits.len();
+    for provider in &providers {
+        let edit = edit_servers(root, *provider, |servers| {
+            servers.shift_remove(&args.name);
+        })?;
+        found += usize::from(edit.is_some());
+        edits.extend(edit);
+    }
+    if found == 0 {
+        anyhow::bail!(
+            "Server '{}' is not defined in {} or configured for {}.",
+            args.name,
+            CONFIG_FILE,
+            provider_names(&providers)
+        );
+    }
+    apply(root, &edits, args.target.dry_run)?;
+    Ok(())
+}
+
+fn sync(args: TargetArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    if config.mcp.servers.is_empty() {
+        anyhow::bail!(
+            "No MCP servers are defined in {}; add one with `ai-dlc-cli mcp add`.",
+            CONFIG_FILE
+        );
+    }
+    for (name, server) in &config.mcp.servers {
+        server.validate(name)?;
+    }
+    let providers = target_providers(root, &args.provider)?;
+
+    let mut edits = Vec::new();
+    for provider in &providers {
+        edits.extend(edit_servers(root, *provider, |servers| {
+            for (name, server) in &config.mcp.servers {
+                provider.merge(servers, name, server);
+            }
+        })?);
+    }
+    apply(root, &edits, args.dry_run)?;
+    println!();
+    println!(
+        "{} server(s) synced to {}: {} file(s) {}.",
+        config.mcp.servers.len(),
+        provider_names(&providers),
+        edits.len(),
+        if args.dry_run {
+            "would change"
+        } else {
+            "changed"
+        }
+    );
+    Ok(())
+}
+
+fn list(args: ListArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    let providers = if args.provider.is_empty() {
+        providers_in_use(root)?
+    } else {
+        args.provider
+    };
+
+    let mut entries: BTreeMap<String, ServerEntry> = config
+        .mcp
+        .servers
+        .iter()
+        .map(|(name, server)| {
+            let entry = ServerEntry {
+                name: name.clone(),
+                definition: Some(server.clone()),
+                configured: None,
+                providers: Vec::new(),
+            };
+            (name.clone(), entry)
+        })
+        .collect();
+    for provider in &providers {
+        let present = read_servers(root, *provider)?.unwrap_or_default();
+        for entry in entries.values_mut() {
+            let Some(server) = &entry.definition else {
+                continue;
+            };
+            let state = match present.get(&entry.name) {
+                None => State::Missing,
+                Some(native) if provider.matches(native, server) => State::InSync,
+                Some(_) => State::Differs,
+            };
+            entry.providers.push(ProviderState {
+                provider: *provider,
+                path: provider.config_path(),
+                state,
+            });
+        }
+        for (name, native) in &present {
+            let entry = entries.entry(name.clone()).or_insert_with(|| ServerEntry {
+                name: name.clone(),
+                definition: None,
+                configured: provider.definition(native),
+                providers: Vec::new(),
+            });
+            if entry.definition.is_none() {
+                entry.providers.push(ProviderState {
+                    provider: *provider,
+                    path: provider.config_path(),
+                    state: State::Unmanaged,
+                });
+            }
+        }
+    }
+
+    let entries: Vec<ServerEntry> = entries.into_values().collect();
+    match args.format {
+        OutputFormat::Text => {
+            for entry in &entries {
+                match &entry.definition {
+                    Some(server) => println!("{} ({})", entry.name, server.summary()),
+                    None => match &entry.configured {
+                        Some(server) => println!(
+                            "{} ({}; not defined in {})",
+                            entry.name,
+                            server.summary(),
+                            CONFIG_FILE
+                        ),
+                        None => println!("{} (not defined in {})", entry.name, CONFIG_FILE),
+                    },
+                }
+                for state in &entry.providers {
+                    println!(
+                        "  {:<10} {:<10} {}",
+                        format!("{:?}", state.provider).to_lowercase(),
+                        state.state.label(),
+                        state.path
+                    );
+                }
+            }
+            if !entries.is_empty() {
+                println!();
+            }
+            println!(
+                "{} server(s) defined in {}; compared with {}",
+                config.mcp.servers.len(),
+                CONFIG_FILE,
+                provider_names(&providers)
+            );
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+    }
+    Ok(())
+}
+
+impl McpServer {
+    fn validate(&self, name: &str) -> anyhow::Result<()> {
+        match self.transport {
+            Transport::Stdio if self.command.is_none() => anyhow::bail!(
+                "Server '{}' uses stdio but has no command; pass it after `--`, such as \
+                 `mcp add {} -- npx -y @scope/server`.",
+                name,
+                name
+            ),
+            Transport::Stdio if self.url.is_some() || !self.headers.is_empty() => anyhow::bail!(
+                "Server '{}' uses stdio, which takes no URL or headers; pass --transport http \
+                 or sse for a remote server.",
+                name
+            ),
+            Transport::Http | Transport::Sse if self.url.is_none() => {
+                anyhow::bail!("Server '{}' is remote but has no --url.", name)
+            }
+            Transport::Http | Transport::Sse
+                if self.command.is_some() || !self.args.is_empty() || !self.env.is_empty() =>
+            {
+                anyhow::bail!(
+                    "Server '{}' is remote, which takes no command or environment; drop them or \
+                     use --transport stdio.",
+                    name
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// One line describing how the server is reached.
+    pub fn summary(&self) -> String {
+        match self.transport {
+            Transport::Stdio => {
+                let mut line = format!("stdio: {}", self.command.as_deref().unwrap_or_default());
+                for arg in &self.args {
+                    line.push(' ');
+                    line.push_str(arg);
+                }
+                line
+            }
+            Transport::Http => format!("http: {}", self.url.as_deref().unwrap_or_default()),
+            Transport::Sse => format!("sse: {}", self.url.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn to_toml(&self) -> toml_edit::Table {
+        let mut table = toml_edit::Table::new();
+        table.insert(
+            "transport",
+            toml_edit::value(format!("{:?}", self.transport).to_lowercase()),
+        );
+        if let Some(command) = &self.command {
+            table.insert("command", toml_edit::value(command));
+        }
+        if !self.args.is_empty() {
+            table.insert(
+                "args",
+                toml_edit::value(self.args.iter().collect::<toml_edit::Array>()),
+            );
+        }
+        if let Some(url) = &self.url {
+            table.insert("url", toml_edit::value(url));
+        }
+        for (key, values) in [("env", &self.env), ("headers", &self.headers)] {
+            if !values.is_empty() {
+                let inline: toml_edit::InlineTable = values
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), value))
+                    .collect();
+                table.insert(key, toml_edit::value(inline));
+            }
+        }
+        table
+    }
+}
+
+impl McpProvider {
+    const ALL: [McpProvider; 4] = [
+        McpProvider::Claude,
+        McpProvider::Cursor,
+        McpProvider::Gemini,
+        McpProvider::Roo,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            McpProvider::Claude => "claude",
+            McpProvider::Cursor => "cursor",
+            McpProvider::Gemini => "gemini",
+            McpProvider::Roo => "roo",
+        }
+    }
+
+    /// Where the provider reads MCP servers from, relative to the root.
+    pub fn config_path(self) -> &'static str {
+        match self {
+            McpProvider::Claude => ".mcp.json",
+            McpProvider::Cursor => ".cursor/mcp.json",
+            McpProvider::Gemini => ".gemini/settings.json",
+            McpProvider::Roo => ".roo/mcp.json",
+        }
+    }
+
+    /// `server` in this provider's schema.
+    fn native(self, server: &McpServer) -> Map<String, Value> {
+        let mut entry = Map::new();
+        match server.transport {
+            Transport::Stdio => {
+                if self == McpProvider::Claude {
+                    entry.insert("type".into(), "stdio".into());
+                }
+                entry.insert("command".into(), server.command.clone().into());
+                if !server.args.is_empty() {
+                    entry.insert("args".into(), server.args.clone().into());
+                }
+                if !server.env.is_empty() {
+                    entry.insert("env".into(), string_map(&server.env));
+                }
+            }
+            Transport::Http | Transport::Sse => {
+                let sse = server.transport == Transport::Sse;
+                let url = server.url.clone().into();
+                match self {
+                    McpProvider::Claude => {
+                        entry.insert("type".into(), if sse { "sse" } else { "http" }.into());
+                        entry.insert("url".into(), url);
+                    }
+                    McpProvider::Roo => {
+                        let kind = if sse { "sse" } else { "streamable-http" };
+                        entry.insert("type".into(), kind.into());
+                        entry.insert("url".into(), url);
+                    }
+                    // Gemini tells the transports apart by key.
+                    McpProvider::Gemini if !sse => {
+                        entry.insert("httpUrl".into(), url);
+                    }
+                    // Cursor picks the transport itself.
+                    McpProvider::Gemini | McpProvider::Cursor => {
+                        entry.insert("url".into(), url);
+                    }
+                }
+                if !server.headers.is_empty() {
+                    entry.insert("headers".into(), string_map(&server.headers));
+                }
+            }
+        }
+        entry
+    }
+
+    /// Reads a server entry from this provider's config back into a
+    /// definition, if it has a recognisable command or URL.
+    pub fn definition(self, entry: &Value) -> Option<McpServer> {
+        let strings = |key: &str| -> BTreeMap<String, String> {
+            entry
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|values| {
+                    values
+                        .iter()
+                        .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.into())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        if let Some(command) = entry.get("command").and_then(Value::as_str) {
+            return Some(McpServer {
+                transport: Transport::Stdio,
+                command: Some(command.to_string()),
+                args: entry
+                    .get("args")
+                    .and_then(Value::as_array)
+                    .map(|args| {
+                        args.iter()
+                            .filter_map(|arg| Some(arg.as_str()?.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                env: strings("env"),
+                ..McpServer::default()
+            });
+        }
+        let (url, transport) = match (
+            entry.get("httpUrl").and_then(Value::as_str),
+            entry.get("url").and_then(Value::as_str),
+        ) {
+            (Some(url), _) => (url, Transport::Http),
+            (None, Some(url)) => {
+                let sse = match entry.get("type").and_then(Value::as_str) {
+                    Some(kind) => kind == "sse",
+                    None => self == McpProvider::Gemini,
+                };
+                (url, if sse { Transport::Sse } else { Transport::Http })
+            }
+            (None, None) => return None,
+        };
+        Some(McpServer {
+            transport,
+            url: Some(url.to_string()),
+            headers: strings("headers"),
+            ..McpServer::default()
+        })
+    }
+
+    /// Whether `entry` already says what `server` would write.
+    fn matches(self, entry: &Value, server: &McpServer) -> bool {
+        let native = self.native(server);
+        DEFINITION_KEYS
+            .iter()
+            .all(|key| entry.get(*key) == native.get(*key))
+    }
+
+    /// Writes `server` into `servers` under `name`, keeping keys of an
+    /// existing entry that the definition does not set, in their order.
+    fn merge(self, servers: &mut Map<String, Value>, name: &str, server: &McpServer) {
+        let mut native = self.native(server);
+        let Some(Value::Object(existing)) = servers.get_mut(name) else {
+            servers.insert(name.to_string(), Value::Object(native));
+            return;
+        };
+        let mut merged = Map::new();
+        for (key, value) in std::mem::take(existing) {
+            if !DEFINITION_KEYS.contains(&key.as_str()) {
+                merged.insert(key, value);
+            } else if let Some(value) = native.shift_remove(&key) {
+                merged.insert(key, value);
+            }
+        }
+        merged.extend(native);
+        *existing = merged;
+    }
+}
+
+impl State {
+    fn label(self) -> &'static str {
+        match self {
+            State::InSync => "in sync",
+            State::Differs => "differs",
+            State::Missing => "missing",
+            State::Unmanaged => "unmanaged",
+        }
+    }
+}
+
+fn string_map(values: &BTreeMap<String, String>) -> Value {
+    Value::Object(
+        values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone().into()))
+            .collect(),
+    )
+}
+
+/// Server names become JSON and TOML keys shared by every provider, so
+/// they are kept to letters, digits, `-` and `_`.
+fn check_name(name: &str) -> anyhow::Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        anyhow::bail!(
+            "Invalid server name '{}'; use letters, digits, '-' and '_', such as 'context7'.",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// The providers named on the command line, else those in use.
+fn target_providers(root: &Path, given: &[McpProvider]) -> anyhow::Result<Vec<McpProvider>> {
+    if !given.is_empty() {
+        let mut providers = given.to_vec();
+        providers.sort();
+        providers.dedup();
+        return Ok(providers);
+    }
+    let providers = providers_in_use(root)?;
+    if providers.is_empty() {
+        anyhow::bail!(
+            "No provider has been scaffolded or has an MCP config here; pass --provider {}.",
+            McpProvider::ALL.map(McpProvider::name).join(", ")
+        );
+    }
+    Ok(providers)
+}
+
+/// Providers recorded in the lockfile or whose MCP config exists.
+pub fn providers_in_use(root: &Path) -> anyhow::Result<Vec<McpProvider>> {
+    let lockfile = Lockfile::load(root)?;
+    Ok(McpProvider::ALL
+        .into_iter()
+        .filter(|provider| {
+            lockfile.providers.contains_key(provider.name())
+                || root.join(provider.config_path()).is_file()
+        })
+        .collect())
+}
+
+fn provider_names(providers: &[McpProvider]) -> String {
+    if providers.is_empty() {
+        return "no providers".to_string();
+    }
+    providers
+        .iter()
+        .map(|provider| provider.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The servers in `provider`'s config, or `None` when it has no config.
+pub fn read_servers(
+    root: &Path,
+    provider: McpProvider,
+) -> anyhow::Result<Option<Map<String, Value>>> {
+    let path = root.join(provider.config_path());
+    let Some(contents) = fsutil::read_optional(&path)? else {
+        return Ok(None);
+    };
+    let document: Value = serde_json::from_slice(&contents)
+        .with_context(|| format!("Failed to parse MCP config: {:?}", path))?;
+    match document.get(SERVERS_KEY) {
+        None => Ok(Some(Map::new())),
+        Some(Value::Object(servers)) => Ok(Some(servers.clone())),
+        Some(_) => anyhow::bail!("{:?}: '{}' must be an object.", path, SERVERS_KEY),
+    }
+}
+
+/// Applies `change` to the servers in `provider`'s config, creating the file
+/// if needed. Everything outside the servers is left as it was, and the
+/// file keeps its indentation; `None` means nothing changed.
+fn edit_servers(
+    root: &Path,
+    provider: McpProvider,
+    change: impl FnOnce(&mut Map<String, Value>),
+) -> anyhow::Result<Option<Edit>> {
+    let path = root.join(provider.config_path());
+    let old = fsutil::read_optional(&path)?
+        .map(|contents| {
+            String::from_utf8(contents)
+                .with_context(|| format!("MCP config is not valid UTF-8: {:?}", path))
+        })
+        .transpose()?;
+    let mut document = match &old {
+        Some(old) => serde_json::from_str(old)
+            .with_context(|| format!("Failed to parse MCP config: {:?}", path))?,
+        None => Value::Object(Map::new()),
+    };
+    let Value::Object(top) = &mut document else {
+        anyhow::bail!("{:?} must hold a JSON object.", path);
+    };
+    let before = top.get(SERVERS_KEY).cloned();
+    let servers = top
+        .entry(SERVERS_KEY)
+        .or_insert_with(|| Value::Object(Map::new()));
+    let Value::Object(servers) = servers else {
+        anyhow::bail!("{:?}: '{}' must be an object.", path, SERVERS_KEY);
+    };
+    change(servers);
+    if old.is_some() && top.get(SERVERS_KEY) == before.as_ref() {
+        return Ok(None);
+    }
+
+    let new = merge::json_string(
+        &document,
+        old.as_deref().map(merge::indentation).unwrap_or("  "),
+        old.as_deref().is_none_or(|old| old.ends_with('\n')),
+    )?;
+    Ok(Some(Edit {
+        path: provider.config_path().to_string(),
+        old,
+        new,
+    }))
+}
+
+/// Applies `change` to the `[mcp.servers]` table of ai-dlc.toml, keeping the
+/// rest of the file, comments included, as it was.
+fn edit_config(
+    root: &Path,
+    change: impl FnOnce(&mut toml_edit::Table),
+) -> anyhow::Result<Option<Edit>> {
+    let path = root.join(CONFIG_FILE);
+    let old = fsutil::read_optional(&path)?
+        .map(|contents| {
+            String::from_utf8(contents)
+                .with_context(|| format!("Config is not valid UTF-8: {:?}", path))
+        })
+        .transpose()?;
+    let mut document: toml_edit::DocumentMut = old
+        .as_deref()
+        .unwrap_or_default()
+        .parse()
+        .with_context(|| format!("Failed to parse config: {:?}", path))?;
+
+    let mcp = document
+        .entry("mcp")
+        .or_insert_with(|| implicit_table().into())
+        .as_table_mut()
+        .with_context(|| format!("{:?}: 'mcp' must be a table.", path))?;
+    let servers = mcp
+        .entry("servers")
+        .or_insert_with(|| implicit_table().into())
+        .as_table_mut()
+        .with_context(|| format!("{:?}: 'mcp.servers' must be a table.", path))?;
+    change(servers);
+
+    let new = document.to_string();
+    if old.as_deref() == Some(new.as_str()) {
+        return Ok(None);
+    }
+    Ok(Some(Edit {
+        path: CONFIG_FILE.to_string(),
+        old,
+        new,
+    }))
+}
+
+fn implicit_table() -> toml_edit::Table {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(true);
+    table
+}
+
+/// Writes `edits`, or only reports them with `dry_run`.
+fn apply(root: &Path, edits: &[Edit], dry_run: bool) -> anyhow::Result<()> {
+    for edit in edits {
+        let action = match (&edit.old, dry_run) {
+            (None, false) => "create",
+            (None, true) => "would create",
+            (Some(_), false) => "update",
+            (Some(_), true) => "would update",
+        };
+        println!("{:<10} {}", action, edit.path);
+        if dry_run {
+            continue;
+        }
+        let target = root.join(&edit.path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        std::fs::write(&target, &edit.new)
+            .with_context(|| format!("Failed to write file: {:?}", target))?;
+    }
+    if edits.is_empty() {
+        println!("Every config is already up to date.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdio(command: &str, args: &[&str]) -> McpServer {
+        McpServer {
+            command: Some(command.to_string()),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            ..McpServer::default()
+        }
+    }
+
+    fn write(root: &Path, edit: Option<Edit>) {
+        let edit = edit.expect("expected a change");
+        let path = root.join(&edit.path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, edit.new).unwrap();
+    }
+
+    fn read(root: &Path, path: &str) -> String {
+        std::fs::read_to_string(root.join(path)).unwrap()
+    }
+
+    #[test]
+    fn edit_servers_round_trips_in_each_schema() {
+        // Four-space indentation, a key outside the servers and a server the
+        // user added by hand, with a key of its own.
+        let original = r#"{
+    "theme": "dark",
+    "mcpServers": {
+        "mine": {
+            "command": "my-server",
+            "alwaysAllow": [
+                "read"
+            ]
+        }
+    }
+}
+"#;
+        let docs = stdio("npx", &["-y", "docs-server"]);
+        for provider in McpProvider::ALL {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path();
+            let path = provider.config_path();
+            std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            std::fs::write(root.join(path), original).unwrap();
+
+            write(
+                root,
+                edit_servers(root, provider, |servers| {
+                    provider.merge(servers, "docs", &docs)
+                })
+                .unwrap(),
+            );
+            let added = read(root, path);
+            assert!(
+                added.starts_with("{\n    \"theme\": \"dark\",\n"),
+                "{added}"
+            );
+            let servers = read_servers(root, provider).unwrap().unwrap();
+            assert_eq!(
+                servers.keys().collect::<Vec<_>>(),
+                ["mine", "docs"],
+                "{provider:?}"
+            );
+            assert_eq!(servers["mine"]["alwaysAllow"], serde_json::json!(["read"]));
+            assert_eq!(provider.definition(&servers["docs"]), Some(docs.clone()));
+            assert!(provider.matches(&servers["docs"], &docs));
+            assert_eq!(
+                servers["docs"].get("type").is_some(),
+                provider == McpProvider::Claude,
+                "{provider:?}"
+            );
+
+            // Syncing the same definition again changes nothing.
+            let unchanged = edit_servers(root, provider, |servers| {
+                provider.merge(servers, "docs", &docs)
+            })
+            .unwrap();
+            assert!(unchanged.is_none(), "{provider:?}");
+
+            write(
+                root,
+                edit_servers(root, provider, |servers| {
+                    servers.shift_remove("docs");
+                })
+                .unwrap(),
+            );
+            assert_eq!(read(root, path), original, "{provider:?}");
+        }
+    }
+
+    #[test]
+    fn merge_keeps_the_users_keys_of_a_server() {
+        let provider = McpProvider::Roo;
+        let mut servers = Map::new();
+        servers.insert(
+            "docs".to_string(),
+            serde_json::json!({ "command": "old", "alwaysAllow": ["read"], "disabled": false }),
+        );
+        provider.merge(&mut servers, "docs", &stdio("new", &["--fast"]));
+        assert_eq!(
+            servers["docs"],
+            serde_json::json!({
+                "command": "new",
+                "alwaysAllow": ["read"],
+                "disabled": false,
+                "args": ["--fast"],
+            })
+        );
+        let keys: Vec<&String> = servers["docs"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["command", "alwaysAllow", "disabled", "args"]);
+    }
+
+    #[test]
+    fn remote_servers_use_each_providers_keys() {
+        let server = McpServer {
+            transport: Transport::Http,
+            url: Some("https://example.com/mcp".to_string()),
+            ..McpServer::default()
+        };
+        for (provider, key, kind) in [
+            (McpProvider::Claude, "url", Some("http")),
+            (McpProvider::Cursor, "url", None),
+            (McpProvider::Gemini, "httpUrl", None),
+            (McpProvider::Roo, "url", Some("streamable-http")),
+        ] {
+            let entry = Value::Object(provider.native(&server));
+            assert_eq!(entry[key], "https://example.com/mcp", "{provider:?}");
+            assert_eq!(entry.get("type").and_then(Value::as_str), kind);
+            assert_eq!(provider.definition(&entry), Some(server.clone()));
+        }
+    }
+
+    #[test]
+    fn edit_config_keeps_comments_and_other_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let original = "# Shared ai-dlc settings\n\
+                        templates_dir = \"agents\" # local overrides\n\
+                        \n\
+                        [mcp.servers.mine]\n\
+                        # Started by hand\n\
+                        command = \"my-server\"\n";
+        std::fs::write(root.join(CONFIG_FILE), original).unwrap();
+
+        write(
+            root,
+            edit_config(root, |servers| {
+                let server = stdio("npx", &["docs-server"]);
+                servers.insert("docs", toml_edit::Item::Table(server.to_toml()));
+            })
+            .unwrap(),
+        );
+        let added = read(root, CONFIG_FILE);
+        assert!(added.starts_with(original), "{added}");
+        let config = Config::load(root).unwrap();
+        assert_eq!(
+            config.mcp.servers.keys().collect::<Vec<_>>(),
+            ["docs", "mine"]
+        );
+        assert_eq!(config.mcp.servers["docs"], stdio("npx", &["docs-server"]));
+
+        write(
+            root,
+            edit_config(root, |servers| {
+                servers.remove("docs");
+            })
+            .unwrap(),
+        );
+        assert_eq!(read(root, CONFIG_FILE), original);
+
+        // Removing what is not there leaves the file alone.
+        let unchanged = edit_config(root, |servers| {
+            servers.remove("docs");
+        })
+        .unwrap();
+        assert!(unchanged.is_none());
+    }
+
+    #[test]
+    fn edit_config_creates_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let edit = edit_config(root, |servers| {
+            servers.insert("docs", toml_edit::Item::Table(stdio("docs", &[]).to_toml()));
+        })
+        .unwrap()
+        .unwrap();
+        assert!(edit.old.is_none());
+        assert_eq!(
+            edit.new,
+            "[mcp.servers.docs]\ntransport = \"stdio\"\ncommand = \"docs\"\n"
+        );
+    }
+}
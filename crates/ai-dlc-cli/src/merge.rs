@@ -0,0 +1,405 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::render::TEMPLATE_SUFFIX;
+
+/// How arrays are combined when a settings template is merged into a file
+/// that already exists, declared with `merge` on a manifest mapping.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    /// Add the template's items that the file lacks.
+    Union,
+    /// Use the template's arrays.
+    Replace,
+    /// Keep the file's arrays; only missing ones are added.
+    Keep,
+}
+
+/// Settings formats that can be merged structurally.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl SettingsFormat {
+    /// The format of `path`, from its extension, ignoring `.jinja`.
+    pub fn of(path: &str) -> Option<Self> {
+        let path = path.strip_suffix(TEMPLATE_SUFFIX).unwrap_or(path);
+        match path.rsplit_once('.')?.1 {
+            "json" => Some(SettingsFormat::Json),
+            "toml" => Some(SettingsFormat::Toml),
+            "yaml" | "yml" => Some(SettingsFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse(self, text: &str) -> anyhow::Result<Value> {
+        Ok(match self {
+            SettingsFormat::Json => serde_json::from_str(text)?,
+            SettingsFormat::Toml => toml::from_str(text)?,
+            SettingsFormat::Yaml => serde_yaml::from_str(text)?,
+        })
+    }
+}
+
+/// Merges the `template` version of the settings file at `path` into the
+/// `local` one. Keys only the template has are added and keys only the file
+/// has are kept; where both set a value, the file wins unless it still holds
+/// what `base`, the version last scaffolded, held. Arrays follow `strategy`.
+/// The file keeps its layout as far as the format allows: JSON keeps its
+/// indentation and TOML its comments.
+pub fn merge_settings(
+    path: &str,
+    local: &[u8],
+    template: &[u8],
+    base: Option<&[u8]>,
+    strategy: MergeStrategy,
+) -> anyhow::Result<Vec<u8>> {
+    let format = SettingsFormat::of(path)
+        .with_context(|| format!("'{}' is not a JSON, TOML or YAML file", path))?;
+    let local_text =
+        std::str::from_utf8(local).with_context(|| format!("'{}' is not valid UTF-8", path))?;
+    let local_value = format
+        .parse(local_text)
+        .with_context(|| format!("Failed to parse '{}'", path))?;
+    let template_value = std::str::from_utf8(template)
+        .map_err(anyhow::Error::from)
+        .and_then(|text| format.parse(text))
+        .with_context(|| format!("Failed to parse the template for '{}'", path))?;
+    // A base that no longer parses is no help in telling edits apart.
+    let base_value = base
+        .and_then(|base| std::str::from_utf8(base).ok())
+        .and_then(|text| format.parse(text).ok());
+
+    let merged = merge_value(base_value.as_ref(), &local_value, &template_value, strategy);
+    if merged == local_value {
+        return Ok(local.to_vec());
+    }
+    let text = match format {
+        SettingsFormat::Json => {
+            json_string(&merged, indentation(local_text), local_text.ends_with('\n'))?
+        }
+        SettingsFormat::Toml => {
+            let mut document: toml_edit::DocumentMut = local_text
+                .parse()
+                .with_context(|| format!("Failed to parse '{}'", path))?;
+            if let (Value::Object(old), Value::Object(new)) = (&local_value, &merged) {
+                patch_toml(document.as_table_mut(), old, new);
+            }
+            document.to_string()
+        }
+        SettingsFormat::Yaml => serde_yaml::to_string(&merged)?,
+    };
+    Ok(text.into_bytes())
+}
+
+fn merge_value(
+    base: Option<&Value>,
+    local: &Value,
+    template: &Value,
+    strategy: MergeStrategy,
+) -> Value {
+    match (local, template) {
+        (Value::Object(local), Value::Object(template)) => {
+            let base = base.and_then(Value::as_object);
+            let mut merged = Map::new();
+            for (key, value) in local {
+                let base_value = base.and_then(|base| base.get(key));
+                match template.get(key) {
+                    Some(template_value) => {
+                        let value = merge_value(base_value, value, template_value, strategy);
+                        merged.insert(key.clone(), value);
+                    }
+                    // Dropped from the template and never edited here.
+                    None if base_value == Some(value) => {}
+                    None => {
+                        merged.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for (key, value) in template {
+                // A key deleted from the file since it was scaffolded stays
+                // deleted.
+                let deleted = base.is_some_and(|base| base.contains_key(key));
+                if !local.contains_key(key) && !deleted {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+            Value::Object(merged)
+        }
+        (Value::Array(local), Value::Array(template)) => match strategy {
+            MergeStrategy::Union => {
+                let base = base.and_then(Value::as_array);
+                let mut merged = local.clone();
+                for item in template {
+                    let deleted = base.is_some_and(|base| base.contains(item));
+                    if !merged.contains(item) && !deleted {
+                        merged.push(item.clone());
+                    }
+                }
+                Value::Array(merged)
+            }
+            MergeStrategy::Replace => Value::Array(template.clone()),
+            MergeStrategy::Keep => Value::Array(local.clone()),
+        },
+        _ if base == Some(local) => template.clone(),
+        _ => local.clone(),
+    }
+}
+
+/// Brings `table`, which parsed to `old`, in line with `new`, rewriting only
+/// the entries that changed so comments and layout elsewhere survive.
+fn patch_toml(table: &mut toml_edit::Table, old: &Map<String, Value>, new: &Map<String, Value>) {
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        table.remove(key);
+    }
+    for (key, value) in new {
+        if old.get(key) == Some(value) {
+            continue;
+        }
+        match (table.get_mut(key), old.get(key), value) {
+            (
+                Some(toml_edit::Item::Table(child)),
+                Some(Value::Object(old_child)),
+                Value::Object(new_child),
+            ) => patch_toml(child, old_child, new_child),
+            (Some(toml_edit::Item::Value(existing)), _, _) => {
+                let decor = existing.decor().clone();
+                *existing = toml_value(value);
+                *existing.decor_mut() = decor;
+            }
+            _ => {
+                table.insert(key, toml_item(value));
+            }
+        }
+    }
+}
+
+fn toml_item(value: &Value) -> toml_edit::Item {
+    match value {
+        Value::Object(entries) => {
+            let mut table = toml_edit::Table::new();
+            for (key, value) in entries {
+                table.insert(key, toml_item(value));
+            }
+            toml_edit::Item::Table(table)
+        }
+        Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object) => {
+            let mut tables = toml_edit::ArrayOfTables::new();
+            for item in items {
+                if let toml_edit::Item::Table(table) = toml_item(item) {
+                    tables.push(table);
+                }
+            }
+            toml_edit::Item::ArrayOfTables(tables)
+        }
+        _ => toml_edit::Item::Value(toml_value(value)),
+    }
+}
+
+fn toml_value(value: &Value) -> toml_edit::Value {
+    match value {
+        Value::Object(entries) => {
+            let table: toml_edit::InlineTable = entries
+                .iter()
+                .map(|(key, value)| (key.as_str(), toml_value(value)))
+                .collect();
+            toml_edit::Value::InlineTable(table)
+        }
+        Value::Array(items) => toml_edit::Value::Array(items.iter().map(toml_value).collect()),
+        Value::String(text) => text.as_str().into(),
+        Value::Bool(flag) => (*flag).into(),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => integer.into(),
+            None => number.as_f64().unwrap_or_default().into(),
+        },
+        // TOML has no null; templates written in TOML never produce one.
+        Value::Null => "".into(),
+    }
+}
+
+/// `value` as pretty-printed JSON indented with `indent`.
+pub fn json_string(value: &Value, indent: &str, trailing_newline: bool) -> anyhow::Result<String> {
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer)?;
+    let mut text = String::from_utf8(out)?;
+    if trailing_newline {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// The indentation of the first indented line, so rewritten files match
+/// their style.
+pub fn indentation(text: &str) -> &str {
+    text.lines()
+        .find_map(|line| {
+            let trimmed = line.trim_start();
+            let indent = &line[..line.len() - trimmed.len()];
+            (!trimmed.is_empty() && !indent.is_empty()).then_some(indent)
+        })
+        .unwrap_or("  ")
+}
+
+/// A unified diff from `old` to `new` for the file at `path`.
+pub fn diff(path: &str, old: &[u8], new: &[u8]) -> String {
+    let old = String::from_utf8_lossy(old);
+    let new = String::from_utf8_lossy(new);
+    diffy::DiffOptions::new()
+        .set_original_filename(format!("a/{path}"))
+        .set_modified_filename(format!("b/{path}"))
+        .create_patch(&old, &new)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// The same local file and template in each format.
+    const CASES: &[(&str, &str, &str)] = &[
+        (
+            "settings.json",
+            r#"{
+    "theme": "dark",
+    "permissions": { "allow": ["a", "mine"] }
+}
+"#,
+            r#"{
+  "theme": "light",
+  "model": "x",
+  "permissions": { "allow": ["a", "b"] }
+}
+"#,
+        ),
+        (
+            "config.toml",
+            r#"# Project settings
+theme = "dark" # my choice
+
+[permissions]
+# Commands allowed without asking
+allow = ["a", "mine"]
+"#,
+            r#"theme = "light"
+model = "x"
+
+[permissions]
+allow = ["a", "b"]
+"#,
+        ),
+        (
+            "config.yaml",
+            "theme: dark\npermissions:\n  allow:\n  - a\n  - mine\n",
+            "theme: light\nmodel: x\npermissions:\n  allow: [a, b]\n",
+        ),
+    ];
+
+    fn merged(path: &str, local: &str, template: &str, strategy: MergeStrategy) -> Value {
+        let merged =
+            merge_settings(path, local.as_bytes(), template.as_bytes(), None, strategy).unwrap();
+        let text = String::from_utf8(merged).unwrap();
+        SettingsFormat::of(path).unwrap().parse(&text).unwrap()
+    }
+
+    #[test]
+    fn strategies_combine_arrays_in_every_format() {
+        for (strategy, allow) in [
+            (MergeStrategy::Union, json!(["a", "mine", "b"])),
+            (MergeStrategy::Replace, json!(["a", "b"])),
+            (MergeStrategy::Keep, json!(["a", "mine"])),
+        ] {
+            for (path, local, template) in CASES {
+                assert_eq!(
+                    merged(path, local, template, strategy),
+                    json!({ "theme": "dark", "model": "x", "permissions": { "allow": allow } }),
+                    "{path} with {strategy:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn toml_comments_survive() {
+        let (path, local, template) = CASES[1];
+        let merged = merge_settings(
+            path,
+            local.as_bytes(),
+            template.as_bytes(),
+            None,
+            MergeStrategy::Union,
+        )
+        .unwrap();
+        let text = String::from_utf8(merged).unwrap();
+        assert!(text.starts_with("# Project settings\n"), "{text}");
+        assert!(text.contains("theme = \"dark\" # my choice"), "{text}");
+        assert!(
+            text.contains("# Commands allowed without asking\n"),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn json_keeps_its_indentation() {
+        let (path, local, template) = CASES[0];
+        let merged = merge_settings(
+            path,
+            local.as_bytes(),
+            template.as_bytes(),
+            None,
+            MergeStrategy::Union,
+        )
+        .unwrap();
+        let text = String::from_utf8(merged).unwrap();
+        assert!(text.contains("\n    \"theme\": \"dark\""), "{text}");
+        assert!(text.ends_with("}\n"), "{text}");
+    }
+
+    #[test]
+    fn base_tells_template_changes_from_local_edits() {
+        let base = json!({ "theme": "light", "font": "mono", "allow": ["a", "old"] });
+        let local = json!({ "theme": "light", "allow": ["a"], "own": true });
+        let template = json!({ "theme": "blue", "font": "mono", "allow": ["a", "old", "new"] });
+        assert_eq!(
+            merge_value(Some(&base), &local, &template, MergeStrategy::Union),
+            // The untouched theme follows the template; the deleted font and
+            // array item stay deleted.
+            json!({ "theme": "blue", "allow": ["a", "new"], "own": true })
+        );
+    }
+
+    #[test]
+    fn unchanged_file_is_returned_as_is() {
+        let local = b"{\"theme\":   \"dark\"}";
+        let merged = merge_settings(
+            "settings.json",
+            local,
+            b"{\"theme\": \"light\"}",
+            None,
+            MergeStrategy::Union,
+        )
+        .unwrap();
+        assert_eq!(merged, local);
+    }
+
+    #[test]
+    fn unparseable_file_is_an_error() {
+        let err = merge_settings(
+            "settings.json",
+            b"{ not json",
+            b"{}",
+            None,
+            MergeStrategy::Union,
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to parse 'settings.json'"));
+        assert!(merge_settings("notes.md", b"", b"", None, MergeStrategy::Union).is_err());
+    }
+}
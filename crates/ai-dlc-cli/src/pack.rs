@@ -0,0 +1,543 @@
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock;
+use crate::plan::slash_path;
+use crate::signing;
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct PackArgs {
+    #[command(subcommand)]
+    command: PackCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum PackCommand {
+    /// Build a .tar.gz or .zip template pack with a checksum manifest.
+    Create(CreateArgs),
+    /// Generate an ed25519 key for signing template packs.
+    Keygen(KeygenArgs),
+    /// Check a pack's checksums and signature against the trusted keys.
+    Verify(VerifyArgs),
+}
+
+#[derive(Args, Debug)]
+struct CreateArgs {
+    /// Directory laid out like `templates/`, one subdirectory per provider.
+    dir: PathBuf,
+    /// Archive to write; the format follows its extension.
+    #[arg(long, short, value_name = "FILE")]
+    output: PathBuf,
+    /// Sign the manifest with this key, as written by `pack keygen`.
+    #[arg(long, value_name = "FILE")]
+    signing_key: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct KeygenArgs {
+    /// File to write the private key to.
+    #[arg(long, short, value_name = "FILE")]
+    output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+struct VerifyArgs {
+    /// Pack to check, in the same form as `scaffold --from`.
+    pack: String,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_pack(args: PackArgs) -> anyhow::Result<()> {
+    match args.command {
+        PackCommand::Create(args) => create_pack(args),
+        PackCommand::Keygen(args) => keygen(args),
+        PackCommand::Verify(args) => {
+            let root = &args.root.resolve()?;
+            let config = Config::load(root)?;
+            let source = PackSource::parse(&args.pack)?;
+            let mut pack = source.fetch()?;
+            signing::verify(
+                &source.to_string(),
+                &mut pack.files,
+                &config.trusted_keys,
+                source.requires_manifest(),
+            )?;
+            println!(
+                "{} files verified in {} ({})",
+                pack.files.len(),
+                source,
+                pack.revision
+            );
+            Ok(())
+        }
+    }
+}
+
+fn create_pack(args: CreateArgs) -> anyhow::Result<()> {
+    let output = args.output.to_string_lossy();
+    if !ARCHIVE_SUFFIXES
+        .iter()
+        .any(|suffix| output.ends_with(suffix))
+    {
+        anyhow::bail!("--output must end in {}", ARCHIVE_SUFFIXES.join(", "));
+    }
+    let key = match &args.signing_key {
+        Some(path) => Some(signing::parse_signing_key(
+            &std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read signing key: {:?}", path))?,
+        )?),
+        None => None,
+    };
+
+    let mut files = BTreeMap::new();
+    for path in fsutil::walk_files(&args.dir, "")? {
+        let skip = path.split('/').any(|part| part == ".git")
+            || path == signing::PACK_MANIFEST
+            || path == signing::PACK_SIGNATURE;
+        if skip {
+            continue;
+        }
+        let source = args.dir.join(&path);
+        let contents =
+            std::fs::read(&source).with_context(|| format!("Failed to read file: {:?}", source))?;
+        files.insert(path, contents);
+    }
+    if files.is_empty() {
+        anyhow::bail!("{:?} contains no files to pack.", args.dir);
+    }
+
+    let (manifest, signature) = signing::sign(&files, key.as_ref())?;
+    files.insert(signing::PACK_MANIFEST.to_string(), manifest);
+    if let Some(signature) = signature {
+        files.insert(signing::PACK_SIGNATURE.to_string(), signature);
+    }
+    let archive = if output.ends_with(".zip") {
+        write_zip(&files)?
+    } else {
+        write_tar_gz(&files)?
+    };
+    std::fs::write(&args.output, &archive)
+        .with_context(|| format!("Failed to write template pack: {:?}", args.output))?;
+
+    println!(
+        "Packed {} files into {} ({}{})",
+        files.len(),
+        args.output.display(),
+        lock::hash_bytes(&archive),
+        if key.is_some() { ", signed" } else { "" }
+    );
+    Ok(())
+}
+
+fn write_tar_gz(files: &BTreeMap<String, Vec<u8>>) -> anyhow::Result<Vec<u8>> {
+    let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, &contents[..])?;
+    }
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn write_zip(files: &BTreeMap<String, Vec<u8>>) -> anyhow::Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (path, contents) in files {
+        writer.start_file(path.as_str(), options)?;
+        writer.write_all(contents)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+fn keygen(args: KeygenArgs) -> anyhow::Result<()> {
+    if args.output.exists() {
+        anyhow::bail!(
+            "{:?} already exists; refusing to overwrite a key.",
+            args.output
+        );
+    }
+    let key = signing::generate_key()?;
+    let mut file = std::fs::OpenOptions::new();
+    file.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut file, 0o600);
+    file.open(&args.output)
+        .and_then(|mut file| writeln!(file, "{}", signing::encode_signing_key(&key)))
+        .with_context(|| format!("Failed to write signing key: {:?}", args.output))?;
+
+    println!("Private key written to {}", args.output.display());
+    println!("Add the public key to trusted_keys in ai-dlc.toml:");
+    println!();
+    println!(
+        "trusted_keys = [\"{}\"]",
+        signing::encode_public_key(&key.verifying_key())
+    );
+    Ok(())
+}
+
+/// Prefix marking a `--from` value as a git repository.
+const GIT_PREFIX: &str = "git+";
+
+/// File name endings recognised as archive packs.
+const ARCHIVE_SUFFIXES: [&str; 3] = [".tar.gz", ".tgz", ".zip"];
+
+/// Where a template pack comes from, as given to `--from`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PackSource {
+    /// `git+<url>#<ref>`; the ref defaults to the remote's default branch.
+    Git { url: String, reference: String },
+    /// A local `.tar.gz`, `.tgz` or `.zip` file, optionally pinned to the
+    /// hash it had when it was recorded.
+    Archive {
+        path: PathBuf,
+        expected: Option<String>,
+    },
+}
+
+/// A fetched pack: its files keyed by `/`-separated path, and the exact
+/// revision they were read from (a commit, or the archive's hash).
+pub struct Pack {
+    pub files: BTreeMap<String, Vec<u8>>,
+    pub revision: String,
+}
+
+impl PackSource {
+    /// Parses a `--from` value. Local paths are made absolute so the source
+    /// means the same thing from any working directory.
+    pub fn parse(raw: &str) -> anyhow::Result<Self> {
+        if ARCHIVE_SUFFIXES.iter().any(|suffix| raw.ends_with(suffix)) {
+            let path = raw.strip_prefix("file://").unwrap_or(raw);
+            if path.contains("://") {
+                anyhow::bail!(
+                    "Archive packs must be local files; download '{}' first and pass its path.",
+                    raw
+                );
+            }
+            return Ok(PackSource::Archive {
+                path: std::path::absolute(path)
+                    .with_context(|| format!("Invalid archive path '{}'", raw))?,
+                expected: None,
+            });
+        }
+        let Some(rest) = raw.strip_prefix(GIT_PREFIX) else {
+            anyhow::bail!(
+                "Unsupported template pack '{}'; expected git+<url>#<ref> or a .tar.gz/.zip \
+                 archive, for example git+https://github.com/org/agents.git#v1.2.0 or \
+                 ./agents-1.2.0.tar.gz",
+                raw
+            );
+        };
+        let (url, reference) = match rest.rsplit_once('#') {
+            Some((url, reference)) if !reference.is_empty() => (url, reference),
+            Some((url, _)) => (url, "HEAD"),
+            None => (rest, "HEAD"),
+        };
+        if url.is_empty() {
+            anyhow::bail!("Template pack '{}' has no repository URL.", raw);
+        }
+        Ok(PackSource::Git {
+            url: normalize_git_url(url)?,
+            reference: reference.to_string(),
+        })
+    }
+
+    /// The same source pinned to `revision`.
+    pub fn pinned(&self, revision: &str) -> Self {
+        match self {
+            PackSource::Git { url, .. } => PackSource::Git {
+                url: url.clone(),
+                reference: revision.to_string(),
+            },
+            PackSource::Archive { path, .. } => PackSource::Archive {
+                path: path.clone(),
+                expected: Some(revision.to_string()),
+            },
+        }
+    }
+
+    /// Archives must carry a manifest; git packs may rely on the commit.
+    pub fn requires_manifest(&self) -> bool {
+        matches!(self, PackSource::Archive { .. })
+    }
+
+    /// Fetches the pack, reusing the cache for git repositories, and reads
+    /// its files into memory. Nothing is written to the repository.
+    pub fn fetch(&self) -> anyhow::Result<Pack> {
+        match self {
+            PackSource::Git { url, reference } => fetch_git(url, reference),
+            PackSource::Archive { path, expected } => read_archive(path, expected.as_deref()),
+        }
+    }
+}
+
+impl fmt::Display for PackSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackSource::Git { url, reference } => write!(f, "{GIT_PREFIX}{url}#{reference}"),
+            PackSource::Archive { path, .. } => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// Keeps remote URLs as given and turns local paths into absolute ones. An
+/// absolute path that has since disappeared is kept, so a recorded pack can
+/// still be served from the cache.
+fn normalize_git_url(url: &str) -> anyhow::Result<String> {
+    let path = Path::new(url);
+    let is_remote = url.contains("://") || (url.contains(':') && !path.exists());
+    if is_remote || (path.is_absolute() && !path.exists()) {
+        return Ok(url.to_string());
+    }
+    let path = std::fs::canonicalize(path)
+        .with_context(|| format!("Template pack repository {:?} does not exist", url))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Directory holding cached template packs: `$AI_DLC_CACHE_DIR`, or
+/// `ai-dlc` under `$XDG_CACHE_HOME` or `~/.cache`.
+pub fn cache_dir() -> anyhow::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("AI_DLC_CACHE_DIR") {
+        return Ok(PathBuf::from(dir));
+    }
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => match std::env::var_os("HOME") {
+            Some(home) => PathBuf::from(home).join(".cache"),
+            None => anyhow::bail!(
+                "Cannot locate a cache directory; set AI_DLC_CACHE_DIR or XDG_CACHE_HOME."
+            ),
+        },
+    };
+    Ok(base.join("ai-dlc"))
+}
+
+/// Mirrors `url` into the cache and reads the tree at `reference`. A ref
+/// already present in the cache as a commit is used without contacting the
+/// remote; branches and tags are refreshed, falling back to the cached copy
+/// when the remote cannot be reached.
+fn fetch_git(url: &str, reference: &str) -> anyhow::Result<Pack> {
+    let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+    let repo = cache_dir()?.join("git").join(&key[..16]);
+
+    if !repo.join("HEAD").exists() {
+        tracing::info!("Cloning template pack {}", url);
+        if let Some(parent) = repo.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+        }
+        let target = repo.to_string_lossy();
+        // `--` keeps a url starting with `-` from being read as an option.
+        git(None, &["clone", "--quiet", "--mirror", "--", url, &target])?;
+    } else if is_commit_id(reference) && resolve(&repo, reference).is_ok() {
+        tracing::debug!("Template pack {} already has {} cached", url, reference);
+    } else {
+        tracing::info!("Fetching template pack {}", url);
+        if let Err(err) = git(Some(&repo), &["fetch", "--quiet", "--prune", "origin"]) {
+            tracing::warn!("{:#}; using the cached copy of {}.", err, url);
+        }
+    }
+
+    let commit = resolve(&repo, reference).with_context(|| {
+        format!(
+            "Template pack {} has no branch, tag or commit '{}'",
+            url, reference
+        )
+    })?;
+    tracing::info!("Using template pack {} at {}", url, commit);
+    Ok(Pack {
+        files: read_tree(&repo, &commit)?,
+        revision: commit,
+    })
+}
+
+fn is_commit_id(reference: &str) -> bool {
+    reference.len() >= 7 && reference.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn resolve(repo: &Path, reference: &str) -> anyhow::Result<String> {
+    let spec = format!("{reference}^{{commit}}");
+    let out = git(
+        Some(repo),
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            "--end-of-options",
+            &spec,
+        ],
+    )?;
+    Ok(String::from_utf8_lossy(&out).trim().to_string())
+}
+
+/// Reads every regular file in the tree of `commit`. Symlinks and
+/// submodules are skipped.
+fn read_tree(repo: &Path, commit: &str) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+    let listing = git(Some(repo), &["ls-tree", "-r", "-z", commit])?;
+    let mut blobs = Vec::new();
+    for record in listing.split(|b| *b == 0).filter(|r| !r.is_empty()) {
+        let record = String::from_utf8_lossy(record);
+        let Some((meta, path)) = record.split_once('\t') else {
+            continue;
+        };
+        let mut meta = meta.split(' ');
+        match (meta.next(), meta.next(), meta.next()) {
+            (Some("100644" | "100755"), Some("blob"), Some(oid)) => {
+                blobs.push((path.to_string(), oid.to_string()));
+            }
+            _ => tracing::debug!("Skipping non-file tree entry {}", path),
+        }
+    }
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run git; is it installed?")?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let request: String = blobs.iter().map(|(_, oid)| format!("{oid}\n")).collect();
+    // Written from a thread so a large response cannot block the request.
+    let writer = std::thread::spawn(move || stdin.write_all(request.as_bytes()));
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+
+    let mut files = BTreeMap::new();
+    for (path, oid) in blobs {
+        let mut header = String::new();
+        stdout.read_line(&mut header)?;
+        let size: usize = header
+            .split(' ')
+            .nth(2)
+            .and_then(|size| size.trim().parse().ok())
+            .with_context(|| format!("Unexpected git cat-file output for {oid}: {header:?}"))?;
+        let mut contents = vec![0; size + 1];
+        stdout.read_exact(&mut contents)?;
+        contents.pop();
+        files.insert(path, contents);
+    }
+    writer
+        .join()
+        .expect("git cat-file writer panicked")
+        .context("Failed to write to git cat-file")?;
+    // stdout has been taken, so this only collects stderr.
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git cat-file --batch failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(files)
+}
+
+/// Reads a `.tar.gz` or `.zip` pack. When every entry sits below a single
+/// top-level directory, that directory is treated as the pack root.
+fn read_archive(path: &Path, expected: Option<&str>) -> anyhow::Result<Pack> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read template pack: {:?}", path))?;
+    let revision = lock::hash_bytes(&bytes);
+    if let Some(expected) = expected
+        && expected != revision
+    {
+        anyhow::bail!(
+            "Template pack {:?} has changed since it was recorded ({} != {}).",
+            path,
+            revision,
+            expected
+        );
+    }
+
+    let mut entries = Vec::new();
+    if path.to_string_lossy().ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+            .with_context(|| format!("Failed to open zip archive: {:?}", path))?;
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            if entry.is_dir() {
+                continue;
+            }
+            let Some(name) = entry.enclosed_name() else {
+                anyhow::bail!("{:?} contains an unsafe path '{}'.", path, entry.name());
+            };
+            let name = slash_path(&name);
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            entries.push((name, contents));
+        }
+    } else {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&bytes[..]));
+        for entry in archive
+            .entries()
+            .with_context(|| format!("Failed to open tar archive: {:?}", path))?
+        {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let name = entry.path()?.into_owned();
+            let safe = name
+                .components()
+                .all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+            if !safe {
+                anyhow::bail!("{:?} contains an unsafe path {:?}.", path, name);
+            }
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+            entries.push((
+                slash_path(&name).trim_start_matches("./").to_string(),
+                contents,
+            ));
+        }
+    }
+
+    let top_level: BTreeSet<&str> = entries
+        .iter()
+        .map(|(name, _)| name.split_once('/').map_or("", |(dir, _)| dir))
+        .collect();
+    let strip = match top_level.into_iter().collect::<Vec<_>>()[..] {
+        [dir] if !dir.is_empty() => format!("{dir}/"),
+        _ => String::new(),
+    };
+    let files = entries
+        .into_iter()
+        .map(|(name, contents)| (name[strip.len()..].to_string(), contents))
+        .collect();
+    Ok(Pack { files, revision })
+}
+
+/// Runs git, returning its stdout or an error carrying its stderr.
+fn git(dir: Option<&Path>, args: &[&str]) -> anyhow::Result<Vec<u8>> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run git; is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
@@ -0,0 +1,462 @@
+use anyhow::Context;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::fsutil;
+use crate::merge::{self, MergeStrategy};
+
+/// How plans and reports are printed.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// How scaffold treats a destination file that exists with different contents.
+#[derive(ValueEnum, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Leave the existing file untouched.
+    #[default]
+    Skip,
+    /// Replace the existing file with the template.
+    Overwrite,
+    /// Copy the existing file to `<name>.orig` before replacing it.
+    Backup,
+    /// Ask for each conflicting file.
+    Prompt,
+    /// Abort the run before anything is written.
+    Fail,
+}
+
+/// What scaffolding a single file does to the destination tree.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileAction {
+    Create,
+    Overwrite,
+    Backup,
+    /// A settings file merged into the existing one.
+    Merge,
+    Skip,
+    /// The file differs on disk and the policy needs a decision at write time.
+    Conflict,
+    Unchanged,
+}
+
+impl FileAction {
+    fn label(self) -> &'static str {
+        match self {
+            FileAction::Create => "create",
+            FileAction::Overwrite => "overwrite",
+            FileAction::Backup => "backup",
+            FileAction::Merge => "merge",
+            FileAction::Skip => "skip",
+            FileAction::Conflict => "conflict",
+            FileAction::Unchanged => "unchanged",
+        }
+    }
+}
+
+impl ConflictPolicy {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictPolicy::Skip => "skip",
+            ConflictPolicy::Overwrite => "overwrite",
+            ConflictPolicy::Backup => "backup",
+            ConflictPolicy::Prompt => "prompt",
+            ConflictPolicy::Fail => "fail",
+        }
+    }
+
+    fn action(self) -> FileAction {
+        match self {
+            ConflictPolicy::Skip => FileAction::Skip,
+            ConflictPolicy::Overwrite => FileAction::Overwrite,
+            ConflictPolicy::Backup => FileAction::Backup,
+            ConflictPolicy::Prompt | ConflictPolicy::Fail => FileAction::Conflict,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct PlannedFile {
+    pub provider: String,
+    /// Destination path relative to the scaffold root, always `/`-separated.
+    pub path: String,
+    pub action: FileAction,
+    /// What a merge changes in the existing file, as a unified diff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    #[serde(skip)]
+    pub contents: Vec<u8>,
+    /// The template itself when `contents` is it merged into the existing
+    /// file; this is what gets locked.
+    #[serde(skip)]
+    pub template: Option<Vec<u8>>,
+}
+
+impl PlannedFile {
+    pub fn dest(&self, dest_root: &Path) -> PathBuf {
+        dest_root.join(&self.path)
+    }
+
+    pub fn write(&self, dest_root: &Path) -> anyhow::Result<()> {
+        let path = self.dest(dest_root);
+        if self.action == FileAction::Backup {
+            let backup = backup_path(&path);
+            tracing::info!("Backing up {:?} to {:?}", path, backup);
+            std::fs::copy(&path, &backup)
+                .with_context(|| format!("Failed to back up file: {:?}", path))?;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create parent directory: {:?}", parent))?;
+        }
+        tracing::debug!("Writing file: {:?}", path);
+        std::fs::write(&path, &self.contents)
+            .with_context(|| format!("Failed to write file: {:?}", path))
+    }
+}
+
+#[derive(Serialize, Debug, Default, Clone, Copy)]
+pub struct PlanSummary {
+    pub create: usize,
+    pub overwrite: usize,
+    pub backup: usize,
+    pub merge: usize,
+    pub skip: usize,
+    pub conflict: usize,
+    pub unchanged: usize,
+}
+
+/// The full set of file operations a scaffold run would perform.
+#[derive(Serialize, Debug, Default)]
+pub struct ScaffoldPlan {
+    pub on_conflict: ConflictPolicy,
+    pub files: Vec<PlannedFile>,
+}
+
+impl ScaffoldPlan {
+    pub fn new(on_conflict: ConflictPolicy) -> Self {
+        Self {
+            on_conflict,
+            files: Vec::new(),
+        }
+    }
+
+    /// Records `contents` as destined for `path` under `dest_root`, comparing
+    /// against whatever is already on disk.
+    pub fn add(
+        &mut self,
+        provider: &str,
+        dest_root: &Path,
+        path: String,
+        contents: Vec<u8>,
+    ) -> anyhow::Result<()> {
+        let dest = dest_root.join(&path);
+        let action = match std::fs::read(&dest) {
+            Ok(existing) if existing == contents => FileAction::Unchanged,
+            Ok(_) => self.on_conflict.action(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => FileAction::Create,
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read file: {:?}", dest));
+            }
+        };
+        self.files.push(PlannedFile {
+            provider: provider.to_string(),
+            path,
+            action,
+            diff: None,
+            contents,
+            template: None,
+        });
+        Ok(())
+    }
+
+    /// Records the settings file `contents` as destined for `path`, merged
+    /// into the existing file with `strategy` instead of replacing it. `base`
+    /// is the version last scaffolded, if any. A file that cannot be merged,
+    /// such as one that no longer parses, falls back to the conflict policy.
+    pub fn add_merged(
+        &mut self,
+        provider: &str,
+        dest_root: &Path,
+        path: String,
+        contents: Vec<u8>,
+        strategy: MergeStrategy,
+        base: Option<Vec<u8>>,
+    ) -> anyhow::Result<()> {
+        let dest = dest_root.join(&path);
+        let Some(existing) = fsutil::read_optional(&dest)? else {
+            return self.add(provider, dest_root, path, contents);
+        };
+        if existing == contents {
+            return self.add(provider, dest_root, path, contents);
+        }
+        let merged =
+            match merge::merge_settings(&path, &existing, &contents, base.as_deref(), strategy) {
+                Ok(merged) => merged,
+                Err(err) => {
+                    tracing::warn!("Cannot merge into '{}': {:#}", path, err);
+                    return self.add(provider, dest_root, path, contents);
+                }
+            };
+        self.push_merged(provider, path, &existing, merged, Some(contents));
+        Ok(())
+    }
+
+    /// Records `contents` as an update of the existing `path` that keeps the
+    /// user's parts of it, such as a regenerated section. It is written
+    /// whatever the conflict policy and shown as a diff, like a merge.
+    pub fn add_update(&mut self, provider: &str, path: String, existing: &[u8], contents: Vec<u8>) {
+        self.push_merged(provider, path, existing, contents, None);
+    }
+
+    fn push_merged(
+        &mut self,
+        provider: &str,
+        path: String,
+        existing: &[u8],
+        merged: Vec<u8>,
+        template: Option<Vec<u8>>,
+    ) {
+        let (action, diff) = if merged == existing {
+            (FileAction::Unchanged, None)
+        } else {
+            (
+                FileAction::Merge,
+                Some(merge::diff(&path, existing, &merged)),
+            )
+        };
+        self.files.push(PlannedFile {
+            provider: provider.to_string(),
+            path,
+            action,
+            diff,
+            contents: merged,
+            template,
+        });
+    }
+
+    /// Files that exist on disk with different contents and still need a
+    /// decision from the conflict policy.
+    pub fn conflicts(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.files
+            .iter()
+            .filter(|file| file.action == FileAction::Conflict)
+    }
+
+    pub fn conflicts_mut(&mut self) -> impl Iterator<Item = &mut PlannedFile> {
+        self.files
+            .iter_mut()
+            .filter(|file| file.action == FileAction::Conflict)
+    }
+
+    /// Files whose write would change the destination tree.
+    pub fn pending(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.files.iter().filter(|file| {
+            matches!(
+                file.action,
+                FileAction::Create | FileAction::Overwrite | FileAction::Backup | FileAction::Merge
+            )
+        })
+    }
+
+    pub fn summary(&self) -> PlanSummary {
+        let mut summary = PlanSummary::default();
+        for file in &self.files {
+            match file.action {
+                FileAction::Create => summary.create += 1,
+                FileAction::Overwrite => summary.overwrite += 1,
+                FileAction::Backup => summary.backup += 1,
+                FileAction::Merge => summary.merge += 1,
+                FileAction::Skip => summary.skip += 1,
+                FileAction::Conflict => summary.conflict += 1,
+                FileAction::Unchanged => summary.unchanged += 1,
+            }
+        }
+        summary
+    }
+
+    pub fn print(&self, format: OutputFormat) -> anyhow::Result<()> {
+        match format {
+            OutputFormat::Text => {
+                for file in &self.files {
+                    println!("{:<10} {}", file.action.label(), file.path);
+                    if let Some(diff) = &file.diff {
+                        print!("{diff}");
+                    }
+                }
+                let summary = self.summary();
+                println!(
+                    "\n{} files: {} to create, {} to overwrite, {} to back up and overwrite, \
+                     {} to merge, {} skipped, {} in conflict, {} unchanged (on conflict: {})",
+                    self.files.len(),
+                    summary.create,
+                    summary.overwrite,
+                    summary.backup,
+                    summary.merge,
+                    summary.skip,
+                    summary.conflict,
+                    summary.unchanged,
+                    self.on_conflict.label()
+                );
+            }
+            OutputFormat::Json => {
+                #[derive(Serialize)]
+                struct JsonPlan<'a> {
+                    on_conflict: ConflictPolicy,
+                    files: &'a [PlannedFile],
+                    summary: PlanSummary,
+                }
+                let json = serde_json::to_string_pretty(&JsonPlan {
+                    on_conflict: self.on_conflict,
+                    files: &self.files,
+                    summary: self.summary(),
+                })?;
+                println!("{json}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders a relative path with `/` separators so plans read the same on
+/// every platform.
+pub fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Picks `<name>.orig`, or `<name>.orig.N` when earlier backups already exist,
+/// so repeated runs never clobber a previous backup.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".orig");
+    let mut candidate = PathBuf::from(&name);
+    let mut n = 1;
+    while candidate.exists() {
+        let mut numbered = name.clone();
+        numbered.push(format!(".{n}"));
+        candidate = PathBuf::from(numbered);
+        n += 1;
+    }
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_for(policy: ConflictPolicy, root: &Path) -> ScaffoldPlan {
+        std::fs::write(root.join("same.md"), "same\n").unwrap();
+        std::fs::write(root.join("differs.md"), "local\n").unwrap();
+        let mut plan = ScaffoldPlan::new(policy);
+        for path in ["new.md", "same.md", "differs.md"] {
+            let contents = if path == "same.md" {
+                "same\n"
+            } else {
+                "template\n"
+            };
+            plan.add("claude", root, path.to_string(), contents.into())
+                .unwrap();
+        }
+        plan
+    }
+
+    fn actions(plan: &ScaffoldPlan) -> Vec<(&str, FileAction)> {
+        plan.files
+            .iter()
+            .map(|file| (file.path.as_str(), file.action))
+            .collect()
+    }
+
+    #[test]
+    fn each_policy_decides_only_the_differing_file() {
+        for (policy, expected) in [
+            (ConflictPolicy::Skip, FileAction::Skip),
+            (ConflictPolicy::Overwrite, FileAction::Overwrite),
+            (ConflictPolicy::Backup, FileAction::Backup),
+            (ConflictPolicy::Prompt, FileAction::Conflict),
+            (ConflictPolicy::Fail, FileAction::Conflict),
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            let plan = plan_for(policy, dir.path());
+            assert_eq!(
+                actions(&plan),
+                [
+                    ("new.md", FileAction::Create),
+                    ("same.md", FileAction::Unchanged),
+                    ("differs.md", expected),
+                ],
+                "policy {}",
+                policy.label()
+            );
+        }
+    }
+
+    #[test]
+    fn summary_and_pending_follow_the_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = plan_for(ConflictPolicy::Backup, dir.path());
+        let summary = plan.summary();
+        assert_eq!(
+            (summary.create, summary.backup, summary.unchanged),
+            (1, 1, 1)
+        );
+        let pending: Vec<&str> = plan.pending().map(|file| file.path.as_str()).collect();
+        assert_eq!(pending, ["new.md", "differs.md"]);
+
+        let plan = plan_for(ConflictPolicy::Fail, dir.path());
+        let conflicts: Vec<&str> = plan.conflicts().map(|file| file.path.as_str()).collect();
+        assert_eq!(conflicts, ["differs.md"]);
+        assert_eq!(plan.summary().conflict, 1);
+    }
+
+    #[test]
+    fn json_plan_lists_actions_without_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = plan_for(ConflictPolicy::Skip, dir.path());
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["on_conflict"], "skip");
+        assert_eq!(
+            json["files"][2],
+            serde_json::json!({ "provider": "claude", "path": "differs.md", "action": "skip" })
+        );
+    }
+
+    #[test]
+    fn backup_path_numbers_repeated_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("CLAUDE.md");
+        assert_eq!(backup_path(&file), dir.path().join("CLAUDE.md.orig"));
+        std::fs::write(dir.path().join("CLAUDE.md.orig"), "").unwrap();
+        assert_eq!(backup_path(&file), dir.path().join("CLAUDE.md.orig.1"));
+        std::fs::write(dir.path().join("CLAUDE.md.orig.1"), "").unwrap();
+        assert_eq!(backup_path(&file), dir.path().join("CLAUDE.md.orig.2"));
+    }
+
+    #[test]
+    fn backup_write_keeps_every_previous_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (local, template) in [("first\n", "second\n"), ("second\n", "third\n")] {
+            std::fs::write(root.join("differs.md"), local).unwrap();
+            let mut plan = ScaffoldPlan::new(ConflictPolicy::Backup);
+            plan.add("claude", root, "differs.md".to_string(), template.into())
+                .unwrap();
+            for file in plan.pending() {
+                file.write(root).unwrap();
+            }
+        }
+        let read = |name: &str| std::fs::read_to_string(root.join(name)).unwrap();
+        assert_eq!(read("differs.md"), "third\n");
+        assert_eq!(read("differs.md.orig"), "first\n");
+        assert_eq!(read("differs.md.orig.1"), "second\n");
+    }
+}
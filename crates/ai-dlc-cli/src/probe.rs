@@ -0,0 +1,528 @@
+use clap::Args;
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::task::JoinHandle;
+
+use crate::config::Config;
+use crate::mcp::{self, McpServer, Transport};
+use crate::plan::OutputFormat;
+use crate::workspace::RootArgs;
+
+/// Protocol revision offered in `initialize`; servers answer with the one
+/// they speak.
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// The lists a server can offer, keyed by the capability that announces them.
+const LISTS: &[(&str, &str)] = &[
+    ("tools", "tools/list"),
+    ("prompts", "prompts/list"),
+    ("resources", "resources/list"),
+];
+
+/// Stop following `nextCursor` after this many pages.
+const MAX_PAGES: usize = 20;
+
+/// Bytes of the server's stderr kept for error messages.
+const STDERR_TAIL: usize = 4096;
+
+#[derive(Args, Debug)]
+pub struct ProbeArgs {
+    /// Servers to probe. Defaults to every server defined in ai-dlc.toml or
+    /// configured for a provider.
+    names: Vec<String>,
+    /// Seconds to wait for each answer before giving up on a server.
+    #[arg(long, default_value_t = 10)]
+    timeout: u64,
+    /// Output format for the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Ok,
+    Failed,
+    /// Remote servers are not contacted.
+    Skipped,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Failure {
+    MissingBinary,
+    Spawn,
+    Timeout,
+    Exited,
+    Protocol,
+}
+
+#[derive(Serialize, Debug)]
+struct ProbeReport {
+    name: String,
+    /// Where the server was found: ai-dlc.toml or a provider's config.
+    source: String,
+    summary: String,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<Failure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// `name version` from the server's `serverInfo`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol_version: Option<String>,
+    tools: Vec<String>,
+    prompts: Vec<String>,
+    resources: Vec<String>,
+}
+
+/// What a successful handshake found.
+#[derive(Default)]
+struct Handshake {
+    server: Option<String>,
+    protocol_version: Option<String>,
+    lists: BTreeMap<&'static str, Vec<String>>,
+}
+
+struct ProbeError {
+    failure: Failure,
+    message: String,
+}
+
+impl ProbeError {
+    fn new(failure: Failure, message: impl Into<String>) -> Self {
+        Self {
+            failure,
+            message: message.into(),
+        }
+    }
+}
+
+/// A running stdio server and the JSON-RPC exchange with it.
+struct Session {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+    /// Drains stderr as the server writes it, so a chatty server never blocks
+    /// on a full pipe, and yields the last bytes once the server is gone.
+    stderr: JoinHandle<VecDeque<u8>>,
+    timeout: Duration,
+    next_id: u64,
+}
+
+pub fn handle_probe(args: ProbeArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let mut servers = configured_servers(root)?;
+    if !args.names.is_empty() {
+        for name in &args.names {
+            if !servers.contains_key(name) {
+                anyhow::bail!(
+                    "MCP server '{}' is not defined in ai-dlc.toml or configured for any \
+                     provider; run `ai-dlc-cli mcp list` to see the servers.",
+                    name
+                );
+            }
+        }
+        servers.retain(|name, _| args.names.contains(name));
+    }
+    if servers.is_empty() {
+        anyhow::bail!("No MCP servers are configured; add one with `ai-dlc-cli mcp add`.");
+    }
+
+    let timeout = Duration::from_secs(args.timeout);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let reports: Vec<ProbeReport> = servers
+        .into_iter()
+        .map(|(name, (source, server))| {
+            tracing::info!("Probing MCP server '{}'", name);
+            let mut report = ProbeReport {
+                name,
+                source,
+                summary: server.summary(),
+                status: Status::Skipped,
+                failure: None,
+                error: None,
+                server: None,
+                protocol_version: None,
+                tools: Vec::new(),
+                prompts: Vec::new(),
+                resources: Vec::new(),
+            };
+            if server.transport != Transport::Stdio {
+                report.error = Some("remote servers are not probed".to_string());
+                return report;
+            }
+            match runtime.block_on(probe(root, &server, timeout)) {
+                Ok(mut handshake) => {
+                    report.status = Status::Ok;
+                    report.server = handshake.server;
+                    report.protocol_version = handshake.protocol_version;
+                    report.tools = handshake.lists.remove("tools").unwrap_or_default();
+                    report.prompts = handshake.lists.remove("prompts").unwrap_or_default();
+                    report.resources = handshake.lists.remove("resources").unwrap_or_default();
+                }
+                Err(err) => {
+                    report.status = Status::Failed;
+                    report.failure = Some(err.failure);
+                    report.error = Some(err.message);
+                }
+            }
+            report
+        })
+        .collect();
+
+    let count = |status| reports.iter().filter(|r| r.status == status).count();
+    let (ok, failed, skipped) = (
+        count(Status::Ok),
+        count(Status::Failed),
+        count(Status::Skipped),
+    );
+    match args.format {
+        OutputFormat::Text => {
+            for report in &reports {
+                let label = match report.status {
+                    Status::Ok => "ok",
+                    Status::Failed => "failed",
+                    Status::Skipped => "skipped",
+                };
+                println!("{:<10} {} ({})", label, report.name, report.summary);
+                if let Some(error) = &report.error {
+                    for line in error.lines() {
+                        println!("{:<10} {}", "", line);
+                    }
+                }
+                if let Some(server) = &report.server {
+                    println!(
+                        "{:<10} {}, protocol {}",
+                        "",
+                        server,
+                        report.protocol_version.as_deref().unwrap_or("unknown")
+                    );
+                }
+                if report.status == Status::Ok {
+                    for (kind, names) in [
+                        ("tools", &report.tools),
+                        ("prompts", &report.prompts),
+                        ("resources", &report.resources),
+                    ] {
+                        if !names.is_empty() {
+                            println!("{:<10} {}: {}", "", kind, names.join(", "));
+                        }
+                    }
+                }
+            }
+            println!();
+            println!(
+                "{} server(s): {} ok, {} failed, {} skipped",
+                reports.len(),
+                ok,
+                failed,
+                skipped
+            );
+        }
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&reports)?),
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} MCP server(s) failed the probe", failed);
+    }
+    Ok(())
+}
+
+/// Every server by name, with where it was found: the definitions in
+/// ai-dlc.toml, then servers configured by hand for a provider.
+fn configured_servers(root: &Path) -> anyhow::Result<BTreeMap<String, (String, McpServer)>> {
+    let config = Config::load(root)?;
+    let mut servers: BTreeMap<String, (String, McpServer)> = config
+        .mcp
+        .servers
+        .into_iter()
+        .map(|(name, server)| (name, ("ai-dlc.toml".to_string(), server)))
+        .collect();
+    for provider in mcp::providers_in_use(root)? {
+        for (name, entry) in mcp::read_servers(root, provider)?.unwrap_or_default() {
+            if servers.contains_key(&name) {
+                continue;
+            }
+            match provider.definition(&entry) {
+                Some(server) => {
+                    servers.insert(name, (provider.config_path().to_string(), server));
+                }
+                None => tracing::warn!(
+                    "Server '{}' in {} has no command or URL; skipping it.",
+                    name,
+                    provider.config_path()
+                ),
+            }
+        }
+    }
+    Ok(servers)
+}
+
+/// Starts `server`, runs the `initialize` handshake and collects the lists
+/// its capabilities announce.
+async fn probe(
+    root: &Path,
+    server: &McpServer,
+    timeout: Duration,
+) -> Result<Handshake, ProbeError> {
+    let mut session = Session::start(root, server, timeout)?;
+    let result = session.handshake().await;
+    let _ = session.child.kill().await;
+    match result {
+        Err(mut err) => {
+            // A server that fails usually says why on stderr.
+            if let Some(stderr) = session.stderr().await {
+                err.message = format!("{}\nstderr: {}", err.message, stderr);
+            }
+            Err(err)
+        }
+        result => result,
+    }
+}
+
+impl Session {
+    fn start(root: &Path, server: &McpServer, timeout: Duration) -> Result<Self, ProbeError> {
+        let program = expand(server.command.as_deref().unwrap_or_default());
+        let mut child = Command::new(&program)
+            .args(server.args.iter().map(|arg| expand(arg)))
+            .envs(server.env.iter().map(|(key, value)| (key, expand(value))))
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    ProbeError::new(
+                        Failure::MissingBinary,
+                        format!("command '{program}' not found; is it installed and on PATH?"),
+                    )
+                } else {
+                    ProbeError::new(
+                        Failure::Spawn,
+                        format!("could not start '{program}': {err}"),
+                    )
+                }
+            })?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped")).lines();
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let stderr = tokio::spawn(async move {
+            let mut tail = VecDeque::with_capacity(STDERR_TAIL);
+            let mut chunk = [0; 8192];
+            while let Ok(read @ 1..) = stderr.read(&mut chunk).await {
+                tail.extend(&chunk[..read]);
+                let excess = tail.len().saturating_sub(STDERR_TAIL);
+                tail.drain(..excess);
+            }
+            tail
+        });
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            stderr,
+            timeout,
+            next_id: 1,
+        })
+    }
+
+    async fn handshake(&mut self) -> Result<Handshake, ProbeError> {
+        let result = self
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "ai-dlc-cli", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        let mut handshake = Handshake {
+            protocol_version: result["protocolVersion"].as_str().map(str::to_string),
+            server: result["serverInfo"]["name"].as_str().map(|name| {
+                match result["serverInfo"]["version"].as_str() {
+                    Some(version) => format!("{name} {version}"),
+                    None => name.to_string(),
+                }
+            }),
+            ..Handshake::default()
+        };
+        self.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+
+        for (capability, method) in LISTS {
+            if result["capabilities"].get(*capability).is_none() {
+                continue;
+            }
+            let mut names = Vec::new();
+            let mut cursor: Option<String> = None;
+            for _ in 0..MAX_PAGES {
+                let params = match &cursor {
+                    Some(cursor) => json!({ "cursor": cursor }),
+                    None => json!({}),
+                };
+                let page = self.request(method, params).await?;
+                let items = page[*capability].as_array().ok_or_else(|| {
+                    ProbeError::new(
+                        Failure::Protocol,
+                        format!("answer to {method} has no '{capability}' list"),
+                    )
+                })?;
+                names.extend(items.iter().filter_map(|item| {
+                    item["name"]
+                        .as_str()
+                        .or_else(|| item["uri"].as_str())
+                        .map(str::to_string)
+                }));
+                cursor = page["nextCursor"].as_str().map(str::to_string);
+                if cursor.is_none() {
+                    break;
+                }
+            }
+            handshake.lists.insert(capability, names);
+        }
+        Ok(handshake)
+    }
+
+    /// Sends a request and waits for the answer with the same id, passing
+    /// over notifications and requests from the server.
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, ProbeError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+
+        let answer = async {
+            loop {
+                let line = match self.stdout.next_line().await {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        return Err(ProbeError::new(
+                            Failure::Exited,
+                            format!("server exited before answering {method}"),
+                        ));
+                    }
+                    Err(err) => {
+                        return Err(ProbeError::new(
+                            Failure::Protocol,
+                            format!("could not read the answer to {method}: {err}"),
+                        ));
+                    }
+                };
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let message: Value = serde_json::from_str(&line).map_err(|_| {
+                    ProbeError::new(
+                        Failure::Protocol,
+                        format!(
+                            "server wrote something other than JSON-RPC to stdout: {}",
+                            truncate(&line)
+                        ),
+                    )
+                })?;
+                if message["id"].as_u64() != Some(id) || message.get("method").is_some() {
+                    continue;
+                }
+                if let Some(error) = message.get("error") {
+                    return Err(ProbeError::new(
+                        Failure::Protocol,
+                        format!(
+                            "{method} failed: {} (code {})",
+                            error["message"].as_str().unwrap_or("no message"),
+                            error["code"]
+                        ),
+                    ));
+                }
+                return message.get("result").cloned().ok_or_else(|| {
+                    ProbeError::new(
+                        Failure::Protocol,
+                        format!("answer to {method} has no result"),
+                    )
+                });
+            }
+        };
+        tokio::time::timeout(self.timeout, answer)
+            .await
+            .unwrap_or_else(|_| {
+                Err(ProbeError::new(
+                    Failure::Timeout,
+                    format!("no answer to {method} within {}s", self.timeout.as_secs()),
+                ))
+            })
+    }
+
+    async fn send(&mut self, message: Value) -> Result<(), ProbeError> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .and(self.stdin.flush().await)
+            .map_err(|err| {
+                ProbeError::new(
+                    Failure::Exited,
+                    format!("could not write to the server: {err}"),
+                )
+            })
+    }
+
+    /// The last line the server wrote to stderr. Only call it once the
+    /// server has stopped, or this waits up to a second for it.
+    async fn stderr(&mut self) -> Option<String> {
+        let tail = tokio::time::timeout(Duration::from_secs(1), &mut self.stderr)
+            .await
+            .ok()?
+            .ok()?;
+        let text = String::from_utf8_lossy(&Vec::from(tail)).into_owned();
+        text.lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .map(|line| truncate(line.trim()))
+    }
+}
+
+/// Replaces `${VAR}` and `${VAR:-default}` with values from the environment,
+/// as the assistants do when they start a server.
+fn expand(value: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..start + end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, default),
+            None => (reference, ""),
+        };
+        expanded.push_str(&std::env::var(name).unwrap_or_else(|_| default.to_string()));
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+fn truncate(line: &str) -> String {
+    const LIMIT: usize = 120;
+    match line.char_indices().nth(LIMIT) {
+        Some((index, _)) => format!("{}...", &line[..index]),
+        None => line.to_string(),
+    }
+}
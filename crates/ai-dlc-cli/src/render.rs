@@ -0,0 +1,354 @@
+use anyhow::Context;
+use clap::Args;
+use minijinja::{Environment, UndefinedBehavior};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::scaffold::TemplateFile;
+use crate::workspace::CONFIG_FILE;
+
+/// Suffix marking template files whose contents are rendered. It is
+/// stripped from the destination path.
+pub const TEMPLATE_SUFFIX: &str = ".jinja";
+
+#[derive(Args, Debug, Default)]
+pub struct VarArgs {
+    /// Set a template variable, for example `--var owner_team=platform`.
+    /// Takes precedence over `[variables]` in ai-dlc.toml.
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_var)]
+    vars: Vec<(String, String)>,
+}
+
+pub fn parse_var(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{raw}'")),
+    }
+}
+
+/// Renders template contents and paths with a Jinja-compatible engine.
+/// Undefined variables are errors rather than empty strings.
+pub struct Renderer {
+    vars: BTreeMap<String, String>,
+    /// Structured values bound with [`Renderer::bind`], such as `repo`.
+    values: BTreeMap<String, minijinja::Value>,
+}
+
+impl Renderer {
+    /// Builds the variable set from, in increasing precedence: values derived
+    /// from the repository, `recorded` values from an earlier scaffold, the
+    /// `[variables]` table in ai-dlc.toml and `--var` flags.
+    pub fn new(
+        root: &Path,
+        config: &Config,
+        recorded: &BTreeMap<String, String>,
+        args: &VarArgs,
+    ) -> Self {
+        let mut vars = derived_variables(root);
+        vars.extend(recorded.clone());
+        vars.extend(config.variables.clone());
+        vars.extend(args.vars.iter().cloned());
+        Self {
+            vars,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Makes `value` available to templates as `name`, next to the string
+    /// variables.
+    pub fn bind(&mut self, name: &str, value: &impl serde::Serialize) {
+        self.values
+            .insert(name.to_string(), minijinja::Value::from_serialize(value));
+    }
+
+    /// Names of the variables referenced by `files`, excluding template
+    /// globals such as `range`.
+    pub fn referenced(&self, files: &[TemplateFile]) -> anyhow::Result<BTreeSet<String>> {
+        let env = environment();
+        let mut names = BTreeSet::new();
+        for file in files {
+            for source in self.sources(file)? {
+                let template = env
+                    .template_from_named_str(&file.path, &source)
+                    .map_err(|err| template_error(&file.path, err))?;
+                names.extend(template.undeclared_variables(false));
+            }
+        }
+        let globals: BTreeSet<&str> = env.globals().map(|(name, _)| name).collect();
+        names.retain(|name| !globals.contains(name.as_str()));
+        Ok(names)
+    }
+
+    /// Makes sure every variable `files` reference has a value, asking for
+    /// missing ones on an interactive terminal and failing otherwise.
+    pub fn require(&mut self, files: &[TemplateFile]) -> anyhow::Result<()> {
+        let referenced = self.referenced(files)?;
+        let missing: Vec<&String> = referenced
+            .iter()
+            .filter(|name| !self.vars.contains_key(*name) && !self.values.contains_key(*name))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !std::io::stdin().is_terminal() {
+            let name = missing[0];
+            let file = files
+                .iter()
+                .find(|file| {
+                    self.referenced(std::slice::from_ref(*file))
+                        .is_ok_and(|names| names.contains(name))
+                })
+                .map(|file| file.path.as_str())
+                .unwrap_or("a template");
+            anyhow::bail!(
+                "'{}' references undefined template variable '{}'; pass --var {}=<value> or \
+                 set it under [variables] in {}. Undefined: {}",
+                file,
+                name,
+                name,
+                CONFIG_FILE,
+                missing
+                    .iter()
+                    .map(|name| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        let missing: Vec<String> = missing.into_iter().cloned().collect();
+        for name in missing {
+            let value: String = dialoguer::Input::new()
+                .with_prompt(format!("Value for template variable '{name}'"))
+                .interact_text()?;
+            self.vars.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// The values of `names`, for recording alongside the scaffolded files.
+    pub fn values_of(&self, names: &BTreeSet<String>) -> BTreeMap<String, String> {
+        names
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.vars.get(name)?.clone())))
+            .collect()
+    }
+
+    /// Renders every file in `files`, prompting for missing variables first.
+    pub fn render_all(&mut self, files: Vec<TemplateFile>) -> anyhow::Result<Vec<TemplateFile>> {
+        self.require(&files)?;
+        files.into_iter().map(|file| self.render(file)).collect()
+    }
+
+    /// Renders the path of `file` and, for `.jinja` templates, its contents.
+    pub fn render(&self, file: TemplateFile) -> anyhow::Result<TemplateFile> {
+        let env = environment();
+        let context = self.context();
+        let mut path = if file.path.contains("{{") || file.path.contains("{%") {
+            env.render_str(&file.path, &context)
+                .map_err(|err| template_error(&file.path, err))?
+        } else {
+            file.path.clone()
+        };
+        // Checked after rendering: a variable or a hard-coded expression can
+        // turn an innocent template name into `../..`.
+        if path.is_empty() || fsutil::escapes(&path) {
+            anyhow::bail!(
+                "Template '{}' renders to the path '{}', which is outside the destination; \
+                 template paths must be relative and may not contain '..'.",
+                file.path,
+                path
+            );
+        }
+        let contents = match path.strip_suffix(TEMPLATE_SUFFIX) {
+            Some(stripped) => {
+                let source = template_source(&file)?;
+                let rendered = env
+                    .template_from_named_str(&file.path, source)
+                    .and_then(|template| template.render(&context))
+                    .map_err(|err| template_error(&file.path, err))?;
+                path = stripped.to_string();
+                rendered.into_bytes()
+            }
+            None => file.contents,
+        };
+        Ok(TemplateFile {
+            path,
+            contents,
+            ..file
+        })
+    }
+
+    /// The string variables and bound values, as one template context.
+    fn context(&self) -> BTreeMap<&str, minijinja::Value> {
+        let mut context: BTreeMap<&str, minijinja::Value> = self
+            .vars
+            .iter()
+            .map(|(name, value)| (name.as_str(), minijinja::Value::from(value.as_str())))
+            .collect();
+        context.extend(
+            self.values
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.clone())),
+        );
+        context
+    }
+
+    /// The template sources in `file`: its path and, for `.jinja` files,
+    /// its contents.
+    fn sources(&self, file: &TemplateFile) -> anyhow::Result<Vec<String>> {
+        let mut sources = vec![file.path.clone()];
+        if file.path.ends_with(TEMPLATE_SUFFIX) {
+            sources.push(template_source(file)?.to_string());
+        }
+        Ok(sources)
+    }
+}
+
+fn environment<'source>() -> Environment<'source> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env.set_keep_trailing_newline(true);
+    env
+}
+
+fn template_source(file: &TemplateFile) -> anyhow::Result<&str> {
+    std::str::from_utf8(&file.contents)
+        .with_context(|| format!("Template '{}' is not valid UTF-8", file.path))
+}
+
+fn template_error(path: &str, err: minijinja::Error) -> anyhow::Error {
+    anyhow::anyhow!("Failed to render template '{}': {:#}", path, err)
+}
+
+/// Variables that can be read from the repository itself: the directory name
+/// as `project_name`, the checked-out branch as `default_branch`, which
+/// falls back to `main` outside a git checkout, and the current date as
+/// `today`.
+fn derived_variables(root: &Path) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if let Some(name) = root.file_name().and_then(|name| name.to_str()) {
+        vars.insert("project_name".to_string(), name.to_string());
+    }
+    let branch = std::fs::read_to_string(root.join(".git/HEAD"))
+        .ok()
+        .and_then(|head| Some(head.trim().strip_prefix("ref: refs/heads/")?.to_string()))
+        .unwrap_or_else(|| "main".to_string());
+    vars.insert("default_branch".to_string(), branch);
+    vars.insert("today".to_string(), today());
+    vars
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+fn today() -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    // Days since 1970-01-01 to a proleptic Gregorian date, counting in
+    // 400-year eras that start on 0000-03-01.
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renderer(vars: &[(&str, &str)]) -> Renderer {
+        let dir = tempfile::tempdir().unwrap();
+        let args = VarArgs {
+            vars: vars
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        };
+        Renderer::new(dir.path(), &Config::default(), &BTreeMap::new(), &args)
+    }
+
+    fn template(path: &str, contents: &str) -> TemplateFile {
+        TemplateFile {
+            path: path.to_string(),
+            source: path.to_string(),
+            group: "agents".to_string(),
+            optional: false,
+            merge: None,
+            contents: contents.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn renders_paths_and_jinja_contents() {
+        let renderer = renderer(&[("project_name", "shop"), ("team", "payments")]);
+        let file = renderer
+            .render(template(
+                "agents/{{ project_name }}.md.jinja",
+                "Owned by {{ team }}.\n",
+            ))
+            .unwrap();
+        assert_eq!(file.path, "agents/shop.md");
+        assert_eq!(file.contents, b"Owned by payments.\n");
+
+        // Without the suffix only the path is rendered.
+        let file = renderer
+            .render(template("agents/{{ team }}.md", "Owned by {{ team }}.\n"))
+            .unwrap();
+        assert_eq!(file.path, "agents/payments.md");
+        assert_eq!(file.contents, b"Owned by {{ team }}.\n");
+    }
+
+    #[test]
+    fn rejects_rendered_paths_outside_the_destination() {
+        let renderer = renderer(&[("project_name", "../../../escaped")]);
+        for path in [
+            "agents/{{ project_name }}.md",
+            "{{ \"../..\" }}/escaped.md",
+            "{{ \"/etc\" }}/escaped.md",
+            "{{ \"\" }}",
+        ] {
+            let err = renderer.render(template(path, "")).err().unwrap();
+            assert!(
+                err.to_string().contains("outside the destination"),
+                "{path}: {err:#}"
+            );
+        }
+    }
+
+    #[test]
+    fn undefined_variables_are_named() {
+        let renderer = renderer(&[]);
+        let files = [template(
+            "agents/{{ owner_team }}.md.jinja",
+            "{{ region }}\n",
+        )];
+        let referenced = renderer.referenced(&files).unwrap();
+        assert!(referenced.contains("owner_team") && referenced.contains("region"));
+
+        let err = renderer
+            .render(template("agents/x.md.jinja", "{{ region }}\n"))
+            .err()
+            .unwrap();
+        let message = format!("{err:#}");
+        assert!(message.contains("agents/x.md.jinja"), "{message}");
+        assert!(message.contains("undefined"), "{message}");
+    }
+}
@@ -0,0 +1,330 @@
+use anyhow::Context;
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock::{self, Lockfile};
+use crate::merge::MergeStrategy;
+use crate::plan::{ConflictPolicy, FileAction, OutputFormat, ScaffoldPlan, slash_path};
+use crate::render::{Renderer, VarArgs};
+use crate::templates::{GENERIC_DIR, TemplateArgs, Templates};
+use crate::wizard;
+use crate::workspace::{self, RootArgs};
+
+/// Folder generic documents are written to when --dest is not given,
+/// relative to the project root.
+const DOCS_DIR: &str = "docs";
+
+#[derive(Parser, Debug)]
+pub struct ScaffoldArgs {
+    #[arg(long, short)]
+    provider: Vec<String>,
+    #[arg(long)]
+    all: bool,
+    /// Only scaffold these asset groups (for example agents, commands or mcp).
+    #[arg(long, short)]
+    group: Vec<String>,
+    /// Scaffold a provider-independent document instead: prd, design-spec or
+    /// adr. --dest then names the folder it is written to, docs/ by default.
+    #[arg(long, value_name = "DOC", conflicts_with_all = ["provider", "all", "group"])]
+    generic: Vec<String>,
+    /// Report the files scaffold would create or overwrite without touching disk.
+    #[arg(long)]
+    dry_run: bool,
+    /// Output format used for the dry-run plan.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, requires = "dry_run")]
+    plan_format: OutputFormat,
+    /// What to do with existing files whose contents differ from the template.
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Skip)]
+    on_conflict: ConflictPolicy,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_scaffold(args: ScaffoldArgs) -> anyhow::Result<()> {
+    tracing::info!("Scaffolding templates...");
+    if !args.generic.is_empty() {
+        return scaffold_generic(args);
+    }
+    let dest_root = &args.root.resolve()?;
+    let config = Config::load(dest_root)?;
+    let templates = Templates::load(dest_root, &config, &args.templates, None)?;
+
+    let interactive = !args.all && args.provider.is_empty();
+    let (providers_to_scaffold, groups) = if args.all {
+        let providers = templates
+            .available_providers()?
+            .into_iter()
+            .map(|info| info.name)
+            .collect();
+        (providers, args.group)
+    } else if args.provider.is_empty() {
+        if !std::io::stdin().is_terminal() {
+            tracing::warn!("No providers specified. Use --provider or --all. Exiting.");
+            return Ok(());
+        }
+        let Some(selection) = wizard::select(&templates)? else {
+            tracing::warn!("Nothing selected. Exiting.");
+            return Ok(());
+        };
+        (selection.providers, selection.groups)
+    } else {
+        (args.provider, args.group)
+    };
+
+    tracing::info!("Scaffolding for providers: {:?}", providers_to_scaffold);
+
+    // Load the existing record up front so a corrupt lockfile stops the run
+    // before any template is written.
+    let mut lockfile = Lockfile::load(dest_root)?;
+
+    let mut selected: Vec<(String, Vec<TemplateFile>)> = Vec::new();
+    for provider in &providers_to_scaffold {
+        if let Some(files) = templates.provider_templates(provider)? {
+            selected.push((provider.clone(), select_groups(files, &groups)));
+        }
+    }
+
+    let recorded: BTreeMap<String, String> = selected
+        .iter()
+        .filter_map(|(provider, _)| lockfile.providers.get(provider))
+        .flat_map(|entry| entry.variables.clone())
+        .collect();
+    // One renderer for every provider, so a value prompted for once is reused.
+    let mut renderer = Renderer::new(dest_root, &config, &recorded, &args.vars);
+
+    let mut plan = ScaffoldPlan::new(args.on_conflict);
+    let mut variables = BTreeMap::new();
+    for (provider_name, files) in selected {
+        renderer.require(&files)?;
+        variables.insert(
+            provider_name.clone(),
+            renderer.values_of(&renderer.referenced(&files)?),
+        );
+        for file in files {
+            let file = renderer.render(file)?;
+            match file.merge {
+                Some(strategy) => {
+                    let base = lock::load_base(dest_root, &file.path)?;
+                    plan.add_merged(
+                        &provider_name,
+                        dest_root,
+                        file.path,
+                        file.contents,
+                        strategy,
+                        base,
+                    )?;
+                }
+                None => plan.add(&provider_name, dest_root, file.path, file.contents)?,
+            }
+        }
+    }
+
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+    if interactive && !wizard::confirm(&plan)? {
+        tracing::warn!("Scaffold cancelled; nothing was written.");
+        return Ok(());
+    }
+
+    extract_plan(&mut plan, dest_root)?;
+    if !plan.files.is_empty() {
+        lockfile.record(dest_root, &plan, &templates.hash(), &groups)?;
+        for (provider, values) in variables {
+            if let Some(entry) = lockfile.providers.get_mut(&provider) {
+                entry.variables.extend(values);
+                entry.pack = templates.pack().cloned();
+            }
+        }
+        lockfile.save(dest_root)?;
+        tracing::info!("Recorded scaffolded files in {}", lock::LOCK_PATH);
+    }
+    report(&plan);
+    Ok(())
+}
+
+/// Writes the generic documents named by --generic into the docs folder.
+/// Variables come from the project root as for provider files, but the
+/// documents are the project's own and are not recorded in the lockfile.
+fn scaffold_generic(args: ScaffoldArgs) -> anyhow::Result<()> {
+    let root = &workspace::discover_root()?;
+    let docs_dir = match args.root.dest() {
+        Some(dest) => fsutil::normalize(
+            &std::env::current_dir()
+                .context("Failed to read the current directory")?
+                .join(dest),
+        ),
+        None => root.join(DOCS_DIR),
+    };
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+
+    let mut selected = Vec::new();
+    for document in &args.generic {
+        let Some(files) = templates.generic_templates(document) else {
+            let available: Vec<&str> = templates.generic_documents().into_iter().collect();
+            anyhow::bail!(
+                "Generic document '{}' not found; available: {}.",
+                document,
+                if available.is_empty() {
+                    format!("none (the templates have no {GENERIC_DIR}/ directory)")
+                } else {
+                    available.join(", ")
+                }
+            );
+        };
+        selected.push((document, files));
+    }
+
+    // Plan paths relative to the project root when the folder is inside it,
+    // so they read as `docs/PRD.md`.
+    let (plan_root, prefix) = match docs_dir.strip_prefix(root) {
+        Ok(relative) => (root.clone(), slash_path(relative)),
+        Err(_) => (docs_dir.clone(), String::new()),
+    };
+    let mut renderer = Renderer::new(root, &config, &BTreeMap::new(), &args.vars);
+    let mut plan = ScaffoldPlan::new(args.on_conflict);
+    for (document, files) in selected {
+        renderer.require(&files)?;
+        for file in files {
+            let file = renderer.render(file)?;
+            let path = if prefix.is_empty() {
+                file.path
+            } else {
+                format!("{prefix}/{}", file.path)
+            };
+            plan.add(document, &plan_root, path, file.contents)?;
+        }
+    }
+
+    if args.dry_run {
+        plan.print(args.plan_format)?;
+        return Ok(());
+    }
+    extract_plan(&mut plan, &plan_root)?;
+    report(&plan);
+    Ok(())
+}
+
+/// Logs what a scaffold run did, pointing out files kept because they differ.
+pub fn report(plan: &ScaffoldPlan) {
+    for merged in plan.files.iter().filter(|f| f.action == FileAction::Merge) {
+        tracing::info!(
+            "Merged the template into existing '{}':\n{}",
+            merged.path,
+            merged.diff.as_deref().unwrap_or_default()
+        );
+    }
+    for skipped in plan.files.iter().filter(|f| f.action == FileAction::Skip) {
+        tracing::warn!(
+            "Kept existing '{}' which differs from the template; \
+             rerun with --on-conflict overwrite or backup to replace it.",
+            skipped.path
+        );
+    }
+    let summary = plan.summary();
+    tracing::info!(
+        on_conflict = plan.on_conflict.label(),
+        created = summary.create,
+        overwritten = summary.overwrite,
+        backed_up = summary.backup,
+        merged = summary.merge,
+        skipped = summary.skip,
+        unchanged = summary.unchanged,
+        "Scaffolding complete."
+    );
+}
+
+/// A template file and the `/`-separated path it is written to, relative to
+/// the scaffold root.
+pub struct TemplateFile {
+    pub path: String,
+    /// Path of the template within its provider directory.
+    pub source: String,
+    /// Asset group the file belongs to, such as `agents` or `commands`.
+    pub group: String,
+    /// Whether the group is only installed when explicitly selected.
+    pub optional: bool,
+    /// Set for settings files that are merged into an existing file.
+    pub merge: Option<MergeStrategy>,
+    pub contents: Vec<u8>,
+}
+
+/// Keeps the files in `groups`; an empty selection keeps every group that is
+/// not optional.
+pub fn select_groups(files: Vec<TemplateFile>, groups: &[String]) -> Vec<TemplateFile> {
+    files
+        .into_iter()
+        .filter(|file| {
+            if groups.is_empty() {
+                !file.optional
+            } else {
+                groups.contains(&file.group)
+            }
+        })
+        .collect()
+}
+
+/// Settles outstanding conflicts according to the plan's policy, then writes
+/// every planned file that would change what is on disk. Nothing is written
+/// when the policy refuses to proceed.
+pub fn extract_plan(plan: &mut ScaffoldPlan, dest_root: &Path) -> anyhow::Result<()> {
+    match plan.on_conflict {
+        ConflictPolicy::Fail => {
+            let conflicts: Vec<&str> = plan.conflicts().map(|f| f.path.as_str()).collect();
+            if !conflicts.is_empty() {
+                anyhow::bail!(
+                    "{} existing file(s) differ from the templates; nothing was written:\n  {}",
+                    conflicts.len(),
+                    conflicts.join("\n  ")
+                );
+            }
+        }
+        ConflictPolicy::Prompt => {
+            if plan.conflicts().next().is_some() && !std::io::stdin().is_terminal() {
+                anyhow::bail!(
+                    "--on-conflict prompt needs an interactive terminal; \
+                     choose skip, overwrite, backup or fail instead"
+                );
+            }
+            for file in plan.conflicts_mut() {
+                file.action = prompt_conflict(&file.path)?;
+            }
+        }
+        ConflictPolicy::Skip | ConflictPolicy::Overwrite | ConflictPolicy::Backup => {}
+    }
+
+    for file in plan.pending() {
+        file.write(dest_root)?;
+    }
+    Ok(())
+}
+
+fn prompt_conflict(path: &str) -> anyhow::Result<FileAction> {
+    let choices = [
+        "Keep existing file",
+        "Overwrite",
+        "Back up to .orig and overwrite",
+    ];
+    let choice = dialoguer::Select::new()
+        .with_prompt(format!(
+            "'{path}' already exists and differs from the template"
+        ))
+        .items(choices)
+        .default(0)
+        .interact()?;
+    Ok(match choice {
+        1 => FileAction::Overwrite,
+        2 => FileAction::Backup,
+        _ => FileAction::Skip,
+    })
+}
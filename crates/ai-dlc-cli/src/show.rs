@@ -0,0 +1,69 @@
+use clap::Parser;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::config::Config;
+use crate::lock::Lockfile;
+use crate::render::{Renderer, TEMPLATE_SUFFIX, VarArgs};
+use crate::templates::{TemplateArgs, Templates};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct ShowArgs {
+    /// Template to print, as `<provider>/<path>` shown by `list`. The path
+    /// scaffold writes it to also works.
+    template: String,
+    /// Render template variables the way scaffold would.
+    #[arg(long)]
+    render: bool,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+pub fn handle_show(args: ShowArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let config = Config::load(root)?;
+    let templates = Templates::load(root, &config, &args.templates, None)?;
+
+    let Some((provider, path)) = args.template.split_once('/') else {
+        anyhow::bail!(
+            "Expected <provider>/<path>, for example claude/agents/example.md; \
+             run `ai-dlc-cli list` to see the templates."
+        );
+    };
+    let file = templates
+        .provider_templates(provider)?
+        .unwrap_or_default()
+        .into_iter()
+        .find(|file| {
+            file.source == path
+                || file.path == path
+                || file.path.strip_suffix(TEMPLATE_SUFFIX) == Some(path)
+        });
+    let Some(file) = file else {
+        anyhow::bail!(
+            "Template '{}' not found; run `ai-dlc-cli list --provider {}` to see its templates.",
+            args.template,
+            provider
+        );
+    };
+
+    let contents = if args.render {
+        let recorded = Lockfile::load(root)?
+            .providers
+            .get(provider)
+            .map(|entry| entry.variables.clone())
+            .unwrap_or_else(BTreeMap::new);
+        let mut renderer = Renderer::new(root, &config, &recorded, &args.vars);
+        let file = renderer.render_all(vec![file])?.remove(0);
+        file.contents
+    } else {
+        file.contents
+    };
+    std::io::stdout().write_all(&contents)?;
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use anyhow::Context;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::lock;
+
+/// Manifest at the root of a template pack listing the hash of every file.
+pub const PACK_MANIFEST: &str = "pack-manifest.json";
+
+/// Detached ed25519 signature over the exact bytes of [`PACK_MANIFEST`].
+pub const PACK_SIGNATURE: &str = "pack-manifest.json.sig";
+
+const PACK_MANIFEST_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+struct PackManifest {
+    version: u32,
+    /// `sha256:<hex>` of each file, keyed by its `/`-separated path.
+    files: BTreeMap<String, String>,
+}
+
+/// Checks a pack's files against its manifest and signature, then removes
+/// both from `files`. The signature must come from one of `trusted_keys`;
+/// once any key is trusted, unsigned packs are refused. A pack without a
+/// manifest is only accepted when `require_manifest` is false and no keys
+/// are trusted.
+pub fn verify(
+    pack: &str,
+    files: &mut BTreeMap<String, Vec<u8>>,
+    trusted_keys: &[String],
+    require_manifest: bool,
+) -> anyhow::Result<()> {
+    let manifest_bytes = files.remove(PACK_MANIFEST);
+    let signature = files.remove(PACK_SIGNATURE);
+
+    let Some(manifest_bytes) = manifest_bytes else {
+        if require_manifest || !trusted_keys.is_empty() {
+            anyhow::bail!(
+                "Template pack {} has no {}; refusing to use unverified templates.",
+                pack,
+                PACK_MANIFEST
+            );
+        }
+        tracing::debug!("Template pack {} has no {}", pack, PACK_MANIFEST);
+        return Ok(());
+    };
+
+    match signature {
+        Some(signature) => verify_signature(pack, &manifest_bytes, &signature, trusted_keys)?,
+        None if !trusted_keys.is_empty() => anyhow::bail!(
+            "Template pack {} is not signed, but trusted_keys is set in ai-dlc.toml.",
+            pack
+        ),
+        None => tracing::warn!(
+            "Template pack {} is not signed; only checksums were verified.",
+            pack
+        ),
+    }
+
+    let manifest: PackManifest = serde_json::from_slice(&manifest_bytes).with_context(|| {
+        format!(
+            "Failed to parse {} of template pack {}",
+            PACK_MANIFEST, pack
+        )
+    })?;
+    if manifest.version > PACK_MANIFEST_VERSION {
+        anyhow::bail!(
+            "{} of template pack {} has version {} but this CLI only understands version {}; \
+             upgrade ai-dlc-cli.",
+            PACK_MANIFEST,
+            pack,
+            manifest.version,
+            PACK_MANIFEST_VERSION
+        );
+    }
+
+    for (path, contents) in files.iter() {
+        match manifest.files.get(path) {
+            Some(expected) if *expected == lock::hash_bytes(contents) => {}
+            Some(_) => anyhow::bail!(
+                "Checksum mismatch for '{}' in template pack {}; the pack has been altered.",
+                path,
+                pack
+            ),
+            None => anyhow::bail!(
+                "'{}' in template pack {} is not listed in {}.",
+                path,
+                pack,
+                PACK_MANIFEST
+            ),
+        }
+    }
+    if let Some(path) = manifest
+        .files
+        .keys()
+        .find(|path| !files.contains_key(*path))
+    {
+        anyhow::bail!(
+            "'{}' is listed in {} but missing from template pack {}.",
+            path,
+            PACK_MANIFEST,
+            pack
+        );
+    }
+    tracing::info!("Verified {} file(s) in template pack {}", files.len(), pack);
+    Ok(())
+}
+
+fn verify_signature(
+    pack: &str,
+    manifest: &[u8],
+    signature: &[u8],
+    trusted_keys: &[String],
+) -> anyhow::Result<()> {
+    if trusted_keys.is_empty() {
+        anyhow::bail!(
+            "Template pack {} is signed, but no trusted_keys are set in ai-dlc.toml to check it \
+             against; add the publisher's public key.",
+            pack
+        );
+    }
+    let signature = BASE64
+        .decode(String::from_utf8_lossy(signature).trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .with_context(|| format!("{} of template pack {} is malformed", PACK_SIGNATURE, pack))?;
+    for key in trusted_keys {
+        let key = parse_public_key(key)?;
+        if key.verify_strict(manifest, &signature).is_ok() {
+            tracing::info!(
+                "Template pack {} is signed by {}",
+                pack,
+                encode_public_key(&key)
+            );
+            return Ok(());
+        }
+    }
+    anyhow::bail!(
+        "Template pack {} is not signed by any key in trusted_keys; refusing to use it.",
+        pack
+    )
+}
+
+/// Builds the manifest for `files` and, with a key, its signature.
+pub fn sign(
+    files: &BTreeMap<String, Vec<u8>>,
+    key: Option<&SigningKey>,
+) -> anyhow::Result<(Vec<u8>, Option<Vec<u8>>)> {
+    let manifest = PackManifest {
+        version: PACK_MANIFEST_VERSION,
+        files: files
+            .iter()
+            .map(|(path, contents)| (path.clone(), lock::hash_bytes(contents)))
+            .collect(),
+    };
+    let mut bytes = serde_json::to_vec_pretty(&manifest)?;
+    bytes.push(b'\n');
+    let signature = key.map(|key| {
+        let mut encoded = BASE64.encode(key.sign(&bytes).to_bytes());
+        encoded.push('\n');
+        encoded.into_bytes()
+    });
+    Ok((bytes, signature))
+}
+
+/// Creates a new signing key.
+pub fn generate_key() -> anyhow::Result<SigningKey> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed)
+        .map_err(|err| anyhow::anyhow!("Failed to gather randomness for a key: {err}"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Reads a signing key stored as base64, as written by `pack keygen`.
+pub fn parse_signing_key(encoded: &str) -> anyhow::Result<SigningKey> {
+    let seed: [u8; 32] = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .context("Signing key is not a base64-encoded 32-byte ed25519 key")?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+pub fn encode_signing_key(key: &SigningKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    BASE64.encode(key.to_bytes())
+}
+
+fn parse_public_key(encoded: &str) -> anyhow::Result<VerifyingKey> {
+    let bytes: [u8; 32] = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .with_context(|| {
+            format!("Trusted key '{encoded}' is not a base64-encoded 32-byte ed25519 public key")
+        })?;
+    VerifyingKey::from_bytes(&bytes)
+        .with_context(|| format!("Trusted key '{encoded}' is not a valid ed25519 public key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACK: &str = "test-pack";
+
+    const PROVIDER: &str = r#"name = "claude"
+
+[groups.core]
+description = "Project memory"
+
+[groups.agents]
+description = "Sub-agents"
+
+[[mappings]]
+source = "CLAUDE.md.jinja"
+dest = "CLAUDE.md"
+group = "core"
+
+[[mappings]]
+source = "agents/"
+dest = ".claude/agents/"
+group = "agents"
+"#;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn public(key: &SigningKey) -> String {
+        encode_public_key(&key.verifying_key())
+    }
+
+    /// A pack's files with its manifest, signed by `signer` if given.
+    fn pack(signer: Option<&SigningKey>) -> BTreeMap<String, Vec<u8>> {
+        let mut files = BTreeMap::from([
+            (
+                "claude/provider.toml".to_string(),
+                PROVIDER.as_bytes().to_vec(),
+            ),
+            (
+                "claude/CLAUDE.md.jinja".to_string(),
+                b"# {{ project_name }}\n".to_vec(),
+            ),
+            (
+                "claude/agents/reviewer.md".to_string(),
+                b"---\nname: reviewer\ndescription: Reviews changes.\n---\n".to_vec(),
+            ),
+        ]);
+        let (manifest, signature) = sign(&files, signer).unwrap();
+        files.insert(PACK_MANIFEST.to_string(), manifest);
+        if let Some(signature) = signature {
+            files.insert(PACK_SIGNATURE.to_string(), signature);
+        }
+        files
+    }
+
+    fn error(result: anyhow::Result<()>) -> String {
+        format!("{:#}", result.expect_err("verification should fail"))
+    }
+
+    #[test]
+    fn accepts_a_pack_signed_by_a_trusted_key() {
+        let signer = key(1);
+        let mut files = pack(Some(&signer));
+        verify(PACK, &mut files, &[public(&key(2)), public(&signer)], true).unwrap();
+        assert!(!files.contains_key(PACK_MANIFEST));
+        assert!(!files.contains_key(PACK_SIGNATURE));
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn rejects_a_tampered_file() {
+        let signer = key(1);
+        let mut files = pack(Some(&signer));
+        files.insert(
+            "claude/CLAUDE.md.jinja".to_string(),
+            b"# Altered\n".to_vec(),
+        );
+        let err = error(verify(PACK, &mut files, &[public(&signer)], true));
+        assert!(
+            err.contains("Checksum mismatch for 'claude/CLAUDE.md.jinja'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_a_file_missing_from_the_manifest() {
+        let signer = key(1);
+        let mut files = pack(Some(&signer));
+        files.insert("claude/extra.md".to_string(), b"sneaked in\n".to_vec());
+        let err = error(verify(PACK, &mut files, &[public(&signer)], true));
+        assert!(err.contains("'claude/extra.md'"), "{err}");
+        assert!(err.contains("is not listed in"), "{err}");
+    }
+
+    #[test]
+    fn rejects_a_listed_file_missing_from_the_pack() {
+        let signer = key(1);
+        let mut files = pack(Some(&signer));
+        files.remove("claude/CLAUDE.md.jinja");
+        let err = error(verify(PACK, &mut files, &[public(&signer)], true));
+        assert!(err.contains("missing from template pack"), "{err}");
+    }
+
+    #[test]
+    fn rejects_a_signature_from_an_untrusted_key() {
+        let mut files = pack(Some(&key(1)));
+        let err = error(verify(PACK, &mut files, &[public(&key(2))], true));
+        assert!(
+            err.contains("not signed by any key in trusted_keys"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn rejects_an_unsigned_pack_once_keys_are_trusted() {
+        let mut files = pack(None);
+        let err = error(verify(PACK, &mut files, &[public(&key(1))], true));
+        assert!(err.contains("is not signed"), "{err}");
+
+        let mut files = pack(None);
+        files.remove(PACK_MANIFEST);
+        let err = error(verify(PACK, &mut files, &[public(&key(1))], false));
+        assert!(
+            err.contains("refusing to use unverified templates"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn accepts_an_unsigned_pack_when_no_keys_are_trusted() {
+        let mut files = pack(None);
+        verify(PACK, &mut files, &[], true).unwrap();
+
+        let mut files = pack(None);
+        files.remove(PACK_MANIFEST);
+        verify(PACK, &mut files, &[], false).unwrap();
+        let mut files = pack(None);
+        files.remove(PACK_MANIFEST);
+        let err = error(verify(PACK, &mut files, &[], true));
+        assert!(err.contains("has no"), "{err}");
+    }
+}
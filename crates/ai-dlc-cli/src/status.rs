@@ -0,0 +1,221 @@
+use clap::Parser;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock::{self, LOCK_PATH, Lockfile};
+use crate::plan::OutputFormat;
+use crate::render::{Renderer, VarArgs};
+use crate::scaffold;
+use crate::templates::{RecordedTemplates, TemplateArgs};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct StatusArgs {
+    /// Providers to inspect; defaults to every provider in the lockfile.
+    #[arg(long, short)]
+    provider: Vec<String>,
+    /// Exit with an error when any file has drifted, for use in CI.
+    #[arg(long)]
+    check: bool,
+    /// Output format for the report.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+/// How a scaffolded file compares to the lock record and the templates
+/// embedded in this binary.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum FileState {
+    /// Matches what was scaffolded and what the binary ships.
+    Unmodified,
+    /// Edited since it was scaffolded.
+    Modified,
+    /// Recorded in the lockfile but deleted locally.
+    Missing,
+    /// Present in a provider directory but not managed by ai-dlc.
+    Untracked,
+    /// Unmodified locally, but the binary ships a different version (or no
+    /// longer ships it, or ships a file that was never installed).
+    Outdated,
+}
+
+impl FileState {
+    fn label(self) -> &'static str {
+        match self {
+            FileState::Unmodified => "unmodified",
+            FileState::Modified => "modified",
+            FileState::Missing => "missing",
+            FileState::Untracked => "untracked",
+            FileState::Outdated => "outdated",
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct FileStatus {
+    provider: String,
+    path: String,
+    state: FileState,
+}
+
+pub fn handle_status(args: StatusArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let lockfile = Lockfile::load(root)?;
+    let config = Config::load(root)?;
+    let mut loader = RecordedTemplates::new(root, &config, &args.templates);
+
+    let providers: Vec<String> = if args.provider.is_empty() {
+        lockfile.providers.keys().cloned().collect()
+    } else {
+        args.provider
+    };
+    if providers.is_empty() {
+        anyhow::bail!(
+            "No scaffold record found at {}; run `ai-dlc scaffold` first or pass --provider.",
+            LOCK_PATH
+        );
+    }
+
+    let managed: BTreeSet<&str> = lockfile
+        .providers
+        .values()
+        .flat_map(|entry| entry.files.keys().map(String::as_str))
+        .collect();
+
+    let mut statuses: BTreeMap<String, FileStatus> = BTreeMap::new();
+    for provider in &providers {
+        let entry = lockfile.providers.get(provider);
+        let locked = entry.map(|entry| entry.files.clone()).unwrap_or_default();
+        let groups = entry
+            .map(|entry| entry.groups.as_slice())
+            .unwrap_or_default();
+        let recorded = entry
+            .map(|entry| entry.variables.clone())
+            .unwrap_or_default();
+        let source = loader.get(entry.and_then(|entry| entry.pack.as_ref()), true)?;
+        let templates: BTreeMap<String, Vec<u8>> =
+            Renderer::new(root, &config, &recorded, &VarArgs::default())
+                .render_all(scaffold::select_groups(
+                    source.provider_templates(provider)?.unwrap_or_default(),
+                    groups,
+                ))?
+                .into_iter()
+                .map(|file| (file.path, file.contents))
+                .collect();
+
+        let record = |statuses: &mut BTreeMap<String, FileStatus>, path: &str, state| {
+            statuses.insert(
+                path.to_string(),
+                FileStatus {
+                    provider: provider.clone(),
+                    path: path.to_string(),
+                    state,
+                },
+            );
+        };
+
+        for (path, entry) in &locked {
+            let state = match fsutil::read_optional(&root.join(path))? {
+                None => FileState::Missing,
+                Some(local) if lock::hash_bytes(&local) != entry.hash => FileState::Modified,
+                Some(_) => {
+                    // A merged file was written as more than the template, so
+                    // the template it came from is the recorded merge base.
+                    let base = lock::load_base(root, path)?
+                        .map(|base| lock::hash_bytes(&base))
+                        .unwrap_or_else(|| entry.hash.clone());
+                    match templates.get(path) {
+                        Some(template) if lock::hash_bytes(template) == base => {
+                            FileState::Unmodified
+                        }
+                        _ => FileState::Outdated,
+                    }
+                }
+            };
+            record(&mut statuses, path, state);
+        }
+
+        for path in templates.keys().filter(|path| !locked.contains_key(*path)) {
+            let state = if root.join(path).exists() {
+                FileState::Untracked
+            } else {
+                FileState::Outdated
+            };
+            record(&mut statuses, path, state);
+        }
+
+        let mut dirs: BTreeSet<String> = locked
+            .keys()
+            .chain(templates.keys())
+            .filter_map(|path| path.split_once('/').map(|(dir, _)| dir.to_string()))
+            .collect();
+        dirs.insert(format!(".{provider}"));
+        for dir in dirs {
+            for path in fsutil::walk_files(root, &dir)? {
+                if !managed.contains(path.as_str())
+                    && !statuses.contains_key(&path)
+                    && !is_user_local(&path)
+                {
+                    record(&mut statuses, &path, FileState::Untracked);
+                }
+            }
+        }
+    }
+
+    let drifted = statuses
+        .values()
+        .filter(|status| status.state != FileState::Unmodified)
+        .count();
+
+    match args.format {
+        OutputFormat::Text => {
+            for status in statuses.values() {
+                if status.state != FileState::Unmodified {
+                    println!("{:<10} {}", status.state.label(), status.path);
+                }
+            }
+            let count = |wanted: FileState| {
+                statuses
+                    .values()
+                    .filter(|status| status.state == wanted)
+                    .count()
+            };
+            println!(
+                "\n{} files: {} unmodified, {} modified, {} missing, {} untracked, {} outdated",
+                statuses.len(),
+                count(FileState::Unmodified),
+                count(FileState::Modified),
+                count(FileState::Missing),
+                count(FileState::Untracked),
+                count(FileState::Outdated)
+            );
+        }
+        OutputFormat::Json => {
+            let files: Vec<&FileStatus> = statuses.values().collect();
+            println!("{}", serde_json::to_string_pretty(&files)?);
+        }
+    }
+
+    if args.check && drifted > 0 {
+        anyhow::bail!(
+            "{} scaffolded file(s) have drifted from the standard",
+            drifted
+        );
+    }
+    Ok(())
+}
+
+/// Personal files the tools keep beside the shared ones and expect to stay
+/// out of version control, such as `.claude/settings.local.json` or a
+/// `.gemini/.env` holding API keys. They are never drift.
+fn is_user_local(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    name == ".env" || name.ends_with(".local") || name.contains(".local.")
+}
@@ -0,0 +1,777 @@
+use anyhow::Context;
+use serde::Serialize;
+use tree_sitter::{Language, Node, Parser};
+
+/// Signatures longer than this are cut, so one generic-heavy item does not
+/// swamp a dossier.
+const MAX_SIGNATURE: usize = 160;
+
+/// Source languages whose public symbols can be extracted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceLanguage {
+    Rust,
+    Python,
+    TypeScript,
+    Tsx,
+}
+
+impl SourceLanguage {
+    /// The language of `path`, from its extension.
+    pub fn of(path: &str) -> Option<Self> {
+        let (_, extension) = path.rsplit_once('.')?;
+        Some(match extension {
+            "rs" => SourceLanguage::Rust,
+            "py" | "pyi" => SourceLanguage::Python,
+            "ts" | "mts" | "cts" => SourceLanguage::TypeScript,
+            "tsx" => SourceLanguage::Tsx,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SourceLanguage::Rust => "Rust",
+            SourceLanguage::Python => "Python",
+            SourceLanguage::TypeScript | SourceLanguage::Tsx => "TypeScript",
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            SourceLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+            SourceLanguage::Python => tree_sitter_python::LANGUAGE.into(),
+            SourceLanguage::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            SourceLanguage::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        }
+    }
+}
+
+/// The structure of one source file: what it exposes, what it imports and
+/// whether it is an entry point.
+#[derive(Serialize, Debug, Default)]
+pub struct FileOutline {
+    pub symbols: Vec<Symbol>,
+    pub imports: Vec<Import>,
+    /// Why the file is an entry point, such as `binary (fn main)`.
+    pub entry_point: Option<String>,
+}
+
+/// A public item: a Rust `pub` item, a Python name without a leading
+/// underscore or an exported TypeScript declaration.
+#[derive(Serialize, Debug)]
+pub struct Symbol {
+    pub kind: &'static str,
+    pub name: String,
+    /// The declaration up to its body, on one line.
+    pub signature: String,
+    /// 1-based line of the declaration.
+    pub line: usize,
+    /// The type or class a method belongs to.
+    pub parent: Option<String>,
+    /// First line of the doc comment or docstring.
+    pub doc: Option<String>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Import {
+    /// The module or package, such as `crate::config`, `serde` or `./util`.
+    pub path: String,
+    /// Whether it points inside the project rather than at a dependency.
+    pub internal: bool,
+}
+
+/// Parses `source`, the file at `path`, and outlines it.
+pub fn outline(path: &str, language: SourceLanguage, source: &str) -> anyhow::Result<FileOutline> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .with_context(|| format!("Failed to load the {} grammar", language.name()))?;
+    let tree = parser
+        .parse(source, None)
+        .with_context(|| format!("Failed to parse '{}'", path))?;
+    if tree.root_node().has_error() {
+        tracing::debug!("'{}' has syntax errors; its outline may be partial.", path);
+    }
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let mut outline = FileOutline::default();
+    let source = source.as_bytes();
+    match language {
+        SourceLanguage::Rust => {
+            rust_items(tree.root_node(), source, None, &mut outline);
+            let modules = rust_modules(tree.root_node(), source);
+            resolve_rust_imports(&mut outline, &modules);
+        }
+        SourceLanguage::Python => python_module(tree.root_node(), source, &mut outline),
+        SourceLanguage::TypeScript | SourceLanguage::Tsx => {
+            typescript_module(tree.root_node(), source, &mut outline)
+        }
+    }
+    outline.entry_point = outline.entry_point.take().or_else(|| {
+        let entry = match (language, file_name) {
+            (SourceLanguage::Rust, "lib.rs") => "library root",
+            (SourceLanguage::Rust, "build.rs") => "build script",
+            (SourceLanguage::Python, "__main__.py") => "package entry point (python -m)",
+            (SourceLanguage::Python, "__init__.py") => "package root",
+            (SourceLanguage::TypeScript | SourceLanguage::Tsx, name)
+                if name.starts_with("index.") =>
+            {
+                "module root"
+            }
+            _ => return None,
+        };
+        Some(entry.to_string())
+    });
+    outline.imports.sort();
+    outline.imports.dedup();
+    Ok(outline)
+}
+
+fn text<'a>(node: Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+fn field_text<'a>(node: Node, field: &str, source: &'a [u8]) -> Option<&'a str> {
+    node.child_by_field_name(field)
+        .map(|child| text(child, source))
+}
+
+/// The declaration of `node` up to its body or value, on one line.
+fn signature(node: Node, source: &[u8]) -> String {
+    let end = ["body", "value"]
+        .iter()
+        .filter_map(|field| node.child_by_field_name(field))
+        .map(|child| child.start_byte())
+        .min()
+        .unwrap_or(node.end_byte());
+    let raw = String::from_utf8_lossy(&source[node.start_byte()..end]);
+    let mut signature = raw
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace(" )", ")");
+    while signature.ends_with(['=', ':', ';', '{']) {
+        signature.pop();
+        signature.truncate(signature.trim_end().len());
+    }
+    if signature.chars().count() > MAX_SIGNATURE {
+        signature = signature.chars().take(MAX_SIGNATURE).collect::<String>() + "…";
+    }
+    signature
+}
+
+fn symbol(
+    kind: &'static str,
+    name: &str,
+    node: Node,
+    source: &[u8],
+    parent: Option<&str>,
+    doc: Option<String>,
+) -> Symbol {
+    Symbol {
+        kind,
+        name: name.to_string(),
+        signature: signature(node, source),
+        line: node.start_position().row + 1,
+        parent: parent.map(str::to_string),
+        doc,
+    }
+}
+
+/// First sentence of the `///` comments directly above `node`, skipping
+/// attributes.
+fn rust_doc(node: Node, source: &[u8]) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut sibling = node.prev_sibling();
+    while let Some(previous) = sibling {
+        match previous.kind() {
+            "attribute_item" => {}
+            "line_comment" => {
+                let comment = text(previous, source);
+                match comment.strip_prefix("///") {
+                    Some(line) if !line.starts_with('/') => lines.push(line.trim()),
+                    _ => break,
+                }
+            }
+            _ => break,
+        }
+        sibling = previous.prev_sibling();
+    }
+    lines.reverse();
+    first_sentence(lines)
+}
+
+/// The first sentence of the first paragraph of a doc comment, on one line.
+fn first_sentence<'a>(lines: impl IntoIterator<Item = &'a str>) -> Option<String> {
+    let paragraph: Vec<&str> = lines
+        .into_iter()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    let text = paragraph.join(" ");
+    let sentence = match text.find(". ") {
+        Some(end) => &text[..=end],
+        None => &text,
+    };
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+fn rust_items(node: Node, source: &[u8], parent: Option<&str>, outline: &mut FileOutline) {
+    let mut cursor = node.walk();
+    for item in node.named_children(&mut cursor) {
+        let kind = match item.kind() {
+            "function_item" => "fn",
+            "struct_item" => "struct",
+            "enum_item" => "enum",
+            "union_item" => "union",
+            "trait_item" => "trait",
+            "type_item" => "type",
+            "const_item" => "const",
+            "static_item" => "static",
+            "mod_item" => "mod",
+            "use_declaration" => {
+                if let Some(argument) = field_text(item, "argument", source) {
+                    outline.imports.push(rust_import(argument));
+                }
+                continue;
+            }
+            "impl_item" => {
+                // Only inherent impls add to a type's public surface.
+                if item.child_by_field_name("trait").is_none()
+                    && let (Some(name), Some(body)) = (
+                        field_text(item, "type", source),
+                        item.child_by_field_name("body"),
+                    )
+                {
+                    rust_items(body, source, Some(name), outline);
+                }
+                continue;
+            }
+            "macro_definition" => {
+                let exported = rust_attributes(item, source)
+                    .any(|attribute| attribute.contains("macro_export"));
+                if exported && let Some(name) = field_text(item, "name", source) {
+                    outline.symbols.push(Symbol {
+                        kind: "macro",
+                        name: name.to_string(),
+                        signature: format!("macro_rules! {name}"),
+                        line: item.start_position().row + 1,
+                        parent: None,
+                        doc: rust_doc(item, source),
+                    });
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(name) = field_text(item, "name", source) else {
+            continue;
+        };
+        if kind == "fn" && name == "main" && parent.is_none() {
+            outline.entry_point = Some("binary (fn main)".to_string());
+        }
+        // Only a bare `pub` is public; `pub(crate)`, `pub(super)` and
+        // `pub(in path)` stay inside the crate.
+        let public = item.named_child(0).is_some_and(|child| {
+            child.kind() == "visibility_modifier" && text(child, source) == "pub"
+        });
+        if !public {
+            continue;
+        }
+        outline.symbols.push(symbol(
+            kind,
+            name,
+            item,
+            source,
+            parent,
+            rust_doc(item, source),
+        ));
+        if kind == "mod"
+            && let Some(body) = item.child_by_field_name("body")
+        {
+            rust_items(body, source, Some(name), outline);
+        }
+    }
+}
+
+/// Names of the modules declared at the top of a Rust file.
+fn rust_modules(node: Node, source: &[u8]) -> Vec<String> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .filter(|item| item.kind() == "mod_item")
+        .filter_map(|item| field_text(item, "name", source).map(str::to_string))
+        .collect()
+}
+
+fn rust_attributes<'a>(node: Node<'a>, source: &'a [u8]) -> impl Iterator<Item = &'a str> {
+    std::iter::successors(node.prev_sibling(), |sibling| sibling.prev_sibling())
+        .take_while(|sibling| matches!(sibling.kind(), "attribute_item" | "line_comment"))
+        .map(move |sibling| text(sibling, source))
+}
+
+fn rust_import(argument: &str) -> Import {
+    let segments: Vec<&str> = argument
+        .trim_start_matches("::")
+        .split("::")
+        .map(str::trim)
+        .collect();
+    let plain = |segment: &&&str| segment.chars().all(|c| c.is_alphanumeric() || c == '_');
+    match segments[0] {
+        "crate" | "self" | "super" => {
+            let path = match segments.get(1).filter(plain) {
+                Some(module) => format!("{}::{}", segments[0], module),
+                None => segments[0].to_string(),
+            };
+            Import {
+                path,
+                internal: true,
+            }
+        }
+        first => Import {
+            path: first.split(['{', ' ']).next().unwrap_or(first).to_string(),
+            internal: false,
+        },
+    }
+}
+
+/// Treats imports of modules the file declares with `mod` as internal and
+/// drops the standard library, which is not a dependency.
+fn resolve_rust_imports(outline: &mut FileOutline, modules: &[String]) {
+    outline
+        .imports
+        .retain(|import| !matches!(import.path.as_str(), "std" | "core" | "alloc"));
+    for import in &mut outline.imports {
+        if !import.internal && modules.contains(&import.path) {
+            import.internal = true;
+            import.path = format!("self::{}", import.path);
+        }
+    }
+}
+
+/// The first sentence of the docstring opening `body`.
+fn python_docstring(body: Option<Node>, source: &[u8]) -> Option<String> {
+    let first = body?.named_child(0)?;
+    let string = first.named_child(0)?;
+    if first.kind() != "expression_statement" || string.kind() != "string" {
+        return None;
+    }
+    first_sentence(
+        text(string, source)
+            .trim_start_matches(['r', 'u', 'R', 'U'])
+            .trim_matches(['"', '\''])
+            .lines(),
+    )
+}
+
+fn python_module(node: Node, source: &[u8], outline: &mut FileOutline) {
+    let mut cursor = node.walk();
+    for statement in node.named_children(&mut cursor) {
+        let definition = match statement.kind() {
+            "decorated_definition" => statement.child_by_field_name("definition"),
+            _ => Some(statement),
+        };
+        let Some(definition) = definition else {
+            continue;
+        };
+        match definition.kind() {
+            "function_definition" | "class_definition" => {
+                python_definition(definition, source, None, outline)
+            }
+            "import_statement" => {
+                let mut names = definition.walk();
+                for name in definition.children_by_field_name("name", &mut names) {
+                    let module = match name.kind() {
+                        "aliased_import" => field_text(name, "name", source),
+                        _ => Some(text(name, source)),
+                    };
+                    if let Some(module) = module {
+                        outline.imports.push(python_import(module));
+                    }
+                }
+            }
+            "import_from_statement" => {
+                if let Some(module) = field_text(definition, "module_name", source) {
+                    outline.imports.push(python_import(module));
+                }
+            }
+            "if_statement" => {
+                let condition = field_text(definition, "condition", source).unwrap_or_default();
+                if condition.contains("__name__") && condition.contains("__main__") {
+                    outline.entry_point = Some("script (if __name__ == \"__main__\")".to_string());
+                }
+            }
+            "expression_statement" => {
+                // Module-level constants, by the UPPER_CASE convention.
+                let Some(assignment) = definition
+                    .named_child(0)
+                    .filter(|child| child.kind() == "assignment")
+                else {
+                    continue;
+                };
+                let Some(name) = field_text(assignment, "left", source) else {
+                    continue;
+                };
+                let constant = !name.starts_with('_')
+                    && name.chars().any(|c| c.is_ascii_uppercase())
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+                if constant {
+                    outline
+                        .symbols
+                        .push(symbol("const", name, assignment, source, None, None));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn python_definition(
+    definition: Node,
+    source: &[u8],
+    parent: Option<&str>,
+    outline: &mut FileOutline,
+) {
+    let Some(name) = field_text(definition, "name", source) else {
+        return;
+    };
+    if name.starts_with('_') {
+        return;
+    }
+    let body = definition.child_by_field_name("body");
+    let is_class = definition.kind() == "class_definition";
+    outline.symbols.push(symbol(
+        if is_class { "class" } else { "def" },
+        name,
+        definition,
+        source,
+        parent,
+        python_docstring(body, source),
+    ));
+    if is_class && let Some(body) = body {
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            let member = match member.kind() {
+                "decorated_definition" => member.child_by_field_name("definition"),
+                _ => Some(member),
+            };
+            if let Some(method) = member.filter(|m| m.kind() == "function_definition") {
+                python_definition(method, source, Some(name), outline);
+            }
+        }
+    }
+}
+
+fn python_import(module: &str) -> Import {
+    if module.starts_with('.') {
+        Import {
+            path: module.to_string(),
+            internal: true,
+        }
+    } else {
+        Import {
+            path: module.split('.').next().unwrap_or(module).to_string(),
+            internal: false,
+        }
+    }
+}
+
+/// First sentence of the `/** … */` comment directly above `node`.
+fn jsdoc(node: Node, source: &[u8]) -> Option<String> {
+    let comment = node.prev_sibling().filter(|s| s.kind() == "comment")?;
+    first_sentence(
+        text(comment, source)
+            .strip_prefix("/**")?
+            .trim_end_matches("*/")
+            .lines()
+            .map(|line| line.trim().trim_start_matches('*'))
+            .take_while(|line| !line.trim_start().starts_with('@')),
+    )
+}
+
+fn typescript_module(node: Node, source: &[u8], outline: &mut FileOutline) {
+    let mut cursor = node.walk();
+    for statement in node.named_children(&mut cursor) {
+        match statement.kind() {
+            "import_statement" => {
+                if let Some(from) = field_text(statement, "source", source) {
+                    outline.imports.push(typescript_import(from));
+                }
+            }
+            "export_statement" => {
+                // `export … from "…"` re-exports another module.
+                if let Some(from) = field_text(statement, "source", source) {
+                    outline.imports.push(typescript_import(from));
+                }
+                let doc = jsdoc(statement, source);
+                if let Some(declaration) = statement.child_by_field_name("declaration") {
+                    typescript_declaration(declaration, statement, source, doc, outline);
+                } else if statement.child_by_field_name("value").is_some() {
+                    outline
+                        .symbols
+                        .push(symbol("default", "default", statement, source, None, doc));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn typescript_declaration(
+    declaration: Node,
+    export: Node,
+    source: &[u8],
+    doc: Option<String>,
+    outline: &mut FileOutline,
+) {
+    let kind = match declaration.kind() {
+        "function_declaration" | "generator_function_declaration" | "function_signature" => {
+            "function"
+        }
+        "class_declaration" | "abstract_class_declaration" => "class",
+        "interface_declaration" => "interface",
+        "type_alias_declaration" => "type",
+        "enum_declaration" => "enum",
+        "internal_module" | "module" => "namespace",
+        "lexical_declaration" | "variable_declaration" => {
+            let mut cursor = declaration.walk();
+            for declarator in declaration.named_children(&mut cursor) {
+                if declarator.kind() != "variable_declarator" {
+                    continue;
+                }
+                if let Some(name) = field_text(declarator, "name", source) {
+                    let mut symbol = symbol("const", name, declarator, source, None, doc.clone());
+                    let keyword = text(declaration, source)
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or("const");
+                    symbol.signature = format!("export {keyword} {}", symbol.signature);
+                    outline.symbols.push(symbol);
+                }
+            }
+            return;
+        }
+        _ => return,
+    };
+    let Some(name) = field_text(declaration, "name", source) else {
+        return;
+    };
+    let mut symbol = symbol(kind, name, declaration, source, None, doc);
+    // Report the line of the `export`, where a reader would look.
+    symbol.line = export.start_position().row + 1;
+    symbol.signature = format!("export {}", symbol.signature);
+    outline.symbols.push(symbol);
+}
+
+fn typescript_import(from: &str) -> Import {
+    let path = from.trim_matches(['"', '\'', '`']);
+    if path.starts_with('.') || path.starts_with('/') {
+        return Import {
+            path: path.to_string(),
+            internal: true,
+        };
+    }
+    // `@scope/name/sub` belongs to `@scope/name`, `name/sub` to `name`.
+    let segments = if path.starts_with('@') { 2 } else { 1 };
+    Import {
+        path: path.split('/').take(segments).collect::<Vec<_>>().join("/"),
+        internal: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_bare_pub_items_are_public() {
+        let source = "\
+pub fn exported() {}
+pub(crate) fn crate_only() {}
+pub(super) struct ParentOnly;
+pub(in crate::a) enum PathOnly {}
+fn private() {}
+
+pub struct Widget;
+
+impl Widget {
+    pub fn new() -> Self { Widget }
+    pub(crate) fn helper(&self) {}
+}
+";
+        let outline = outline("src/lib.rs", SourceLanguage::Rust, source).unwrap();
+        let names: Vec<&str> = outline
+            .symbols
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(names, ["exported", "Widget", "new"]);
+    }
+
+    fn kinds_and_names(outline: &FileOutline) -> Vec<(&str, &str, Option<&str>)> {
+        outline
+            .symbols
+            .iter()
+            .map(|symbol| (symbol.kind, symbol.name.as_str(), symbol.parent.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn python_names_without_an_underscore_are_public() {
+        let source = r#""""Billing helpers."""
+import os
+import numpy as np
+from .models import Invoice
+from requests.adapters import HTTPAdapter
+
+MAX_RETRIES = 3
+_cache = {}
+default_region = "eu"
+
+
+@dataclass
+class Invoice:
+    """An issued invoice. Immutable once sent."""
+
+    def total(self) -> int:
+        """Sum of the lines."""
+        return 0
+
+    def _round(self):
+        pass
+
+
+def _helper():
+    pass
+
+
+async def send(invoice: Invoice, retries: int = MAX_RETRIES) -> None:
+    pass
+
+
+if __name__ == "__main__":
+    send(None)
+"#;
+        let outline = outline("billing/cli.py", SourceLanguage::Python, source).unwrap();
+        assert_eq!(
+            kinds_and_names(&outline),
+            [
+                ("const", "MAX_RETRIES", None),
+                ("class", "Invoice", None),
+                ("def", "total", Some("Invoice")),
+                ("def", "send", None),
+            ]
+        );
+        let class = &outline.symbols[1];
+        assert_eq!(class.doc.as_deref(), Some("An issued invoice."));
+        assert_eq!(class.line, 13);
+        assert_eq!(outline.symbols[2].doc.as_deref(), Some("Sum of the lines."));
+        assert!(
+            outline.symbols[3]
+                .signature
+                .contains("retries: int = MAX_RETRIES"),
+            "{}",
+            outline.symbols[3].signature
+        );
+
+        let imports: Vec<(&str, bool)> = outline
+            .imports
+            .iter()
+            .map(|import| (import.path.as_str(), import.internal))
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                (".models", true),
+                ("numpy", false),
+                ("os", false),
+                ("requests", false)
+            ]
+        );
+        assert_eq!(
+            outline.entry_point.as_deref(),
+            Some("script (if __name__ == \"__main__\")")
+        );
+
+        let package = super::outline("billing/__init__.py", SourceLanguage::Python, "").unwrap();
+        assert_eq!(package.entry_point.as_deref(), Some("package root"));
+    }
+
+    #[test]
+    fn typescript_exports_are_public() {
+        let source = r#"import { readFile } from "node:fs/promises";
+import type { Config } from "./config";
+import { z } from "@scope/schema/v2";
+export * from "./util";
+
+/** Parses a manifest. Throws on bad input.
+ * @param text the raw manifest
+ */
+export function parse(text: string): Manifest {
+  return JSON.parse(text);
+}
+
+function internal() {}
+
+export interface Manifest {
+  name: string;
+}
+
+export type Id = string;
+export enum Mode { Fast, Safe }
+export const DEFAULT_MODE = Mode.Safe, RETRIES = 3;
+export let counter = 0;
+export abstract class Loader {}
+export default Loader;
+"#;
+        let outline = outline("src/index.ts", SourceLanguage::TypeScript, source).unwrap();
+        assert_eq!(
+            kinds_and_names(&outline),
+            [
+                ("function", "parse", None),
+                ("interface", "Manifest", None),
+                ("type", "Id", None),
+                ("enum", "Mode", None),
+                ("const", "DEFAULT_MODE", None),
+                ("const", "RETRIES", None),
+                ("const", "counter", None),
+                ("class", "Loader", None),
+                ("default", "default", None),
+            ]
+        );
+        let parse = &outline.symbols[0];
+        assert_eq!(parse.doc.as_deref(), Some("Parses a manifest."));
+        assert_eq!(parse.line, 9);
+        assert!(
+            parse
+                .signature
+                .starts_with("export function parse(text: string): Manifest"),
+            "{}",
+            parse.signature
+        );
+        assert!(
+            outline.symbols[6]
+                .signature
+                .starts_with("export let counter")
+        );
+
+        let imports: Vec<(&str, bool)> = outline
+            .imports
+            .iter()
+            .map(|import| (import.path.as_str(), import.internal))
+            .collect();
+        assert_eq!(
+            imports,
+            [
+                ("./config", true),
+                ("./util", true),
+                ("@scope/schema", false),
+                ("node:fs", false)
+            ]
+        );
+        assert_eq!(outline.entry_point.as_deref(), Some("module root"));
+    }
+}
@@ -0,0 +1,457 @@
+use anyhow::Context;
+use clap::Args;
+use include_dir::{Dir, DirEntry};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use crate::TEMPLATES_DIR;
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock::PackRecord;
+use crate::manifest::{MANIFEST_FILE, Manifest};
+use crate::pack::PackSource;
+use crate::plan::slash_path;
+use crate::render::TEMPLATE_SUFFIX;
+use crate::scaffold::TemplateFile;
+use crate::signing;
+
+/// Directory of provider-independent documents, such as a PRD, one
+/// subdirectory per document. It has no manifest, so it is not a provider.
+pub const GENERIC_DIR: &str = "generic";
+
+/// Directory of project briefing templates for `init-context`, named after
+/// the file they render, such as `CLAUDE.md.jinja`.
+pub const CONTEXT_DIR: &str = "context";
+
+#[derive(Args, Debug, Default)]
+pub struct TemplateArgs {
+    /// Template pack to use instead of the embedded templates: a git
+    /// repository such as `git+https://github.com/org/agents.git#v1.2.0`, or
+    /// a `.tar.gz`/`.zip` archive.
+    #[arg(long, value_name = "PACK")]
+    from: Option<String>,
+    /// Directory of provider templates layered over the embedded ones; its
+    /// files replace embedded files with the same path.
+    #[arg(long, value_name = "DIR")]
+    templates_dir: Option<PathBuf>,
+    /// Use only --templates-dir, ignoring the templates built into this binary.
+    #[arg(long, requires = "templates_dir")]
+    no_embedded_templates: bool,
+}
+
+/// An installable provider and the one-line description shown to users.
+pub struct ProviderInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// The provider templates a command works from: the embedded set or a
+/// template pack, with any local template directory layered on top, keyed by
+/// `/`-separated path
+/// (`claude/provider.toml`, `claude/agents/x.md`, …).
+pub struct Templates {
+    files: BTreeMap<String, Vec<u8>>,
+    /// The pack the base templates were read from, if not the embedded set.
+    pack: Option<PackRecord>,
+}
+
+impl Templates {
+    /// Assembles the templates selected by `args`, falling back to
+    /// `templates_dir` and `embedded_templates` in ai-dlc.toml. A directory
+    /// from the config is relative to `root`. The base is the pack given by
+    /// `--from`, else `recorded`, else the embedded templates.
+    pub fn load(
+        root: &Path,
+        config: &Config,
+        args: &TemplateArgs,
+        recorded: Option<PackSource>,
+    ) -> anyhow::Result<Self> {
+        let (dir, embedded) = match &args.templates_dir {
+            Some(dir) => (Some(dir.clone()), !args.no_embedded_templates),
+            None => (
+                config.templates_dir.as_ref().map(|dir| root.join(dir)),
+                config.embedded_templates,
+            ),
+        };
+        let source = match &args.from {
+            Some(raw) => Some(PackSource::parse(raw)?),
+            None => recorded,
+        };
+
+        let mut templates = match source {
+            Some(source) => {
+                let mut pack = source.fetch()?;
+                signing::verify(
+                    &source.to_string(),
+                    &mut pack.files,
+                    &config.trusted_keys,
+                    source.requires_manifest(),
+                )?;
+                Self {
+                    files: pack.files,
+                    pack: Some(PackRecord {
+                        from: source.to_string(),
+                        revision: pack.revision,
+                    }),
+                }
+            }
+            None if embedded || dir.is_none() => Self::embedded(),
+            None => Self {
+                files: BTreeMap::new(),
+                pack: None,
+            },
+        };
+        if let Some(dir) = dir {
+            templates.overlay(&dir)?;
+        }
+        Ok(templates)
+    }
+
+    /// The templates compiled into this binary.
+    pub fn embedded() -> Self {
+        fn visit(dir: &Dir, files: &mut BTreeMap<String, Vec<u8>>) {
+            for entry in dir.entries() {
+                match entry {
+                    DirEntry::Dir(d) => visit(d, files),
+                    DirEntry::File(f) => {
+                        files.insert(slash_path(f.path()), f.contents().to_vec());
+                    }
+                }
+            }
+        }
+        let mut files = BTreeMap::new();
+        visit(&TEMPLATES_DIR, &mut files);
+        Self { files, pack: None }
+    }
+
+    /// Layers the provider directories found in `dir` over the current set.
+    /// Files replace those with the same path; everything else is kept.
+    pub fn overlay(&mut self, dir: &Path) -> anyhow::Result<()> {
+        if !dir.is_dir() {
+            anyhow::bail!("Template directory {:?} does not exist.", dir);
+        }
+        let mut overridden = 0;
+        for path in fsutil::walk_files(dir, "")? {
+            if path.split('/').any(|part| part == ".git") {
+                continue;
+            }
+            let source = dir.join(&path);
+            let contents = std::fs::read(&source)
+                .with_context(|| format!("Failed to read template: {:?}", source))?;
+            if self.files.insert(path, contents).is_some() {
+                overridden += 1;
+            }
+        }
+        tracing::info!(
+            "Layered templates from {:?} ({} embedded file(s) overridden)",
+            dir,
+            overridden
+        );
+        Ok(())
+    }
+
+    pub fn pack(&self) -> Option<&PackRecord> {
+        self.pack.as_ref()
+    }
+
+    /// Names of the top-level provider directories.
+    fn provider_names(&self) -> BTreeSet<&str> {
+        self.files
+            .keys()
+            .filter_map(|path| path.split_once('/').map(|(provider, _)| provider))
+            .collect()
+    }
+
+    /// Lists the providers that carry a manifest.
+    pub fn available_providers(&self) -> anyhow::Result<Vec<ProviderInfo>> {
+        let mut providers = Vec::new();
+        for name in self.provider_names() {
+            if let Some(manifest) = self.manifest(name)? {
+                let description = if manifest.description.is_empty() {
+                    manifest.display_name().to_string()
+                } else {
+                    format!("{} - {}", manifest.display_name(), manifest.description)
+                };
+                providers.push(ProviderInfo {
+                    name: manifest.name,
+                    description,
+                });
+            }
+        }
+        Ok(providers)
+    }
+
+    /// Reads the `provider.toml` of a provider, if it has one.
+    pub fn manifest(&self, provider_name: &str) -> anyhow::Result<Option<Manifest>> {
+        let path = format!("{provider_name}/{MANIFEST_FILE}");
+        let Some(contents) = self.files.get(&path) else {
+            return Ok(None);
+        };
+        let contents =
+            std::str::from_utf8(contents).with_context(|| format!("{path} is not valid UTF-8"))?;
+        Manifest::parse(provider_name, contents).map(Some)
+    }
+
+    /// Collects the files a provider installs, placed where its manifest maps
+    /// them. `None` means the provider does not exist or has no manifest.
+    pub fn provider_templates(
+        &self,
+        provider_name: &str,
+    ) -> anyhow::Result<Option<Vec<TemplateFile>>> {
+        if !self.provider_names().contains(provider_name) {
+            tracing::warn!("Provider '{}' not found in the templates.", provider_name);
+            return Ok(None);
+        }
+        let Some(manifest) = self.manifest(provider_name)? else {
+            tracing::warn!(
+                "Provider '{}' has no {}; skipping.",
+                provider_name,
+                MANIFEST_FILE
+            );
+            return Ok(None);
+        };
+        manifest.check_cli_version()?;
+
+        let prefix = format!("{provider_name}/");
+        let files = self
+            .files
+            .iter()
+            .filter_map(|(path, contents)| {
+                let relative = path.strip_prefix(&prefix)?;
+                Some((relative.to_string(), contents.clone()))
+            })
+            .collect();
+        Ok(Some(manifest.install_set(files)))
+    }
+
+    /// Names of the generic documents that can be scaffolded.
+    pub fn generic_documents(&self) -> BTreeSet<&str> {
+        let prefix = format!("{GENERIC_DIR}/");
+        self.files
+            .keys()
+            .filter_map(|path| path.strip_prefix(&prefix)?.split_once('/'))
+            .map(|(document, _)| document)
+            .collect()
+    }
+
+    /// The files of generic `document`, relative to the folder it is
+    /// scaffolded into. `None` means there is no such document.
+    pub fn generic_templates(&self, document: &str) -> Option<Vec<TemplateFile>> {
+        let prefix = format!("{GENERIC_DIR}/{document}/");
+        let files: Vec<TemplateFile> = self
+            .files
+            .iter()
+            .filter_map(|(path, contents)| {
+                let relative = path.strip_prefix(&prefix)?;
+                Some(TemplateFile {
+                    path: relative.to_string(),
+                    source: format!("{document}/{relative}"),
+                    group: document.to_string(),
+                    optional: false,
+                    merge: None,
+                    contents: contents.clone(),
+                })
+            })
+            .collect();
+        (!files.is_empty()).then_some(files)
+    }
+
+    /// The briefing template rendered to `file` by `init-context`, if the
+    /// templates carry one.
+    pub fn context_template(&self, file: &str) -> Option<TemplateFile> {
+        [TEMPLATE_SUFFIX, ""].into_iter().find_map(|suffix| {
+            let path = format!("{file}{suffix}");
+            let contents = self.files.get(&format!("{CONTEXT_DIR}/{path}"))?;
+            Some(TemplateFile {
+                source: path.clone(),
+                path,
+                group: CONTEXT_DIR.to_string(),
+                optional: false,
+                merge: None,
+                contents: contents.clone(),
+            })
+        })
+    }
+
+    /// Hashes every file, including its path, so renames and content edits
+    /// both change the result.
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        for (path, contents) in &self.files {
+            hasher.update(path.as_bytes());
+            hasher.update([0]);
+            hasher.update((contents.len() as u64).to_le_bytes());
+            hasher.update(contents);
+        }
+        format!("sha256:{:x}", hasher.finalize())
+    }
+}
+
+/// Loads templates for providers recorded in the lockfile, each from the pack
+/// it was scaffolded from unless `--from` overrides it. Providers taken from
+/// the same pack share one fetch.
+pub struct RecordedTemplates<'a> {
+    root: &'a Path,
+    config: &'a Config,
+    args: &'a TemplateArgs,
+    loaded: BTreeMap<String, Templates>,
+}
+
+impl<'a> RecordedTemplates<'a> {
+    pub fn new(root: &'a Path, config: &'a Config, args: &'a TemplateArgs) -> Self {
+        Self {
+            root,
+            config,
+            args,
+            loaded: BTreeMap::new(),
+        }
+    }
+
+    /// The templates for a provider recorded with `pack`. With `pinned`, a
+    /// pack is read at the recorded revision rather than its branch or tag.
+    pub fn get(&mut self, pack: Option<&PackRecord>, pinned: bool) -> anyhow::Result<&Templates> {
+        let recorded = match pack {
+            Some(_) if self.args.from.is_some() => None,
+            Some(pack) => {
+                let source = PackSource::parse(&pack.from)?;
+                Some(if pinned {
+                    source.pinned(&pack.revision)
+                } else {
+                    source
+                })
+            }
+            None => None,
+        };
+        let key = recorded
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        if !self.loaded.contains_key(&key) {
+            let templates = Templates::load(self.root, self.config, self.args, recorded)?;
+            self.loaded.insert(key.clone(), templates);
+        }
+        Ok(&self.loaded[&key])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A template directory replacing Claude's example agent, adding an
+    /// agent and adding a provider of its own.
+    fn overlay_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, contents) in [
+            ("claude/agents/example.md", "# Our example\n"),
+            ("claude/agents/extra.md", "# Extra\n"),
+            (
+                "acme/provider.toml",
+                "name = \"acme\"\n[[mappings]]\nsource = \"notes.md\"\ndest = \"NOTES.md\"\n",
+            ),
+            ("acme/notes.md", "# Notes\n"),
+            ("acme/.git/HEAD", "ref: refs/heads/main\n"),
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        dir
+    }
+
+    fn installed(templates: &Templates, provider: &str) -> BTreeMap<String, String> {
+        templates
+            .provider_templates(provider)
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|file| (file.path, String::from_utf8(file.contents).unwrap()))
+            .collect()
+    }
+
+    fn providers(templates: &Templates) -> Vec<String> {
+        templates
+            .available_providers()
+            .unwrap()
+            .into_iter()
+            .map(|provider| provider.name)
+            .collect()
+    }
+
+    #[test]
+    fn overlay_files_replace_embedded_ones() {
+        let dir = overlay_dir();
+        let args = TemplateArgs {
+            templates_dir: Some(dir.path().to_path_buf()),
+            ..TemplateArgs::default()
+        };
+        let templates = Templates::load(dir.path(), &Config::default(), &args, None).unwrap();
+
+        let claude = installed(&templates, "claude");
+        assert_eq!(claude[".claude/agents/example.md"], "# Our example\n");
+        assert_eq!(claude[".claude/agents/extra.md"], "# Extra\n");
+        // Files the overlay does not mention come from the embedded set.
+        assert!(claude.contains_key("CLAUDE.md.jinja"));
+        assert!(claude.contains_key(".claude/settings.json"));
+
+        assert_eq!(installed(&templates, "acme")["NOTES.md"], "# Notes\n");
+        assert!(!templates.files.keys().any(|path| path.contains(".git/")));
+        let names = providers(&templates);
+        assert!(names.contains(&"acme".to_string()) && names.contains(&"cursor".to_string()));
+        assert_ne!(templates.hash(), Templates::embedded().hash());
+    }
+
+    #[test]
+    fn no_embedded_templates_uses_the_overlay_alone() {
+        let dir = overlay_dir();
+        let args = TemplateArgs {
+            templates_dir: Some(dir.path().to_path_buf()),
+            no_embedded_templates: true,
+            ..TemplateArgs::default()
+        };
+        let templates = Templates::load(dir.path(), &Config::default(), &args, None).unwrap();
+        assert_eq!(providers(&templates), ["acme"]);
+        // Claude has files but no manifest without the embedded set.
+        assert!(templates.provider_templates("claude").unwrap().is_none());
+        assert!(templates.generic_documents().is_empty());
+    }
+
+    #[test]
+    fn config_names_the_overlay_relative_to_the_root() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = overlay_dir();
+        let target = root.path().join("agents");
+        std::fs::rename(dir.path(), &target).unwrap();
+
+        let config = Config {
+            templates_dir: Some(PathBuf::from("agents")),
+            embedded_templates: false,
+            ..Config::default()
+        };
+        let templates =
+            Templates::load(root.path(), &config, &TemplateArgs::default(), None).unwrap();
+        assert_eq!(providers(&templates), ["acme"]);
+
+        // The command line wins over the config.
+        let args = TemplateArgs {
+            templates_dir: Some(target.clone()),
+            ..TemplateArgs::default()
+        };
+        let templates = Templates::load(root.path(), &config, &args, None).unwrap();
+        assert!(providers(&templates).contains(&"claude".to_string()));
+    }
+
+    #[test]
+    fn missing_overlay_is_an_error() {
+        let root = tempfile::tempdir().unwrap();
+        let args = TemplateArgs {
+            templates_dir: Some(root.path().join("nowhere")),
+            ..TemplateArgs::default()
+        };
+        let err = Templates::load(root.path(), &Config::default(), &args, None)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("does not exist"), "{err}");
+    }
+}
@@ -0,0 +1,329 @@
+use anyhow::Context;
+use clap::Parser;
+use diffy::{ConflictStyle, MergeOptions};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::config::Config;
+use crate::fsutil;
+use crate::lock::{self, LOCK_PATH, Lockfile, ProviderLock};
+use crate::merge;
+use crate::render::{Renderer, VarArgs};
+use crate::scaffold::{self, TemplateFile};
+use crate::templates::{RecordedTemplates, TemplateArgs};
+use crate::workspace::RootArgs;
+
+#[derive(Parser, Debug)]
+pub struct UpgradeArgs {
+    /// Providers to upgrade; defaults to every provider in the lockfile.
+    #[arg(long, short)]
+    provider: Vec<String>,
+    /// Report what the upgrade would do without touching disk.
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    templates: TemplateArgs,
+    #[command(flatten)]
+    vars: VarArgs,
+    #[command(flatten)]
+    root: RootArgs,
+}
+
+/// What upgrading a single managed file does.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    /// The local file already matches the new template.
+    Unchanged,
+    /// A template file that was not installed before.
+    Create,
+    /// The file had no local edits and was replaced by the new template.
+    Update,
+    /// The file has local edits and nothing upstream changed since.
+    Modified,
+    /// Local edits and template changes were combined without conflicts.
+    Merge,
+    /// Local edits and template changes overlap; markers were written.
+    Conflict,
+    /// The file differs from the template but ai-dlc has no record of what it
+    /// wrote there, such as a hand-written file or one scaffold skipped, so
+    /// there is no base to merge against and it is left untouched.
+    Unmanaged,
+    /// The managed file was deleted locally and is left deleted.
+    Missing,
+    /// The template no longer ships the file and it had no local edits.
+    Remove,
+    /// The template no longer ships the file but it has local edits, so it is
+    /// kept and no longer managed.
+    Unmanage,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Unchanged => "unchanged",
+            Outcome::Create => "create",
+            Outcome::Update => "update",
+            Outcome::Modified => "modified",
+            Outcome::Merge => "merge",
+            Outcome::Conflict => "conflict",
+            Outcome::Unmanaged => "unmanaged",
+            Outcome::Missing => "missing",
+            Outcome::Remove => "remove",
+            Outcome::Unmanage => "unmanage",
+        }
+    }
+}
+
+pub fn handle_upgrade(args: UpgradeArgs) -> anyhow::Result<()> {
+    let root = &args.root.resolve()?;
+    let mut lockfile = Lockfile::load(root)?;
+    if lockfile.providers.is_empty() {
+        anyhow::bail!(
+            "No scaffold record found at {}; run `ai-dlc scaffold` first.",
+            LOCK_PATH
+        );
+    }
+
+    let providers: Vec<String> = if args.provider.is_empty() {
+        lockfile.providers.keys().cloned().collect()
+    } else {
+        for provider in &args.provider {
+            if !lockfile.providers.contains_key(provider) {
+                anyhow::bail!("Provider '{}' is not recorded in {}.", provider, LOCK_PATH);
+            }
+        }
+        args.provider
+    };
+
+    let config = Config::load(root)?;
+    let mut loader = RecordedTemplates::new(root, &config, &args.templates);
+    let mut report = Vec::new();
+
+    for provider in providers {
+        let entry = lockfile
+            .providers
+            .get_mut(&provider)
+            .expect("provider presence checked above");
+        let templates = loader.get(entry.pack.as_ref(), false)?;
+        let Some(files) = templates.provider_templates(&provider)? else {
+            continue;
+        };
+        let files = scaffold::select_groups(files, &entry.groups);
+        let mut renderer = Renderer::new(root, &config, &entry.variables, &args.vars);
+        renderer.require(&files)?;
+        let variables = renderer.values_of(&renderer.referenced(&files)?);
+        let files = renderer.render_all(files)?;
+        upgrade_provider(root, entry, &files, args.dry_run, &mut report)?;
+        if !args.dry_run {
+            entry.cli_version = env!("CARGO_PKG_VERSION").to_string();
+            entry.templates_hash = templates.hash();
+            entry.pack = templates.pack().cloned();
+            entry.variables.extend(variables);
+        }
+    }
+
+    for (path, outcome, diff) in &report {
+        println!("{:<10} {}", outcome.label(), path);
+        if let Some(diff) = diff {
+            print!("{diff}");
+        }
+    }
+    let count = |wanted: Outcome| report.iter().filter(|(_, o, _)| *o == wanted).count();
+    println!(
+        "\n{} files: {} created, {} updated, {} merged, {} in conflict, {} unmanaged, \
+         {} removed, {} locally modified, {} unchanged",
+        report.len(),
+        count(Outcome::Create),
+        count(Outcome::Update),
+        count(Outcome::Merge),
+        count(Outcome::Conflict),
+        count(Outcome::Unmanaged),
+        count(Outcome::Remove),
+        count(Outcome::Modified),
+        count(Outcome::Unchanged)
+    );
+
+    if args.dry_run {
+        return Ok(());
+    }
+
+    lockfile.save(root)?;
+    let unmanaged = count(Outcome::Unmanaged);
+    if unmanaged > 0 {
+        tracing::warn!(
+            "{} file(s) differ from the templates but were not written by ai-dlc, so they \
+             were left alone; compare them by hand or rerun `scaffold --on-conflict \
+             overwrite` or `backup` to take the templates.",
+            unmanaged
+        );
+    }
+    let conflicts = count(Outcome::Conflict);
+    if conflicts > 0 {
+        tracing::warn!(
+            "{} file(s) contain conflict markers between local edits and the new templates; \
+             resolve them by hand.",
+            conflicts
+        );
+    }
+    Ok(())
+}
+
+fn upgrade_provider(
+    root: &Path,
+    entry: &mut ProviderLock,
+    templates: &[TemplateFile],
+    dry_run: bool,
+    report: &mut Vec<(String, Outcome, Option<String>)>,
+) -> anyhow::Result<()> {
+    for file in templates {
+        let dest = root.join(&file.path);
+        let local = fsutil::read_optional(&dest)?;
+        let locked_hash = entry
+            .files
+            .get(&file.path)
+            .map(|locked| locked.hash.clone());
+
+        let mut diff = None;
+        // A settings merge is locked as written, so the user's own keys do
+        // not read as a local edit; text merges keep the template's hash.
+        let mut merged_settings = None;
+        let adopted = locked_hash.is_none();
+        let unedited = match (&local, &locked_hash) {
+            (Some(local), Some(hash)) => lock::hash_bytes(local) == *hash,
+            _ => false,
+        };
+        let (outcome, write) = match (local, locked_hash) {
+            (None, Some(_)) => (Outcome::Missing, None),
+            (None, None) => (Outcome::Create, Some(file.contents.clone())),
+            (Some(local), _) if local == file.contents => (Outcome::Unchanged, None),
+            // A merged settings file holds the user's keys as well, so it is
+            // merged again rather than replaced.
+            (Some(_), Some(_)) if unedited && file.merge.is_none() => {
+                (Outcome::Update, Some(file.contents.clone()))
+            }
+            (Some(local), locked_hash) => {
+                let base = match locked_hash {
+                    Some(_) => lock::load_base(root, &file.path)?,
+                    None => None,
+                };
+                let settings = file.merge.and_then(|strategy| {
+                    merge::merge_settings(
+                        &file.path,
+                        &local,
+                        &file.contents,
+                        base.as_deref(),
+                        strategy,
+                    )
+                    .inspect_err(|err| {
+                        tracing::warn!("Cannot merge into '{}': {:#}", file.path, err)
+                    })
+                    .ok()
+                });
+                if let Some(merged) = settings {
+                    if merged == local && unedited {
+                        merged_settings = Some(merged);
+                        (Outcome::Unchanged, None)
+                    } else if merged == local {
+                        (Outcome::Modified, None)
+                    } else {
+                        diff = Some(merge::diff(&file.path, &local, &merged));
+                        merged_settings = Some(merged.clone());
+                        (Outcome::Merge, Some(merged))
+                    }
+                } else {
+                    // Without a recorded base there is no telling which side
+                    // changed what, so the file is not merged.
+                    match base {
+                        Some(base) => match three_way_merge(&base, &local, &file.contents) {
+                            Ok(merged) if merged == local => (Outcome::Modified, None),
+                            Ok(merged) => {
+                                diff = Some(merge::diff(&file.path, &local, &merged));
+                                (Outcome::Merge, Some(merged))
+                            }
+                            Err(conflicted) => {
+                                diff = Some(merge::diff(&file.path, &local, &conflicted));
+                                (Outcome::Conflict, Some(conflicted))
+                            }
+                        },
+                        None => (Outcome::Unmanaged, None),
+                    }
+                }
+            }
+        };
+
+        if !dry_run {
+            if let Some(contents) = write {
+                if let Some(parent) = dest.parent() {
+                    std::fs::create_dir_all(parent)
+                        .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+                }
+                std::fs::write(&dest, contents)
+                    .with_context(|| format!("Failed to write file: {:?}", dest))?;
+            }
+            if !matches!(outcome, Outcome::Missing | Outcome::Unmanaged) {
+                let contents = merged_settings.as_ref().unwrap_or(&file.contents);
+                entry.lock_file(root, &file.path, contents, &file.contents)?;
+                if adopted && matches!(outcome, Outcome::Merge | Outcome::Unchanged) {
+                    entry.adopt(&file.path);
+                }
+            }
+        }
+        report.push((file.path.clone(), outcome, diff));
+    }
+
+    let shipped: BTreeSet<&str> = templates.iter().map(|f| f.path.as_str()).collect();
+    let dropped: Vec<(String, String)> = entry
+        .files
+        .iter()
+        .filter(|(path, _)| !shipped.contains(path.as_str()))
+        .map(|(path, locked)| (path.clone(), locked.hash.clone()))
+        .collect();
+    for (path, hash) in dropped {
+        let dest = root.join(&path);
+        let outcome = match fsutil::read_optional(&dest)? {
+            Some(local) if lock::hash_bytes(&local) != hash => Outcome::Unmanage,
+            Some(_) => {
+                if !dry_run {
+                    fsutil::remove_file_and_prune(root, &path)?;
+                }
+                Outcome::Remove
+            }
+            None => Outcome::Remove,
+        };
+        if !dry_run {
+            entry.unlock_file(root, &path)?;
+        }
+        report.push((path, outcome, None));
+    }
+    Ok(())
+}
+
+/// Merges local edits and template changes against the version that was
+/// originally scaffolded. Conflicts are returned as `Err` holding the file
+/// with git-style markers.
+fn three_way_merge(base: &[u8], local: &[u8], template: &[u8]) -> Result<Vec<u8>, Vec<u8>> {
+    let mut options = MergeOptions::new();
+    options.set_conflict_style(ConflictStyle::Merge);
+    options
+        .merge_bytes(base, local, template)
+        .map_err(relabel_conflicts)
+}
+
+/// Names the conflict sides after what they are instead of diffy's
+/// `ours`/`theirs`.
+fn relabel_conflicts(merged: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(merged.len());
+    for line in merged.split_inclusive(|b| *b == b'\n') {
+        let (text, newline) = match line.strip_suffix(b"\n") {
+            Some(text) => (text, &b"\n"[..]),
+            None => (line, &b""[..]),
+        };
+        match text {
+            b"<<<<<<< ours" => out.extend_from_slice(b"<<<<<<< local"),
+            b">>>>>>> theirs" => out.extend_from_slice(b">>>>>>> template"),
+            _ => out.extend_from_slice(text),
+        }
+        out.extend_from_slice(newline);
+    }
+    out
+}
@@ -0,0 +1,95 @@
+use dialoguer::{Confirm, MultiSelect};
+use std::collections::BTreeMap;
+
+use crate::plan::{OutputFormat, ScaffoldPlan};
+use crate::templates::Templates;
+
+/// Providers and asset groups picked in the wizard. An empty group list
+/// means every group that is not optional.
+pub struct Selection {
+    pub providers: Vec<String>,
+    pub groups: Vec<String>,
+}
+
+/// Asks which providers and asset groups to scaffold. Returns `None` when
+/// nothing was selected.
+pub fn select(templates: &Templates) -> anyhow::Result<Option<Selection>> {
+    let available = templates.available_providers()?;
+    if available.is_empty() {
+        anyhow::bail!("No installable providers were found in the templates.");
+    }
+
+    let items: Vec<String> = available
+        .iter()
+        .map(|info| format!("{:<10} {}", info.name, info.description))
+        .collect();
+    let picked = MultiSelect::new()
+        .with_prompt("Providers to scaffold (space to toggle, enter to confirm)")
+        .items(&items)
+        .interact()?;
+    if picked.is_empty() {
+        return Ok(None);
+    }
+    let providers: Vec<String> = picked
+        .into_iter()
+        .map(|index| available[index].name.clone())
+        .collect();
+
+    // Group name -> (description, optional), merged across the picked
+    // providers.
+    let mut groups: BTreeMap<String, (String, bool)> = BTreeMap::new();
+    for provider in &providers {
+        let manifest = templates.manifest(provider)?;
+        for file in templates.provider_templates(provider)?.unwrap_or_default() {
+            let description = manifest
+                .as_ref()
+                .and_then(|manifest| manifest.groups.get(&file.group))
+                .map(|info| info.description.clone())
+                .unwrap_or_default();
+            let entry = groups.entry(file.group).or_insert((description, true));
+            entry.1 &= file.optional;
+        }
+    }
+    if groups.len() <= 1 && groups.values().all(|(_, optional)| !optional) {
+        return Ok(Some(Selection {
+            providers,
+            groups: Vec::new(),
+        }));
+    }
+
+    let names: Vec<&String> = groups.keys().collect();
+    let items: Vec<String> = groups
+        .iter()
+        .map(|(name, (description, _))| format!("{:<10} {}", name, description))
+        .collect();
+    let defaults: Vec<bool> = groups.values().map(|(_, optional)| !optional).collect();
+    let picked = MultiSelect::new()
+        .with_prompt("Asset groups to include")
+        .items(&items)
+        .defaults(&defaults)
+        .interact()?;
+    if picked.is_empty() {
+        return Ok(None);
+    }
+    // Keeping the default selection is recorded as "defaults" so groups
+    // added by later releases are picked up by upgrades.
+    let chosen: Vec<bool> = (0..names.len()).map(|i| picked.contains(&i)).collect();
+    let groups = if chosen == defaults {
+        Vec::new()
+    } else {
+        picked
+            .into_iter()
+            .map(|index| names[index].clone())
+            .collect()
+    };
+    Ok(Some(Selection { providers, groups }))
+}
+
+/// Shows the plan and asks for confirmation before anything is written.
+pub fn confirm(plan: &ScaffoldPlan) -> anyhow::Result<bool> {
+    plan.print(OutputFormat::Text)?;
+    Ok(Confirm::new()
+        .with_prompt("Write these files?")
+        .default(true)
+        .interact()?)
+}
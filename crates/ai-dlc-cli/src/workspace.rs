@@ -0,0 +1,74 @@
+use anyhow::Context;
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+/// Project marker and configuration file recognised at a repository root.
+pub const CONFIG_FILE: &str = "ai-dlc.toml";
+
+#[derive(Args, Debug)]
+pub struct RootArgs {
+    /// Directory to operate on. Defaults to the enclosing git repository root,
+    /// or the nearest directory containing ai-dlc.toml.
+    #[arg(long, value_name = "DIR")]
+    dest: Option<PathBuf>,
+}
+
+impl RootArgs {
+    /// Resolves the root that scaffolded paths are relative to, warning when
+    /// it does not look like the top of a repository.
+    pub fn resolve(&self) -> anyhow::Result<PathBuf> {
+        if let Some(dest) = &self.dest {
+            if !is_project_root(dest) {
+                tracing::warn!(
+                    "{:?} is not a repository root (no .git or {}); using it anyway.",
+                    dest,
+                    CONFIG_FILE
+                );
+            }
+            return Ok(dest.clone());
+        }
+        discover_root()
+    }
+
+    /// The directory given with `--dest`, if any.
+    pub fn dest(&self) -> Option<&Path> {
+        self.dest.as_deref()
+    }
+}
+
+/// The project root enclosing the current directory, or the current
+/// directory itself when there is none.
+pub fn discover_root() -> anyhow::Result<PathBuf> {
+    let cwd = std::env::current_dir().context("Failed to read the current directory")?;
+    match find_project_root(&cwd) {
+        Some(root) => {
+            if root != cwd {
+                tracing::info!("Using project root {:?}", root);
+            }
+            Ok(root)
+        }
+        None => {
+            tracing::warn!(
+                "No git repository or {} found above {:?}; using the current directory, \
+                 which is not a repository root.",
+                CONFIG_FILE,
+                cwd
+            );
+            Ok(cwd)
+        }
+    }
+}
+
+/// Walks up from `start` to the nearest directory that is a git repository
+/// root or carries an `ai-dlc.toml` marker.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| is_project_root(dir))
+        .map(Path::to_path_buf)
+}
+
+fn is_project_root(dir: &Path) -> bool {
+    // `.git` is a file rather than a directory inside worktrees and submodules.
+    dir.join(".git").exists() || dir.join(CONFIG_FILE).is_file()
+}
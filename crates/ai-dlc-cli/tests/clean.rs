@@ -0,0 +1,132 @@
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+const SETTINGS: &str = "{\"model\": \"opus\"}\n";
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    dir
+}
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+/// Runs `args`, expecting success, and returns stdout.
+fn run(dest: &Path, args: &[&str]) -> String {
+    let output = ai_dlc(dest, args);
+    assert!(
+        output.status.success(),
+        "ai-dlc-cli {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn merged_settings_are_unmodified_and_left_in_place() {
+    let dir = repo();
+    let root = dir.path();
+    std::fs::create_dir(root.join(".claude")).unwrap();
+    std::fs::write(root.join(".claude/settings.json"), SETTINGS).unwrap();
+
+    run(root, &["scaffold", "-p", "claude"]);
+    let merged = std::fs::read_to_string(root.join(".claude/settings.json")).unwrap();
+    assert!(
+        merged.contains("\"opus\"") && merged.contains("deny"),
+        "{merged}"
+    );
+
+    let output = ai_dlc(root, &["status", "--check"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{stdout}");
+    assert!(stdout.contains("0 modified"), "{stdout}");
+
+    let stdout = run(root, &["clean", "-p", "claude", "--force", "--dry-run"]);
+    assert!(
+        stdout.contains("unmanage   .claude/settings.json"),
+        "{stdout}"
+    );
+    assert!(
+        !stdout.contains("remove     .claude/settings.json"),
+        "{stdout}"
+    );
+
+    run(root, &["clean", "-p", "claude", "--force"]);
+    assert_eq!(
+        std::fs::read_to_string(root.join(".claude/settings.json")).unwrap(),
+        merged
+    );
+    assert!(!root.join("CLAUDE.md").exists());
+    assert!(!root.join(".ai-dlc").exists());
+}
+
+#[test]
+fn modified_files_are_kept_unless_forced() {
+    let dir = repo();
+    let root = dir.path();
+    run(root, &["scaffold", "-p", "claude"]);
+    std::fs::write(root.join(".claude/agents/example.md"), "# Mine now\n").unwrap();
+
+    let stdout = run(root, &["clean", "-p", "claude"]);
+    assert!(
+        stdout.contains("keep       .claude/agents/example.md"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("remove     CLAUDE.md"), "{stdout}");
+    assert!(root.join(".claude/agents/example.md").exists());
+    assert!(!root.join("CLAUDE.md").exists());
+
+    let stdout = run(root, &["clean", "-p", "claude", "--force"]);
+    assert!(
+        stdout.contains("remove     .claude/agents/example.md"),
+        "{stdout}"
+    );
+    assert!(!root.join(".claude/agents/example.md").exists());
+}
+
+#[test]
+fn emptied_directories_are_pruned() {
+    let dir = repo();
+    let root = dir.path();
+    run(root, &["scaffold", "-p", "claude"]);
+    assert!(root.join(".claude/agents").is_dir());
+
+    run(root, &["clean", "-p", "claude"]);
+    assert!(!root.join(".claude").exists());
+    assert!(!root.join(".ai-dlc").exists());
+    assert!(root.join(".git").is_dir());
+}
+
+#[test]
+fn pre_existing_identical_files_are_left_alone() {
+    let scratch = repo();
+    run(scratch.path(), &["scaffold", "-p", "claude"]);
+    let example =
+        std::fs::read_to_string(scratch.path().join(".claude/agents/example.md")).unwrap();
+
+    let dir = repo();
+    let root = dir.path();
+    std::fs::create_dir_all(root.join(".claude/agents")).unwrap();
+    std::fs::write(root.join(".claude/agents/example.md"), &example).unwrap();
+
+    run(root, &["scaffold", "-p", "claude"]);
+    let stdout = run(root, &["clean", "-p", "claude", "--force"]);
+    assert!(
+        stdout.contains("unmanage   .claude/agents/example.md"),
+        "{stdout}"
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join(".claude/agents/example.md")).unwrap(),
+        example
+    );
+    assert!(!root.join("CLAUDE.md").exists());
+}
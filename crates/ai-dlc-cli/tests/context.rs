@@ -0,0 +1,195 @@
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+const BEGIN: &str = "<!-- ai-dlc:context:begin -->";
+const END: &str = "<!-- ai-dlc:context:end -->";
+
+const WORKFLOW: &str = "name: CI
+on:
+  push:
+  pull_request:
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - run: cargo test --workspace
+      - run: |
+          pnpm install
+          pnpm run lint
+";
+
+/// A Rust workspace with a pnpm front end, a Makefile and a GitHub Actions
+/// workflow.
+fn fixture() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let files = [
+        ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+        ("crates/core/src/lib.rs", "pub fn core() {}\n"),
+        ("crates/core/src/parse.rs", "pub fn parse() {}\n"),
+        (
+            "package.json",
+            r#"{"scripts": {"build": "vite build", "lint": "eslint .", "test": "echo \"Error: no test specified\" && exit 1"}}"#,
+        ),
+        ("pnpm-lock.yaml", "lockfileVersion: '9.0'\n"),
+        ("web/main.ts", "export const main = 1;\n"),
+        (
+            "Makefile",
+            ".PHONY: all\nall: build\nbuild:\n\tcargo build\nrelease:\n\tcargo build --release\n",
+        ),
+        ("docs/guide.md", "# Guide\n"),
+        (".github/workflows/ci.yml", WORKFLOW),
+        ("target/debug/ignored.rs", "fn ignored() {}\n"),
+    ];
+    std::fs::create_dir(root.join(".git")).unwrap();
+    for (path, contents) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli");
+    assert!(
+        output.status.success(),
+        "ai-dlc-cli {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn commands(facts: &Value) -> Vec<(String, String)> {
+    facts["commands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|command| {
+            (
+                command["kind"].as_str().unwrap().to_string(),
+                command["run"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn facts_cover_build_files_commands_ci_and_layout() {
+    let dir = fixture();
+    let output = ai_dlc(dir.path(), &["init-context", "--facts"]);
+    let facts: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(
+        facts["build_files"],
+        serde_json::json!(["Cargo.toml", "package.json", "Makefile"])
+    );
+    let commands = commands(&facts);
+    for (kind, run) in [
+        ("build", "cargo build --workspace"),
+        ("test", "cargo test --workspace"),
+        (
+            "lint",
+            "cargo clippy --workspace --all-targets -- -D warnings",
+        ),
+        ("build", "pnpm run build"),
+        ("lint", "pnpm run lint"),
+        ("build", "make all"),
+        ("build", "make build"),
+    ] {
+        assert!(
+            commands.contains(&(kind.to_string(), run.to_string())),
+            "{kind}: {run} missing from {commands:?}"
+        );
+    }
+    // Neither the placeholder npm test script nor an unknown make target.
+    assert!(!commands.iter().any(|(_, run)| run == "pnpm run test"));
+    assert!(!commands.iter().any(|(_, run)| run == "make release"));
+
+    let ci = &facts["ci"][0];
+    assert_eq!(ci["system"], "GitHub Actions");
+    assert_eq!(ci["path"], ".github/workflows/ci.yml");
+    assert_eq!(ci["name"], "CI");
+    assert_eq!(ci["triggers"], serde_json::json!(["push", "pull_request"]));
+    assert_eq!(
+        ci["commands"],
+        serde_json::json!(["cargo test --workspace", "pnpm install", "pnpm run lint"])
+    );
+
+    let layout: Vec<(&str, u64)> = facts["layout"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            (
+                entry["path"].as_str().unwrap(),
+                entry["files"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        layout,
+        [(".github/", 1), ("crates/", 2), ("docs/", 1), ("web/", 1)]
+    );
+    assert_eq!(facts["layout"][1]["description"], "Rust workspace members");
+    assert_eq!(facts["layout"][3]["description"], Value::Null);
+    assert_eq!(facts["languages"][0]["name"], "Rust");
+    assert_eq!(facts["languages"][0]["files"], 2);
+}
+
+#[test]
+fn rerun_keeps_text_outside_the_markers() {
+    let dir = fixture();
+    let root = dir.path();
+    ai_dlc(root, &["init-context", "-p", "claude"]);
+    let briefing = std::fs::read_to_string(root.join("CLAUDE.md")).unwrap();
+    assert!(
+        briefing.contains("`cargo test --workspace` (from `Cargo.toml`)"),
+        "{briefing}"
+    );
+    assert!(!briefing.contains("go.mod"), "{briefing}");
+
+    let begin = briefing.find(BEGIN).unwrap();
+    let end = briefing.find(END).unwrap() + END.len();
+    let edited = format!(
+        "# Our notes\n\nRead this first.\n\n{}\n\n## Team rules\n\nNo Friday deploys.\n",
+        &briefing[begin..end]
+    );
+    std::fs::write(root.join("CLAUDE.md"), &edited).unwrap();
+    std::fs::write(root.join("go.mod"), "module example.com/x\n").unwrap();
+
+    ai_dlc(root, &["init-context", "-p", "claude"]);
+    let rerun = std::fs::read_to_string(root.join("CLAUDE.md")).unwrap();
+    assert!(
+        rerun.starts_with("# Our notes\n\nRead this first.\n\n<!-- ai-dlc:context:begin -->"),
+        "{rerun}"
+    );
+    assert!(
+        rerun.ends_with("<!-- ai-dlc:context:end -->\n\n## Team rules\n\nNo Friday deploys.\n"),
+        "{rerun}"
+    );
+    assert!(rerun.contains("`go test ./...` (from `go.mod`)"), "{rerun}");
+}
+
+#[test]
+fn briefings_without_markers_are_skipped() {
+    let dir = fixture();
+    let mine = "# Hand-written\n";
+    std::fs::write(dir.path().join("CLAUDE.md"), mine).unwrap();
+    let output = ai_dlc(dir.path(), &["init-context", "-p", "claude"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Kept existing 'CLAUDE.md'"), "{stderr}");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("CLAUDE.md")).unwrap(),
+        mine
+    );
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    let files = [
+        (
+            "src/lib.rs",
+            "/// Adds.\npub fn add(a: i32, b: i32) -> i32 { a + b }\n",
+        ),
+        ("app/main.py", "def run():\n    \"\"\"Runs the app.\"\"\"\n"),
+        ("web/index.ts", "export const answer = 42;\n"),
+    ];
+    std::fs::create_dir(root.join(".git")).unwrap();
+    for (path, contents) in files {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+fn initref(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .arg("initref")
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn check_reports_stale_and_orphaned_dossiers() {
+    let dir = repo();
+    let root = dir.path();
+    let output = initref(root, &["--check"]);
+    assert!(!output.status.success());
+    assert!(
+        stdout(&output).contains("missing    ref/src.md"),
+        "{}",
+        stdout(&output)
+    );
+
+    let output = initref(root, &[]);
+    assert!(output.status.success());
+    assert!(root.join("ref/index.md").exists());
+    let dossier = std::fs::read_to_string(root.join("ref/src.md")).unwrap();
+    assert!(
+        dossier.starts_with("<!-- ai-dlc initref: dir=\"src\" source-hash="),
+        "{dossier}"
+    );
+    assert!(dossier.contains("add"), "{dossier}");
+    std::fs::write(root.join("ref/notes.md"), "# Hand-written\n").unwrap();
+
+    let output = initref(root, &["--check"]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(
+        stdout(&output).contains("3 modules: 0 missing, 0 stale, 0 orphaned, 3 up to date"),
+        "{}",
+        stdout(&output)
+    );
+
+    std::fs::write(root.join("src/lib.rs"), "pub fn sub() {}\n").unwrap();
+    std::fs::remove_dir_all(root.join("web")).unwrap();
+    let output = initref(root, &["--check"]);
+    assert!(!output.status.success());
+    let report = stdout(&output);
+    assert!(report.contains("stale      ref/src.md"), "{report}");
+    assert!(report.contains("orphaned   ref/web.md"), "{report}");
+    assert!(!report.contains("ref/app.md"), "{report}");
+    assert!(!report.contains("ref/notes.md"), "{report}");
+    // Checking writes nothing.
+    assert_eq!(
+        std::fs::read_to_string(root.join("ref/src.md")).unwrap(),
+        dossier
+    );
+    assert!(root.join("ref/web.md").exists());
+
+    assert!(initref(root, &[]).status.success());
+    assert!(initref(root, &["--check"]).status.success());
+    assert!(!root.join("ref/web.md").exists());
+    assert!(root.join("ref/notes.md").exists());
+    let dossier = std::fs::read_to_string(root.join("ref/src.md")).unwrap();
+    assert!(dossier.contains("sub"), "{dossier}");
+}
@@ -0,0 +1,153 @@
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    dir
+}
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn list_prints_groups_templates_and_destinations() {
+    let dir = repo();
+    let listing = stdout(&ai_dlc(dir.path(), &["list", "-p", "claude"]));
+    assert!(
+        listing.starts_with(
+            "claude     Claude Code - Sub-agents and project memory for Claude Code sessions\n"
+        ),
+        "{listing}"
+    );
+    for line in [
+        "  agents     Specialised sub-agents\n    claude/agents/example.md -> .claude/agents/example.md\n      Example sub-agent.",
+        "  core       Project memory (CLAUDE.md)\n    claude/CLAUDE.md.jinja -> CLAUDE.md\n",
+        "    claude/settings.json -> .claude/settings.json\n",
+    ] {
+        assert!(listing.contains(line), "{line:?} missing from\n{listing}");
+    }
+    assert!(
+        listing.ends_with("\n1 providers, 3 templates\n"),
+        "{listing}"
+    );
+
+    let all = stdout(&ai_dlc(dir.path(), &["list"]));
+    for provider in ["claude", "cursor", "gemini", "roo"] {
+        assert!(all.contains(&format!("\n{provider:<10} ")) || all.starts_with(provider));
+    }
+}
+
+#[test]
+fn list_json_includes_overlay_groups() {
+    let dir = repo();
+    let templates = tempfile::tempdir().unwrap();
+    let acme = templates.path().join("acme");
+    std::fs::create_dir_all(acme.join("extras")).unwrap();
+    std::fs::write(
+        acme.join("provider.toml"),
+        "name = \"acme\"\ndisplay_name = \"Acme\"\nmin_cli_version = \"0.1.0\"\n\
+         [groups.extras]\ndescription = \"Extra notes\"\noptional = true\n\
+         [[mappings]]\nsource = \"extras/\"\ndest = \".acme/\"\ngroup = \"extras\"\n",
+    )
+    .unwrap();
+    std::fs::write(acme.join("extras/notes.md"), "# Notes\n\nHouse rules.\n").unwrap();
+
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "list",
+            "-p",
+            "acme",
+            "--format",
+            "json",
+            "--templates-dir",
+            templates.path().to_str().unwrap(),
+        ],
+    );
+    let providers: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        providers,
+        serde_json::json!([{
+            "name": "acme",
+            "display_name": "Acme",
+            "description": "",
+            "min_cli_version": "0.1.0",
+            "groups": [{
+                "name": "extras",
+                "description": "Extra notes",
+                "optional": true,
+                "templates": [{
+                    "template": "acme/extras/notes.md",
+                    "dest": ".acme/notes.md",
+                    "description": "Notes",
+                }],
+            }],
+        }])
+    );
+}
+
+#[test]
+fn show_prints_templates_raw_or_rendered() {
+    let dir = repo();
+    let agent = stdout(&ai_dlc(dir.path(), &["show", "claude/agents/example.md"]));
+    assert!(agent.starts_with("---\nname: example\n"), "{agent}");
+    // The path scaffold writes to works as well.
+    assert_eq!(
+        stdout(&ai_dlc(
+            dir.path(),
+            &["show", "claude/.claude/agents/example.md"]
+        )),
+        agent
+    );
+
+    let raw = stdout(&ai_dlc(dir.path(), &["show", "claude/CLAUDE.md.jinja"]));
+    assert!(raw.starts_with("# {{ project_name }}\n"), "{raw}");
+    let rendered = stdout(&ai_dlc(
+        dir.path(),
+        &[
+            "show",
+            "claude/CLAUDE.md",
+            "--render",
+            "--var",
+            "project_name=shop",
+        ],
+    ));
+    assert!(rendered.starts_with("# shop\n"), "{rendered}");
+}
+
+#[test]
+fn show_explains_unknown_templates() {
+    let dir = repo();
+    let output = ai_dlc(dir.path(), &["show", "claude/agents/missing.md"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(
+            "Template 'claude/agents/missing.md' not found; run `ai-dlc-cli list --provider claude`"
+        ),
+        "{stderr}"
+    );
+
+    let output = ai_dlc(dir.path(), &["show", "example.md"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Expected <provider>/<path>"), "{stderr}");
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    dir
+}
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn agent_names_follow_the_lint_rule() {
+    let dir = repo();
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "agent",
+            "code_reviewer",
+            "--description",
+            "Reviews code.",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(dir.path().join(".claude/agents/code_reviewer.md").exists());
+    let output = ai_dlc(dir.path(), &["lint"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "agent",
+            "Code-Reviewer",
+            "--description",
+            "Reviews code.",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("Invalid agent name"));
+}
+
+#[test]
+fn unknown_tools_and_models_are_refused_before_writing() {
+    let dir = repo();
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "agent",
+            "helper",
+            "--description",
+            "Helps.",
+            "--tools",
+            "Read,Reed",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Unknown tool 'Reed'"),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "agent",
+            "helper",
+            "--description",
+            "Helps.",
+            "--model",
+            "gpt-4",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Unknown model 'gpt-4'"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!dir.path().join(".claude").exists());
+
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "agent",
+            "helper",
+            "--description",
+            "Helps.",
+            "--tools",
+            "Read, mcp__github__list_prs",
+            "--model",
+            "claude-sonnet-4-5",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+}
+
+#[test]
+fn cursor_commands_have_no_frontmatter() {
+    let dir = repo();
+    let output = ai_dlc(
+        dir.path(),
+        &[
+            "new",
+            "command",
+            "review:pr",
+            "--provider",
+            "cursor",
+            "--description",
+            "Review a pull request.",
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let command =
+        std::fs::read_to_string(dir.path().join(".cursor/commands/review/pr.md")).unwrap();
+    assert!(command.starts_with("# /review:pr\n"), "{command}");
+    assert!(command.contains("Review a pull request."), "{command}");
+    assert!(!command.contains("---"), "{command}");
+}
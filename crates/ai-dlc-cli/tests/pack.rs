@@ -0,0 +1,141 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("GIT_AUTHOR_NAME", "Pack Author")
+        .env("GIT_AUTHOR_EMAIL", "pack@example.com")
+        .env("GIT_COMMITTER_NAME", "Pack Author")
+        .env("GIT_COMMITTER_EMAIL", "pack@example.com")
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    std::fs::create_dir_all(to).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            std::fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// A bare repository holding the Claude templates, tagged `v1`, and the
+/// commit the tag points at.
+fn pack_repo(dir: &Path) -> (PathBuf, String) {
+    let work = dir.join("work");
+    copy_dir(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("embedded-templates/claude"),
+        &work.join("claude"),
+    );
+    git(&work, &["init", "--quiet"]);
+    git(&work, &["add", "."]);
+    git(&work, &["commit", "--quiet", "-m", "Claude templates"]);
+    git(&work, &["tag", "v1"]);
+    let commit = git(&work, &["rev-parse", "HEAD"]);
+
+    let bare = dir.join("pack.git");
+    git(
+        dir,
+        &["clone", "--quiet", "--bare", "work", bare.to_str().unwrap()],
+    );
+    (bare, commit)
+}
+
+fn scaffold(dest: &Path, cache: &Path, from: &str) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(["scaffold", "-p", "claude", "--from", from, "--dest"])
+        .arg(dest)
+        .env("AI_DLC_CACHE_DIR", cache)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli");
+    assert!(
+        output.status.success(),
+        "scaffold --from {} failed: {}",
+        from,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+fn repo(dir: &Path, name: &str) -> PathBuf {
+    let root = dir.join(name);
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    root
+}
+
+#[test]
+fn git_packs_are_cached_and_pinned_in_the_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let (bare, commit) = pack_repo(dir.path());
+    let cache = dir.path().join("cache");
+    let url = format!("file://{}", bare.display());
+    let from = format!("git+{url}#v1");
+
+    let first = repo(dir.path(), "first");
+    let output = scaffold(&first, &cache, &from);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Cloning template pack"), "{stderr}");
+    assert!(first.join(".claude/agents/example.md").exists());
+
+    let lock: Value =
+        serde_json::from_str(&std::fs::read_to_string(first.join(".ai-dlc/lock.json")).unwrap())
+            .unwrap();
+    let pack = &lock["providers"]["claude"]["pack"];
+    assert_eq!(pack["from"], format!("git+{url}#v1"), "{pack}");
+    assert_eq!(pack["revision"], commit, "{pack}");
+
+    let second = repo(dir.path(), "second");
+    let output = scaffold(&second, &cache, &from);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Cloning template pack"), "{stderr}");
+    assert!(stderr.contains(&commit), "{stderr}");
+
+    // A pinned commit is served from the cache once the remote is gone.
+    std::fs::remove_dir_all(&bare).unwrap();
+    let third = repo(dir.path(), "third");
+    scaffold(&third, &cache, &format!("git+{url}#{commit}"));
+    assert_eq!(
+        std::fs::read(third.join(".claude/agents/example.md")).unwrap(),
+        std::fs::read(first.join(".claude/agents/example.md")).unwrap()
+    );
+}
+
+#[test]
+fn unknown_refs_are_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    let (bare, _) = pack_repo(dir.path());
+    let root = repo(dir.path(), "repo");
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(["scaffold", "-p", "claude", "--from"])
+        .arg(format!("git+file://{}#v9", bare.display()))
+        .arg("--dest")
+        .arg(&root)
+        .env("AI_DLC_CACHE_DIR", dir.path().join("cache"))
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("has no branch, tag or commit 'v9'"),
+        "{stderr}"
+    );
+    assert!(!root.join(".claude").exists());
+}
@@ -0,0 +1,118 @@
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// The fake server from `examples/`, built next to the CLI under test.
+fn fake_server() -> &'static Path {
+    static SERVER: OnceLock<PathBuf> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        let bin = Path::new(env!("CARGO_BIN_EXE_ai-dlc-cli"));
+        let server = bin
+            .parent()
+            .unwrap()
+            .join("examples")
+            .join(format!("fake_mcp_server{}", std::env::consts::EXE_SUFFIX));
+        if !server.exists() {
+            let status = Command::new(env!("CARGO"))
+                .args(["build", "--example", "fake_mcp_server"])
+                .status()
+                .expect("failed to run cargo");
+            assert!(status.success(), "failed to build the fake MCP server");
+        }
+        server
+    })
+}
+
+/// Probes a single server started with `command` and `args`, returning its
+/// JSON report.
+fn probe(command: &str, args: &[&str]) -> Value {
+    let dir = tempfile::tempdir().unwrap();
+    let config = format!(
+        "[mcp.servers.fake]\ncommand = {:?}\nargs = {:?}\n",
+        command, args
+    );
+    std::fs::write(dir.path().join("ai-dlc.toml"), config).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args([
+            "mcp",
+            "probe",
+            "--timeout",
+            "2",
+            "--format",
+            "json",
+            "--dest",
+        ])
+        .arg(dir.path())
+        .output()
+        .expect("failed to run ai-dlc-cli");
+    let reports: Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|err| {
+        panic!(
+            "probe printed no report ({err}): {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+    });
+    let report = reports[0].clone();
+    assert_eq!(
+        output.status.success(),
+        report["status"] == "ok",
+        "exit status does not match the report: {report}"
+    );
+    report
+}
+
+fn probe_fake(mode: &[&str]) -> Value {
+    probe(fake_server().to_str().unwrap(), mode)
+}
+
+#[test]
+fn healthy_server_lists_its_tools() {
+    let report = probe_fake(&[]);
+    assert_eq!(report["status"], "ok", "{report}");
+    assert_eq!(report["server"], "fake-mcp-server 0.1.0");
+    assert_eq!(report["tools"], serde_json::json!(["echo", "add"]));
+    assert_eq!(report["prompts"], serde_json::json!(["greet"]));
+    assert_eq!(report["resources"], serde_json::json!(["readme"]));
+}
+
+#[test]
+fn chatty_stderr_does_not_stall_the_server() {
+    let report = probe_fake(&["--noisy"]);
+    assert_eq!(report["status"], "ok", "{report}");
+    assert_eq!(report["tools"], serde_json::json!(["echo", "add"]));
+}
+
+#[test]
+fn hanging_server_times_out() {
+    let report = probe_fake(&["--hang"]);
+    assert_eq!(report["failure"], "timeout", "{report}");
+}
+
+#[test]
+fn garbage_on_stdout_is_a_protocol_failure() {
+    let report = probe_fake(&["--garbage"]);
+    assert_eq!(report["failure"], "protocol", "{report}");
+}
+
+#[test]
+fn error_answer_is_a_protocol_failure() {
+    let report = probe_fake(&["--error"]);
+    assert_eq!(report["failure"], "protocol", "{report}");
+    let error = report["error"].as_str().unwrap();
+    assert!(error.contains("missing API key"), "{error}");
+}
+
+#[test]
+fn early_exit_reports_stderr() {
+    let report = probe_fake(&["--exit"]);
+    assert_eq!(report["failure"], "exited", "{report}");
+    let error = report["error"].as_str().unwrap();
+    assert!(error.contains("configuration file not found"), "{error}");
+}
+
+#[test]
+fn missing_binary_is_reported() {
+    let report = probe("ai-dlc-no-such-mcp-server", &[]);
+    assert_eq!(report["failure"], "missing_binary", "{report}");
+}
@@ -0,0 +1,88 @@
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+/// A repository and a template directory adding `agents/<name>` with
+/// `contents` to the Claude templates.
+fn setup(name: &str, contents: &str) -> (tempfile::TempDir, tempfile::TempDir) {
+    let repo = tempfile::tempdir().unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+    let templates = tempfile::tempdir().unwrap();
+    let agents = templates.path().join("claude/agents");
+    std::fs::create_dir_all(&agents).unwrap();
+    std::fs::write(agents.join(name), contents).unwrap();
+    (repo, templates)
+}
+
+fn scaffold(dest: &Path, templates: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args([
+            "scaffold",
+            "-p",
+            "claude",
+            "-g",
+            "agents",
+            "--templates-dir",
+        ])
+        .arg(templates)
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn variables_render_into_paths_and_contents() {
+    let (repo, templates) = setup("{{ team }}.md.jinja", "Owned by {{ team }}.\n");
+    let output = scaffold(repo.path(), templates.path(), &["--var", "team=payments"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let agent = std::fs::read_to_string(repo.path().join(".claude/agents/payments.md")).unwrap();
+    assert_eq!(agent, "Owned by payments.\n");
+}
+
+#[test]
+fn undefined_variable_names_the_template_and_the_fix() {
+    let (repo, templates) = setup("owner.md.jinja", "Owned by {{ team }}.\n");
+    let output = scaffold(repo.path(), templates.path(), &[]);
+    assert!(!output.status.success());
+    let stderr = stderr(&output);
+    assert!(
+        stderr.contains("references undefined template variable 'team'"),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--var team=<value>"), "{stderr}");
+    assert!(!repo.path().join(".claude").exists());
+}
+
+#[test]
+fn rendered_paths_may_not_leave_the_destination() {
+    let (repo, templates) = setup("{{ team }}.md", "# Escaped\n");
+    let output = scaffold(
+        repo.path(),
+        templates.path(),
+        &["--var", "team=../../../escaped"],
+    );
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("outside the destination"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!repo.path().join("escaped.md").exists());
+    assert!(!repo.path().parent().unwrap().join("escaped.md").exists());
+
+    let (repo, templates) = setup("{{ \"..\" }}", "# Escaped\n");
+    let output = scaffold(repo.path(), templates.path(), &[]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("outside the destination"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!repo.path().join(".ai-dlc").exists());
+}
@@ -0,0 +1,243 @@
+use serde_json::Value;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+const LOCAL: &str = "# Hand-written notes\n";
+
+/// A repository with a hand-written CLAUDE.md that differs from the template.
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    std::fs::write(dir.path().join("CLAUDE.md"), LOCAL).unwrap();
+    dir
+}
+
+fn scaffold(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(["scaffold", "-p", "claude"])
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+fn read(dest: &Path, path: &str) -> Option<String> {
+    std::fs::read_to_string(dest.join(path)).ok()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn dry_run_prints_the_plan_and_writes_nothing() {
+    let dir = repo();
+    let output = scaffold(dir.path(), &["--dry-run"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("skip       CLAUDE.md"), "{stdout}");
+    assert!(
+        stdout.contains("create     .claude/agents/example.md"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("(on conflict: skip)"), "{stdout}");
+    assert!(!dir.path().join(".claude").exists());
+    assert!(!dir.path().join(".ai-dlc").exists());
+
+    let output = scaffold(
+        dir.path(),
+        &[
+            "--dry-run",
+            "--plan-format",
+            "json",
+            "--on-conflict",
+            "backup",
+        ],
+    );
+    assert!(output.status.success());
+    let plan: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(plan["on_conflict"], "backup");
+    let claude = plan["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|file| file["path"] == "CLAUDE.md")
+        .unwrap();
+    assert_eq!(claude["action"], "backup");
+    assert_eq!(plan["summary"]["backup"], 1);
+    assert_eq!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+}
+
+#[test]
+fn skip_keeps_the_existing_file() {
+    let dir = repo();
+    assert!(scaffold(dir.path(), &[]).status.success());
+    assert_eq!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+    assert!(dir.path().join(".claude/agents/example.md").exists());
+}
+
+#[test]
+fn overwrite_replaces_the_existing_file() {
+    let dir = repo();
+    let output = scaffold(dir.path(), &["--on-conflict", "overwrite"]);
+    assert!(output.status.success());
+    assert_ne!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+    assert!(read(dir.path(), "CLAUDE.md.orig").is_none());
+}
+
+#[test]
+fn backup_numbers_repeated_backups() {
+    let dir = repo();
+    assert!(
+        scaffold(dir.path(), &["--on-conflict", "backup"])
+            .status
+            .success()
+    );
+    let template = read(dir.path(), "CLAUDE.md").unwrap();
+    assert_ne!(template, LOCAL);
+    assert_eq!(read(dir.path(), "CLAUDE.md.orig").as_deref(), Some(LOCAL));
+
+    let edited = "# Edited again\n";
+    std::fs::write(dir.path().join("CLAUDE.md"), edited).unwrap();
+    assert!(
+        scaffold(dir.path(), &["--on-conflict", "backup"])
+            .status
+            .success()
+    );
+    assert_eq!(read(dir.path(), "CLAUDE.md"), Some(template));
+    assert_eq!(read(dir.path(), "CLAUDE.md.orig").as_deref(), Some(LOCAL));
+    assert_eq!(
+        read(dir.path(), "CLAUDE.md.orig.1").as_deref(),
+        Some(edited)
+    );
+}
+
+#[test]
+fn fail_writes_nothing() {
+    let dir = repo();
+    let output = scaffold(dir.path(), &["--on-conflict", "fail"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("CLAUDE.md"), "{stderr}");
+    assert_eq!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+    assert!(!dir.path().join(".claude").exists());
+}
+
+#[test]
+fn prompt_refuses_without_a_terminal() {
+    let dir = repo();
+    let output = scaffold(dir.path(), &["--on-conflict", "prompt"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("interactive terminal"), "{stderr}");
+    assert_eq!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+    assert!(!dir.path().join(".claude").exists());
+}
+
+#[test]
+fn corrupt_lockfile_stops_before_writing() {
+    let dir = repo();
+    std::fs::create_dir(dir.path().join(".ai-dlc")).unwrap();
+    std::fs::write(dir.path().join(".ai-dlc/lock.json"), "{ not json").unwrap();
+    let output = scaffold(dir.path(), &[]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Failed to parse lockfile"), "{stderr}");
+    assert!(!dir.path().join(".claude").exists());
+
+    // Deleting the record starts over with every file unmanaged.
+    std::fs::remove_file(dir.path().join(".ai-dlc/lock.json")).unwrap();
+    assert!(scaffold(dir.path(), &[]).status.success());
+    assert!(dir.path().join(".claude/agents/example.md").exists());
+    assert_eq!(read(dir.path(), "CLAUDE.md").as_deref(), Some(LOCAL));
+}
+
+/// Runs `scaffold --generic` from inside `root`, which the documents take
+/// their project root from.
+fn generic(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .arg("scaffold")
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+#[test]
+fn generic_documents_default_to_the_docs_folder() {
+    let dir = repo();
+    let output = generic(dir.path(), &["--generic", "prd", "--var", "title=Checkout"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let prd = read(dir.path(), "docs/PRD.md").unwrap();
+    assert!(
+        prd.starts_with("# Checkout — Product Requirements\n"),
+        "{prd}"
+    );
+    // Generic documents belong to the project and are not locked.
+    assert!(!dir.path().join(".ai-dlc").exists());
+}
+
+#[test]
+fn generic_documents_can_go_outside_the_project() {
+    let dir = repo();
+    let outside = tempfile::tempdir().unwrap();
+    let output = generic(
+        dir.path(),
+        &[
+            "--generic",
+            "adr",
+            "--var",
+            "title=Use SQLite",
+            "--dest",
+            outside.path().to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(outside.path().join("adr.md").exists());
+    assert!(!dir.path().join("docs").exists());
+}
+
+#[test]
+fn unknown_generic_document_lists_the_available_ones() {
+    let dir = repo();
+    let output = generic(dir.path(), &["--generic", "roadmap"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output)
+            .contains("Generic document 'roadmap' not found; available: adr, design-spec, prd."),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!dir.path().join("docs").exists());
+}
+
+#[test]
+fn generic_paths_cannot_escape_the_docs_folder() {
+    let dir = repo();
+    let templates = tempfile::tempdir().unwrap();
+    let notes = templates.path().join("generic/notes/{{ folder }}");
+    std::fs::create_dir_all(&notes).unwrap();
+    std::fs::write(notes.join("notes.md"), "# Notes\n").unwrap();
+
+    let output = generic(
+        dir.path(),
+        &[
+            "--generic",
+            "notes",
+            "--templates-dir",
+            templates.path().to_str().unwrap(),
+            "--var",
+            "folder=../..",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("outside the destination"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(!dir.path().parent().unwrap().join("notes.md").exists());
+}
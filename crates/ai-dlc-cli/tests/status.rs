@@ -0,0 +1,125 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run ai-dlc-cli")
+}
+
+/// A repository scaffolded for Claude.
+fn scaffolded() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    let output = ai_dlc(dir.path(), &["scaffold", "-p", "claude"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    dir
+}
+
+/// The state of each reported file, and whether `status --check` passed.
+fn status(dest: &Path, args: &[&str]) -> (BTreeMap<String, String>, bool) {
+    let mut full = vec!["status", "--check", "--format", "json"];
+    full.extend_from_slice(args);
+    let output = ai_dlc(dest, &full);
+    let files: Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|err| {
+        panic!(
+            "status printed no report ({err}): {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+    });
+    let states = files
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| {
+            (
+                file["path"].as_str().unwrap().to_string(),
+                file["state"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    (states, output.status.success())
+}
+
+fn state<'a>(states: &'a BTreeMap<String, String>, path: &str) -> Option<&'a str> {
+    states.get(path).map(String::as_str)
+}
+
+#[test]
+fn fresh_scaffold_is_unmodified() {
+    let dir = scaffolded();
+    let (states, passed) = status(dir.path(), &[]);
+    assert!(passed, "{states:?}");
+    assert!(
+        states.values().all(|state| state == "unmodified"),
+        "{states:?}"
+    );
+    assert_eq!(state(&states, "CLAUDE.md"), Some("unmodified"));
+
+    let output = ai_dlc(dir.path(), &["status"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("0 modified, 0 missing, 0 untracked, 0 outdated"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn modified_and_missing_files_fail_the_check() {
+    let dir = scaffolded();
+    std::fs::write(dir.path().join(".claude/agents/example.md"), "# Edited\n").unwrap();
+    let (states, passed) = status(dir.path(), &[]);
+    assert!(!passed);
+    assert_eq!(
+        state(&states, ".claude/agents/example.md"),
+        Some("modified")
+    );
+
+    std::fs::remove_file(dir.path().join("CLAUDE.md")).unwrap();
+    let (states, passed) = status(dir.path(), &[]);
+    assert!(!passed);
+    assert_eq!(state(&states, "CLAUDE.md"), Some("missing"));
+}
+
+#[test]
+fn extra_files_are_untracked_but_user_local_files_are_not() {
+    let dir = scaffolded();
+    let claude = dir.path().join(".claude");
+    std::fs::write(claude.join("settings.local.json"), "{}\n").unwrap();
+    let (states, passed) = status(dir.path(), &[]);
+    assert!(passed, "{states:?}");
+    assert_eq!(state(&states, ".claude/settings.local.json"), None);
+
+    std::fs::write(claude.join("agents/mine.md"), "# Mine\n").unwrap();
+    let (states, passed) = status(dir.path(), &[]);
+    assert!(!passed);
+    assert_eq!(state(&states, ".claude/agents/mine.md"), Some("untracked"));
+}
+
+#[test]
+fn newer_templates_make_files_outdated() {
+    let dir = scaffolded();
+    let templates = tempfile::tempdir().unwrap();
+    let agents = templates.path().join("claude/agents");
+    std::fs::create_dir_all(&agents).unwrap();
+    std::fs::write(agents.join("example.md"), "# A newer example\n").unwrap();
+
+    let dir_arg = templates.path().to_str().unwrap();
+    let (states, passed) = status(dir.path(), &["--templates-dir", dir_arg]);
+    assert!(!passed);
+    assert_eq!(
+        state(&states, ".claude/agents/example.md"),
+        Some("outdated")
+    );
+    assert_eq!(state(&states, "CLAUDE.md"), Some("unmodified"));
+}
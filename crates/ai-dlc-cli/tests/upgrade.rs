@@ -0,0 +1,116 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn ai_dlc(dest: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_ai-dlc-cli"))
+        .args(args)
+        .arg("--dest")
+        .arg(dest)
+        .output()
+        .expect("failed to run ai-dlc-cli");
+    assert!(
+        output.status.success(),
+        "ai-dlc-cli {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn upgrade_leaves_files_skipped_by_scaffold_alone() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir(root.join(".git")).unwrap();
+    let mine = "# Hand-written notes\n\nKeep these.\n";
+    std::fs::write(root.join("CLAUDE.md"), mine).unwrap();
+
+    ai_dlc(root, &["scaffold", "-p", "claude"]);
+    assert_eq!(
+        std::fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+        mine
+    );
+
+    let output = ai_dlc(root, &["upgrade"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("unmanaged  CLAUDE.md"),
+        "unexpected report:\n{}",
+        stdout
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("CLAUDE.md")).unwrap(),
+        mine
+    );
+
+    let lock = std::fs::read_to_string(root.join(".ai-dlc/lock.json")).unwrap();
+    assert!(
+        !lock.contains("\"CLAUDE.md\""),
+        "CLAUDE.md was locked:\n{}",
+        lock
+    );
+}
+
+/// Scaffolds an overlay provider with a single notes file, returning the
+/// project and the overlay so tests can edit either side before upgrading.
+fn scaffold_notes(notes: &str) -> (tempfile::TempDir, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join(".git")).unwrap();
+    let templates = tempfile::tempdir().unwrap();
+    let acme = templates.path().join("acme");
+    std::fs::create_dir_all(&acme).unwrap();
+    std::fs::write(
+        acme.join("provider.toml"),
+        "name = \"acme\"\n[[mappings]]\nsource = \"NOTES.md\"\ndest = \"NOTES.md\"\n",
+    )
+    .unwrap();
+    std::fs::write(acme.join("NOTES.md"), notes).unwrap();
+    let overlay = templates.path().to_str().unwrap();
+    ai_dlc(
+        dir.path(),
+        &["scaffold", "-p", "acme", "--templates-dir", overlay],
+    );
+    (dir, templates)
+}
+
+#[test]
+fn text_merges_and_conflicts_print_their_diff() {
+    let (dir, templates) = scaffold_notes("one\ntwo\nthree\n");
+    let overlay = templates.path().to_str().unwrap();
+    std::fs::write(dir.path().join("NOTES.md"), "one (mine)\ntwo\nthree\n").unwrap();
+    std::fs::write(
+        templates.path().join("acme/NOTES.md"),
+        "one\ntwo\nthree (new)\n",
+    )
+    .unwrap();
+
+    let output = ai_dlc(dir.path(), &["upgrade", "--templates-dir", overlay]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("merge      NOTES.md\n--- a/NOTES.md\n+++ b/NOTES.md\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("\n-three\n+three (new)\n"), "{stdout}");
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("NOTES.md")).unwrap(),
+        "one (mine)\ntwo\nthree (new)\n"
+    );
+
+    let (dir, templates) = scaffold_notes("one\ntwo\nthree\n");
+    let overlay = templates.path().to_str().unwrap();
+    std::fs::write(dir.path().join("NOTES.md"), "one (mine)\ntwo\nthree\n").unwrap();
+    std::fs::write(
+        templates.path().join("acme/NOTES.md"),
+        "one (new)\ntwo\nthree\n",
+    )
+    .unwrap();
+
+    let output = ai_dlc(dir.path(), &["upgrade", "--templates-dir", overlay]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("conflict   NOTES.md\n--- a/NOTES.md\n+++ b/NOTES.md\n"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("\n+<<<<<<< "), "{stdout}");
+    assert!(stdout.contains("\n+one (new)\n"), "{stdout}");
+}